@@ -2,14 +2,23 @@ use env_logger;
 use log::error;
 use clap::Parser;
 use pbo_tools::cli::args::Cli;
-use pbo_tools::cli::CliProcessor;
+use pbo_tools::cli::{load_config, CliProcessor};
 use pbo_tools::core::constants::DEFAULT_TIMEOUT;
 
 fn main() {
     env_logger::init();
     
     let cli = Cli::parse();
-    let processor = CliProcessor::new(DEFAULT_TIMEOUT);
+    let processor = match &cli.config {
+        Some(path) => match load_config(path) {
+            Ok(config) => CliProcessor::with_config(DEFAULT_TIMEOUT, config),
+            Err(e) => {
+                error!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        None => CliProcessor::new(DEFAULT_TIMEOUT),
+    };
     
     if let Err(e) = processor.process_command(cli.command) {
         error!("{}", e);
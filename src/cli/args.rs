@@ -1,5 +1,5 @@
 use std::path::PathBuf;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -10,14 +10,19 @@ pub struct Cli {
     /// Timeout in seconds for operations
     #[arg(short, long, default_value = "30")]
     pub timeout: u32,
+
+    /// TOML file with bin mappings, bad indicators and other settings
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// List contents of PBO file
     List {
-        /// Path to PBO file
-        pbo_path: PathBuf,
+        /// Paths to PBO files
+        #[arg(required = true)]
+        pbo_paths: Vec<PathBuf>,
 
         /// Use brief directory-style output listing
         #[arg(short, long)]
@@ -26,20 +31,27 @@ pub enum Commands {
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
     /// Extract PBO file contents
     Extract {
-        /// Path to PBO file
-        pbo_path: PathBuf,
+        /// Paths to PBO files
+        #[arg(required = true)]
+        pbo_paths: Vec<PathBuf>,
 
-        /// Output directory
+        /// Output directory. With several PBOs each one is extracted into a
+        /// folder named after it inside this directory.
         output_dir: PathBuf,
 
         /// Optional filter for specific files
         #[arg(short, long)]
         filter: Option<String>,
 
-        /// Keep PBO name in output path
+        /// Extract the PBO into a folder named after it inside the output
+        /// directory, as extractpbo does by default. Always done for several PBOs.
         #[arg(short, long)]
         keep_pbo_name: bool,
 
@@ -50,5 +62,42 @@ pub enum Commands {
         /// Don't treat warnings as errors
         #[arg(short = 'w', long)]
         ignore_warnings: bool,
+
+        /// Keep binarized files such as config.bin next to their converted copies
+        #[arg(long)]
+        keep_bin: bool,
+    },
+    /// Print a single file from a PBO, e.g. its config.cpp
+    Cat {
+        /// Path to the PBO file
+        pbo_path: PathBuf,
+
+        /// Path of the file inside the PBO
+        internal_path: String,
+
+        /// Write binary files to stdout as raw bytes instead of failing
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Check that a PBO is well-formed without extracting it: parse its header
+    /// and verify the stored SHA1
+    Validate {
+        /// Path to the PBO file
+        pbo_path: PathBuf,
     },
+    /// Remove temp directories left behind by crashed runs
+    Cleanup {
+        /// Only remove directories older than this many hours
+        #[arg(long, default_value = "24")]
+        max_age_hours: u64,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// JSON object with the prefix and file entries
+    Json,
 }
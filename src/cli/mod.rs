@@ -1,11 +1,136 @@
 pub mod args;
 pub mod commands;
 
-use log::debug;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use log::{debug, error};
 use crate::core::api::{PboApi, PboApiOps};
-use crate::error::types::{Result, PboError};
-use crate::extract::ExtractOptions;
-use self::args::Commands;
+use crate::core::config::PboConfig;
+use crate::core::reader::{NativePboReader, PboEntry};
+use crate::core::extensions::archive_label;
+use crate::error::types::{Result, PboError, ExtractError, FileSystemError};
+use crate::extract::{ExtractOptions, ExtractResult};
+use crate::fs::{process_binary_files_with_options, TempFileManager};
+use self::args::{Commands, OutputFormat};
+
+/// Listed entries with the packing method filled in from the PBO header, which
+/// extractpbo's listing does not include. Entries are left as they are when the
+/// header cannot be read.
+fn entries_with_packing(pbo_path: &Path, result: &ExtractResult) -> Vec<PboEntry> {
+    let mut entries = result.get_entries();
+    let Ok(reader) = NativePboReader::open(pbo_path) else {
+        return entries;
+    };
+
+    let headers = reader.header_entries();
+    for entry in entries.iter_mut().filter(|entry| entry.packing_method.is_none()) {
+        let path = entry.path.replace('/', "\\");
+        entry.packing_method = headers.iter()
+            .find(|header| header.path.replace('/', "\\") == path)
+            .map(|header| header.packing_method);
+    }
+    entries
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct ListingOutput {
+    /// Only set in a listing of several PBOs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pbo: Option<PathBuf>,
+    prefix: Option<String>,
+    files: Vec<PboEntry>,
+}
+
+#[cfg(feature = "serde")]
+impl ListingOutput {
+    fn new(pbo_path: &Path, result: &ExtractResult) -> Self {
+        Self {
+            pbo: None,
+            prefix: result.get_prefix(),
+            files: entries_with_packing(pbo_path, result),
+        }
+    }
+}
+
+/// Render a listing as a JSON object holding the prefix and the file entries
+#[cfg(feature = "serde")]
+pub fn render_listing_json(pbo_path: &Path, result: &ExtractResult) -> Result<String> {
+    serde_json::to_string_pretty(&ListingOutput::new(pbo_path, result)).map_err(|e| PboError::Encoding {
+        context: format!("Failed to serialize listing as JSON: {}", e),
+        path: pbo_path.to_path_buf(),
+    })
+}
+
+/// Render the listings of several PBOs as one JSON array of objects holding
+/// each PBO's path, prefix and file entries
+#[cfg(feature = "serde")]
+pub fn render_listings_json(listings: &[(PathBuf, ExtractResult)]) -> Result<String> {
+    let outputs: Vec<ListingOutput> = listings.iter()
+        .map(|(pbo_path, result)| ListingOutput {
+            pbo: Some(pbo_path.clone()),
+            ..ListingOutput::new(pbo_path, result)
+        })
+        .collect();
+    serde_json::to_string_pretty(&outputs).map_err(|e| PboError::Encoding {
+        context: format!("Failed to serialize listing as JSON: {}", e),
+        path: PathBuf::new(),
+    })
+}
+
+/// Load the configuration file given with `--config`
+#[cfg(feature = "config-file")]
+pub fn load_config(path: &Path) -> Result<PboConfig> {
+    PboConfig::from_toml_file(path)
+}
+
+#[cfg(not(feature = "config-file"))]
+pub fn load_config(path: &Path) -> Result<PboConfig> {
+    Err(PboError::ValidationFailed(
+        "Config files require the config-file feature".to_string()
+    ))
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn render_listing_json(pbo_path: &Path, _result: &ExtractResult) -> Result<String> {
+    Err(PboError::ValidationFailed(
+        "JSON output requires the serde feature".to_string()
+    ))
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn render_listings_json(_listings: &[(PathBuf, ExtractResult)]) -> Result<String> {
+    Err(PboError::ValidationFailed(
+        "JSON output requires the serde feature".to_string()
+    ))
+}
+
+/// Folder inside `output_dir` named after the PBO, e.g. `output/mirrorform`
+/// for `mirrorform.pbo`
+fn pbo_name_dir(output_dir: &Path, pbo_path: &Path) -> PathBuf {
+    output_dir.join(pbo_path.file_stem().unwrap_or_default())
+}
+
+/// Folder for each PBO of a multi-PBO extraction, see `pbo_name_dir`. PBOs
+/// sharing a name, such as `a/mod.pbo` and `b/mod.pbo`, get a numbered suffix
+/// so they are not extracted on top of each other.
+fn pbo_output_dirs(output_dir: &Path, pbo_paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::with_capacity(pbo_paths.len());
+    for pbo_path in pbo_paths {
+        let base = pbo_name_dir(output_dir, pbo_path);
+        let mut dir = base.clone();
+        let mut index = 1;
+        while dirs.contains(&dir) {
+            index += 1;
+            let mut name = base.file_name().unwrap_or_default().to_os_string();
+            name.push(format!("_{}", index));
+            dir = base.with_file_name(name);
+        }
+        dirs.push(dir);
+    }
+    dirs
+}
 
 pub struct CliProcessor {
     api: PboApi,
@@ -13,10 +138,15 @@ pub struct CliProcessor {
 
 impl CliProcessor {
     pub fn new(timeout: u32) -> Self {
+        Self::with_config(timeout, PboConfig::default())
+    }
+
+    pub fn with_config(timeout: u32, config: PboConfig) -> Self {
         debug!("Creating new CliProcessor with timeout: {} seconds", timeout);
         Self {
             api: PboApi::builder()
                 .with_timeout(timeout)
+                .with_config(config)
                 .build(),
         }
     }
@@ -24,37 +154,22 @@ impl CliProcessor {
     pub fn process_command(&self, command: Commands) -> Result<()> {
         debug!("Processing command: {:?}", command);
         match command {
-            Commands::List { pbo_path, brief, verbose } => {
-                debug!("Listing contents of PBO: {}", pbo_path.display());
-                let options = ExtractOptions {
-                    no_pause: true,
-                    warnings_as_errors: true,
-                    brief_listing: brief,
-                    verbose,
-                    ..Default::default()
-                };
-                
-                self.api.list_with_options(&pbo_path, options)
-                    .and_then(|result| {
-                        if result.is_success() {
-                            println!("Files in PBO:");
-                            for file in result.get_file_list() {
-                                println!("  {}", file);
-                            }
-                            Ok(())
-                        } else {
-                            Err(PboError::Extraction(result.get_error_message()
-                                .map(|msg| crate::error::types::ExtractError::CommandFailed {
-                                    cmd: "extractpbo".to_string(),
-                                    reason: msg,
-                                })
-                                .unwrap_or_else(|| crate::error::types::ExtractError::NoFiles)))
-                        }
-                    })
+            Commands::List { pbo_paths, brief, verbose, format: OutputFormat::Json } if pbo_paths.len() > 1 => {
+                // One JSON document for all PBOs, holding those that could be listed
+                let mut listings = Vec::new();
+                let listed = self.for_each_pbo(&pbo_paths, false, |pbo_path| {
+                    listings.push((pbo_path.to_path_buf(), self.list_result(pbo_path, brief, verbose)?));
+                    Ok(())
+                });
+                println!("{}", render_listings_json(&listings)?);
+                listed
+            }
+            Commands::List { pbo_paths, brief, verbose, format } => {
+                self.for_each_pbo(&pbo_paths, true, |pbo_path| {
+                    self.list_pbo(pbo_path, brief, verbose, format)
+                })
             }
-            Commands::Extract { pbo_path, output_dir, filter, keep_pbo_name, verbose, ignore_warnings } => {
-                debug!("Extracting from PBO: {} to {}", pbo_path.display(), output_dir.display());
-                debug!("Using filter: {:?}", filter);
+            Commands::Extract { pbo_paths, output_dir, filter, keep_pbo_name, verbose, ignore_warnings, keep_bin } => {
                 debug!("Current directory: {:?}", std::env::current_dir().unwrap_or_default());
                 
                 // Ensure output directory exists
@@ -66,40 +181,213 @@ impl CliProcessor {
 
                 debug!("Created output directory: {}", output_dir.display());
 
-                let options = ExtractOptions {
-                    no_pause: true,
-                    warnings_as_errors: !ignore_warnings,
-                    file_filter: filter,
-                    verbose,
-                    ..Default::default()
+                // Several PBOs each get their own folder, so their files don't mix
+                // and --keep-bin only converts the files of one PBO at a time
+                let pbo_output_dirs = if pbo_paths.len() > 1 {
+                    pbo_output_dirs(&output_dir, &pbo_paths)
+                } else if keep_pbo_name {
+                    pbo_paths.iter().map(|pbo_path| pbo_name_dir(&output_dir, pbo_path)).collect()
+                } else {
+                    vec![output_dir.clone()]
                 };
+                let mut pbo_output_dirs = pbo_output_dirs.into_iter();
 
-                let result = self.api.extract_with_options(&pbo_path, &output_dir, options);
-                debug!("Extract result: {:?}", result);
-                
-                result.and_then(|result| {
-                    if result.is_success() {
-                        println!("Extracted files:");
-                        for file in result.get_file_list() {
-                            println!("  {}", file);
-                        }
-                        if let Some(prefix) = result.get_prefix() {
-                            println!("\nPBO Prefix: {}", prefix);
-                        }
-                        Ok(())
-                    } else {
-                        debug!("Extraction failed: {}", result);
-                        Err(PboError::Extraction(result.get_error_message()
-                            .map(|msg| crate::error::types::ExtractError::CommandFailed {
-                                cmd: "extractpbo".to_string(),
-                                reason: msg,
-                            })
-                            .unwrap_or_else(|| crate::error::types::ExtractError::NoFiles)))
-                    }
+                self.for_each_pbo(&pbo_paths, true, |pbo_path| {
+                    let pbo_output_dir = pbo_output_dirs.next().unwrap_or_else(|| output_dir.clone());
+                    self.extract_pbo(pbo_path, &pbo_output_dir, filter.clone(), verbose, ignore_warnings, keep_bin)
                 })
             }
+            Commands::Cat { pbo_path, internal_path, raw } => {
+                self.cat_file(&pbo_path, &internal_path, raw)
+            }
+            Commands::Validate { pbo_path } => {
+                self.validate_pbo(&pbo_path)
+            }
+            Commands::Cleanup { max_age_hours } => {
+                debug!("Purging orphaned temp directories older than {} hours", max_age_hours);
+                let removed = TempFileManager::new()
+                    .with_max_age(Duration::from_secs(max_age_hours * 60 * 60))
+                    .purge_orphaned()?;
+                println!("Removed {} orphaned temp directories", removed);
+                Ok(())
+            }
         }
     }
+
+    /// Run `run` for every PBO, printing a header before each when there are
+    /// several. Failures are reported and the remaining PBOs still processed;
+    /// the result is an error if any PBO failed.
+    fn for_each_pbo(&self, pbo_paths: &[PathBuf], headers: bool, mut run: impl FnMut(&Path) -> Result<()>) -> Result<()> {
+        if let [pbo_path] = pbo_paths {
+            return run(pbo_path);
+        }
+
+        let mut failed = 0;
+        for (index, pbo_path) in pbo_paths.iter().enumerate() {
+            if headers {
+                if index > 0 {
+                    println!();
+                }
+                println!("==> {} <==", pbo_path.display());
+            }
+            if let Err(e) = run(pbo_path) {
+                error!("{}: {}", pbo_path.display(), e);
+                failed += 1;
+            }
+        }
+
+        if failed > 0 {
+            return Err(PboError::ValidationFailed(format!(
+                "{} of {} PBOs failed", failed, pbo_paths.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// List the PBO, turning an unsuccessful listing into an error
+    fn list_result(&self, pbo_path: &Path, brief: bool, verbose: bool) -> Result<ExtractResult> {
+        debug!("Listing contents of PBO: {}", pbo_path.display());
+        let options = ExtractOptions {
+            no_pause: true,
+            warnings_as_errors: true,
+            brief_listing: brief,
+            verbose,
+            ..Default::default()
+        };
+
+        let result = self.api.list_with_options(pbo_path, options)?;
+        if result.is_success() {
+            return Ok(result);
+        }
+        Err(PboError::Extraction(result.get_error_message()
+            .map(|msg| crate::error::types::ExtractError::CommandFailed {
+                cmd: "extractpbo".to_string(),
+                reason: msg,
+            })
+            .unwrap_or_else(|| crate::error::types::ExtractError::NoFiles)))
+    }
+
+    fn list_pbo(&self, pbo_path: &Path, brief: bool, verbose: bool, format: OutputFormat) -> Result<()> {
+        let result = self.list_result(pbo_path, brief, verbose)?;
+        if format == OutputFormat::Json {
+            println!("{}", render_listing_json(pbo_path, &result)?);
+            return Ok(());
+        }
+
+        println!("Files in {}:", archive_label(pbo_path));
+        if brief {
+            for file in result.get_file_list() {
+                println!("  {}", file);
+            }
+        } else {
+            for entry in entries_with_packing(pbo_path, &result) {
+                println!("  {:<10} {}", entry.packing_label(), entry.path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Print `internal_path` as text. Binary files are written out unchanged
+    /// with `raw`, and are an error otherwise.
+    fn cat_file(&self, pbo_path: &Path, internal_path: &str, raw: bool) -> Result<()> {
+        debug!("Printing {} from {}", internal_path, pbo_path.display());
+        match self.api.extract_text_file(pbo_path, internal_path) {
+            Ok(text) => {
+                print!("{}", text);
+                Ok(())
+            }
+            Err(PboError::Encoding { .. }) if raw => {
+                let bytes = self.api.extract_file_to_memory(pbo_path, internal_path)?;
+                std::io::stdout().write_all(&bytes).map_err(|e| PboError::FileSystem(FileSystemError::Write {
+                    path: PathBuf::from("<stdout>"),
+                    reason: e.to_string(),
+                }))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parse the PBO header natively and verify the stored SHA1, printing the
+    /// prefix, entry count and checksum state. Any problem is returned as an
+    /// error, so the process exits nonzero.
+    fn validate_pbo(&self, pbo_path: &Path) -> Result<()> {
+        debug!("Validating PBO: {}", pbo_path.display());
+        let mut reader = NativePboReader::open(pbo_path)?;
+        // Fails when the header lists more data than the file holds
+        reader.total_size()?;
+
+        println!("Prefix: {}", reader.prefix().as_deref().unwrap_or("(none)"));
+        println!("Entries: {}", reader.header_entries().len());
+        let checksum = reader.verify_checksum();
+        match &checksum {
+            Ok(true) => println!("SHA1: matches"),
+            Ok(false) => println!("SHA1: not stored"),
+            Err(PboError::Extraction(ExtractError::ChecksumFailed(_))) => println!("SHA1: MISMATCH"),
+            Err(_) => {}
+        }
+        checksum?;
+
+        println!("{} OK", archive_label(pbo_path));
+        Ok(())
+    }
+
+    fn extract_pbo(
+        &self,
+        pbo_path: &Path,
+        output_dir: &Path,
+        filter: Option<String>,
+        verbose: bool,
+        ignore_warnings: bool,
+        keep_bin: bool,
+    ) -> Result<()> {
+        debug!("Extracting from PBO: {} to {}", pbo_path.display(), output_dir.display());
+        debug!("Using filter: {:?}", filter);
+
+        let options = ExtractOptions {
+            no_pause: true,
+            warnings_as_errors: !ignore_warnings,
+            file_filter: filter,
+            verbose,
+            ..Default::default()
+        };
+
+        // In verbose mode print extractpbo's output live instead of after the fact
+        let result = if verbose {
+            self.api.extract_with_callback(pbo_path, output_dir, options, |line| println!("{}", line))
+        } else {
+            self.api.extract_with_options(pbo_path, output_dir, options)
+        };
+        debug!("Extract result: {:?}", result);
+        
+        result.and_then(|result| {
+            if result.is_success() {
+                if !verbose {
+                    println!("Extracted files:");
+                    for file in result.get_file_list() {
+                        println!("  {}", file);
+                    }
+                }
+                if let Some(prefix) = result.get_prefix() {
+                    println!("\n{} Prefix: {}", archive_label(pbo_path), prefix);
+                }
+                if keep_bin {
+                    // Copy rather than rename so both forms end up side by side
+                    process_binary_files_with_options(output_dir, self.api.config(), true, &mut |path, index, total| {
+                        debug!("Converting {}/{}: {}", index, total, path.display());
+                    })?;
+                }
+                Ok(())
+            } else {
+                debug!("Extraction failed: {}", result);
+                Err(PboError::Extraction(result.get_error_message()
+                    .map(|msg| crate::error::types::ExtractError::CommandFailed {
+                        cmd: "extractpbo".to_string(),
+                        reason: msg,
+                    })
+                    .unwrap_or_else(|| crate::error::types::ExtractError::NoFiles)))
+            }
+        })
+    }
 }
 
 #[cfg(test)]
@@ -115,9 +403,10 @@ mod tests {
         let cli = CliProcessor::new(10);
         let test_pbo = test_utils::get_test_pbo_path();
         let result = cli.process_command(Commands::List { 
-            pbo_path: test_pbo,
+            pbo_paths: vec![test_pbo],
             brief: false,
             verbose: false,
+            format: OutputFormat::Text,
         });
         assert!(result.is_ok());
     }
@@ -130,16 +419,52 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         
         let result = cli.process_command(Commands::Extract { 
-            pbo_path: test_pbo,
+            pbo_paths: vec![test_pbo],
             output_dir: temp_dir.path().to_path_buf(),
             filter: None,
             keep_pbo_name: false,
             verbose: false,
             ignore_warnings: false,
+            keep_bin: false,
         });
         assert!(result.is_ok());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_cli_extract_keep_pbo_name() {
+        use std::os::unix::fs::PermissionsExt;
+
+        test_utils::setup();
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path().join("output");
+
+        // Stand-in for extractpbo writing one file to its destination, the last argument
+        let script = temp_dir.path().join("extractpbo");
+        std::fs::write(&script, "#!/bin/sh\nfor out; do :; done\necho 'class CfgPatches {};' > \"$out/config.cpp\"\necho 'config.cpp'\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let cli = CliProcessor {
+            api: PboApi::builder().with_timeout(10).with_extractpbo_path(script).build(),
+        };
+
+        let result = cli.process_command(Commands::Extract {
+            pbo_paths: vec![test_utils::get_test_pbo_path()],
+            output_dir: output_dir.clone(),
+            filter: None,
+            keep_pbo_name: true,
+            verbose: false,
+            ignore_warnings: false,
+            keep_bin: false,
+        });
+        assert!(result.is_ok(), "Extraction failed: {:?}", result);
+        assert_eq!(pbo_name_dir(&output_dir, &test_utils::get_test_pbo_path()), output_dir.join("mirrorform"));
+        let names: Vec<_> = std::fs::read_dir(&output_dir).unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["mirrorform"]);
+        assert!(std::fs::read_dir(output_dir.join("mirrorform")).unwrap().next().is_some());
+    }
+
     #[test]
     fn test_cli_with_invalid_paths() {
         test_utils::setup();
@@ -147,20 +472,185 @@ mod tests {
         let invalid_pbo = PathBuf::from("nonexistent.pbo");
         
         let result = cli.process_command(Commands::List { 
-            pbo_path: invalid_pbo.clone(),
+            pbo_paths: vec![invalid_pbo.clone()],
             brief: false,
             verbose: false,
+            format: OutputFormat::Text,
         });
         assert!(result.is_err());
 
         let result = cli.process_command(Commands::Extract { 
-            pbo_path: invalid_pbo,
+            pbo_paths: vec![invalid_pbo],
             output_dir: PathBuf::from("output"),
             filter: None,
             keep_pbo_name: false,
             verbose: false,
             ignore_warnings: false,
+            keep_bin: false,
         });
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_cli_multiple_pbos() {
+        test_utils::setup();
+        let cli = CliProcessor::new(30);
+
+        let result = cli.process_command(Commands::List {
+            pbo_paths: vec![PathBuf::from("nonexistent.pbo"), PathBuf::from("missing.pbo")],
+            brief: true,
+            verbose: false,
+            format: OutputFormat::Text,
+        });
+        match result {
+            Err(PboError::ValidationFailed(msg)) => assert_eq!(msg, "2 of 2 PBOs failed"),
+            other => panic!("Expected aggregated failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pbo_output_dirs() {
+        let output_dir = Path::new("out");
+        let pbo_paths = [
+            PathBuf::from("mods/a.pbo"),
+            PathBuf::from("other/a.pbo"),
+            PathBuf::from("b.pbo"),
+            PathBuf::from("a.xbo"),
+        ];
+        assert_eq!(pbo_output_dirs(output_dir, &pbo_paths), vec![
+            output_dir.join("a"),
+            output_dir.join("a_2"),
+            output_dir.join("b"),
+            output_dir.join("a_3"),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_multiple_pbo_paths() {
+        use clap::Parser;
+        use self::args::Cli;
+
+        let cli = Cli::try_parse_from(["pbo_tools", "extract", "a.pbo", "b.pbo", "out"]).unwrap();
+        match cli.command {
+            Commands::Extract { pbo_paths, output_dir, .. } => {
+                assert_eq!(pbo_paths, vec![PathBuf::from("a.pbo"), PathBuf::from("b.pbo")]);
+                assert_eq!(output_dir, PathBuf::from("out"));
+            }
+            other => panic!("Expected extract command, got {:?}", other),
+        }
+
+        assert!(Cli::try_parse_from(["pbo_tools", "list"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_cat_command() {
+        use clap::Parser;
+        use self::args::Cli;
+
+        let cli = Cli::try_parse_from(["pbo_tools", "cat", "a.pbo", "config.cpp", "--raw"]).unwrap();
+        match cli.command {
+            Commands::Cat { pbo_path, internal_path, raw } => {
+                assert_eq!(pbo_path, PathBuf::from("a.pbo"));
+                assert_eq!(internal_path, "config.cpp");
+                assert!(raw);
+            }
+            other => panic!("Expected cat command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cli_cat_missing_pbo() {
+        test_utils::setup();
+        let cli = CliProcessor::new(10);
+        let result = cli.process_command(Commands::Cat {
+            pbo_path: PathBuf::from("nonexistent.pbo"),
+            internal_path: "config.cpp".to_string(),
+            raw: true,
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_config_arg() {
+        use clap::Parser;
+        use self::args::Cli;
+
+        let cli = Cli::try_parse_from(["pbo_tools", "list", "a.pbo", "--config", "team.toml"]).unwrap();
+        assert_eq!(cli.config, Some(PathBuf::from("team.toml")));
+    }
+
+    #[test]
+    fn test_cli_validate() {
+        use clap::Parser;
+        use self::args::Cli;
+
+        let cli = Cli::try_parse_from(["pbo_tools", "validate", "a.pbo"]).unwrap();
+        assert!(matches!(cli.command, Commands::Validate { pbo_path } if pbo_path == Path::new("a.pbo")));
+
+        let temp = tempdir().unwrap();
+        let cli = CliProcessor::new(10);
+        let pbo_path = test_utils::get_test_pbo_path();
+        assert!(cli.process_command(Commands::Validate { pbo_path: pbo_path.clone() }).is_ok());
+
+        // Flip the last data byte, just before the checksum trailer
+        let mut data = std::fs::read(&pbo_path).unwrap();
+        let index = data.len() - 22;
+        data[index] ^= 0xff;
+        let corrupt = temp.path().join("corrupt.pbo");
+        std::fs::write(&corrupt, data).unwrap();
+        assert!(matches!(
+            cli.process_command(Commands::Validate { pbo_path: corrupt }),
+            Err(PboError::Extraction(ExtractError::ChecksumFailed(_)))
+        ));
+
+        let not_a_pbo = temp.path().join("readme.pbo");
+        std::fs::write(&not_a_pbo, "not a PBO").unwrap();
+        assert!(matches!(
+            cli.process_command(Commands::Validate { pbo_path: not_a_pbo }),
+            Err(PboError::InvalidPbo(_))
+        ));
+    }
+
+    #[test]
+    fn test_cli_cleanup_command() {
+        test_utils::setup();
+        let cli = CliProcessor::new(10);
+        let result = cli.process_command(Commands::Cleanup { max_age_hours: 24 });
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_render_listing_json() {
+        let result = ExtractResult {
+            return_code: 0,
+            stdout: "prefix=tc\\mirrorform;\nconfig.bin:1736976950: 1071 bytes\nlogo.paa".to_string(),
+            stderr: String::new(),
+            ..Default::default()
+        };
+
+        let json = render_listing_json(Path::new("mirrorform.pbo"), &result).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["prefix"], "tc\\mirrorform");
+        assert_eq!(value["files"][0]["path"], "config.bin");
+        assert_eq!(value["files"][0]["size"], 1071);
+        assert_eq!(value["files"][1]["path"], "logo.paa");
+        assert!(value["files"][1]["size"].is_null());
+        assert!(value["files"][1]["packing_method"].is_null());
+
+        // Packing methods come from the header when the PBO can be read
+        let json = render_listing_json(&test_utils::get_test_pbo_path(), &result).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["files"][0]["packing_method"], 0);
+        assert!(value.get("pbo").is_none());
+
+        // Several PBOs make one array naming each PBO
+        let other = ExtractResult { stdout: "prefix=other;\nmod.cpp".to_string(), ..Default::default() };
+        let listings = vec![(PathBuf::from("mirrorform.pbo"), result), (PathBuf::from("other.pbo"), other)];
+        let value: serde_json::Value = serde_json::from_str(&render_listings_json(&listings).unwrap()).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        assert_eq!(value[0]["pbo"], "mirrorform.pbo");
+        assert_eq!(value[1]["prefix"], "other");
+        assert_eq!(value[1]["files"][0]["path"], "mod.cpp");
+    }
 }
@@ -1,13 +1,25 @@
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
 use std::time::Duration;
-use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use log::{debug, warn};
-use crate::error::types::{Result, PboError, ExtractError};
-use crate::extract::{ExtractResult, ExtractorClone, DefaultExtractor, ExtractOptions};
-use crate::fs::TempFileManager;
+use log::{debug, trace, warn, Level};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+use crate::fs_err;
+use crate::error::types::{Result, PboError, ExtractError, FileSystemError};
+use crate::extract::{ExtractManifest, ExtractResult, ExtractorClone, DefaultExtractor, HybridExtractor, NativeExtractor, ExtractOptions, FilterSyntax, OutputLayout, parse_prefix, scaled_timeout};
+use crate::fs::{compressed, move_dir_contents, move_file, FileOperation, TempFileManager};
+use crate::pack::{DefaultPacker, PackOptions, PboPackOps};
 use super::config::PboConfig;
-use super::constants::DEFAULT_TIMEOUT;
+use super::extensions::is_pbo_path;
+use super::tools::ToolAvailability;
+use super::diff::{diff_entries, PboDiff};
+use super::reader::{entry_relative_path, stored_checksum_required, HeaderEntry, NativePboReader};
+use super::constants::{BINARY_EXTENSIONS, DEFAULT_TIMEOUT, FREE_SPACE_MARGIN_PERCENT, SEARCH_MAX_FILE_SIZE, SEARCH_SKIPPED_EXTENSIONS, TEMP_DIR_MAX_AGE_SECS};
 
 /// Core trait defining operations available for PBO files.
 /// 
@@ -35,6 +47,11 @@ use super::constants::DEFAULT_TIMEOUT;
 /// ```
 pub trait PboApiOps {
     /// List contents of a PBO file with standard output format
+    ///
+    /// Listing a plain PBO never writes to the filesystem, and only `capture_log`
+    /// in [`PboApiOps::list_with_options`] writes a file. A PBO compressed with
+    /// gzip or zstd is first decompressed into a temp directory that is removed
+    /// once the listing is done.
     fn list_contents(&self, pbo_path: &Path) -> Result<ExtractResult>;
     
     /// List contents of a PBO file in brief directory-style format
@@ -48,6 +65,339 @@ pub trait PboApiOps {
     
     /// Extract files with custom options for fine-grained control
     fn extract_with_options(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult>;
+
+    /// Extract a single file into memory, using a temporary directory that is removed afterwards
+    fn extract_file_to_memory(&self, pbo_path: &Path, internal_path: &str) -> Result<Vec<u8>>;
+
+    /// Extract an explicit list of internal paths. The result's `found_files()` and
+    /// `missing_files()` report which of the requested paths exist in the PBO.
+    fn extract_file_list(&self, pbo_path: &Path, output_dir: &Path, files: &[&str]) -> Result<ExtractResult>;
+}
+
+/// Requested file list split into entries present in the PBO and missing ones
+pub(crate) struct FileListMatch {
+    /// `-F=` filter naming the present entries as stored in the PBO header
+    pub filter: String,
+    pub found: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+/// Validate `files` and match them against the PBO header, following the
+/// configured case sensitivity and `max_decompressed_size`.
+///
+/// Entries must be exact paths: `,` would split the `-F=` list and wildcards
+/// would match more than the named file, so both are rejected.
+pub(crate) fn match_file_list(pbo_path: &Path, files: &[&str], config: &PboConfig) -> Result<FileListMatch> {
+    let case_sensitive = config.is_case_sensitive();
+    for file in files {
+        validate_file_list_entry(file)?;
+    }
+
+    let normalize = |s: &str| {
+        let s = s.trim().replace('/', "\\");
+        if case_sensitive { s } else { s.to_lowercase() }
+    };
+
+    let reader = NativePboReader::open_with_limit(pbo_path, config.max_decompressed_size())?;
+    let mut matched = Vec::new();
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+
+    for file in files {
+        let wanted = normalize(file);
+        match reader.header_entries().iter().find(|e| normalize(&e.path) == wanted) {
+            Some(entry) => {
+                matched.push(entry.path.clone());
+                found.push(file.to_string());
+            }
+            None => missing.push(file.to_string()),
+        }
+    }
+
+    debug!("File list: {} found, {} missing", found.len(), missing.len());
+    Ok(FileListMatch {
+        filter: matched.join(","),
+        found,
+        missing,
+    })
+}
+
+fn validate_file_list_entry(file: &str) -> Result<()> {
+    if file.trim().is_empty() {
+        return Err(PboError::ValidationFailed("File list entries cannot be empty".to_string()));
+    }
+    if file.contains([',', '*', '?']) {
+        return Err(PboError::ValidationFailed(format!("Invalid file list entry: {}", file)));
+    }
+    ExtractOptions {
+        file_filter: Some(file.to_string()),
+        ..Default::default()
+    }.validate()
+}
+
+/// Build a `*.paa,*.cpp` style glob filter from bare extensions. A leading dot
+/// is accepted; anything else must be alphanumeric. No extensions means no filter.
+pub(crate) fn extension_filter(extensions: &[&str]) -> Result<Option<String>> {
+    let patterns = extensions
+        .iter()
+        .map(|extension| {
+            let bare = extension.trim().trim_start_matches('.');
+            if bare.is_empty() || !bare.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(PboError::ValidationFailed(format!("Invalid file extension: {}", extension)));
+            }
+            Ok(format!("*.{}", bare))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(if patterns.is_empty() { None } else { Some(patterns.join(",")) })
+}
+
+/// Header paths worth extracting for a content search: no known binary formats,
+/// nothing above `SEARCH_MAX_FILE_SIZE`, and `.bin` files only when the config
+/// maps them to a text form.
+pub(crate) fn search_candidates(entries: &[HeaderEntry], config: &PboConfig) -> Vec<String> {
+    entries.iter()
+        .filter(|entry| u64::from(entry.original_size.max(entry.data_size)) <= SEARCH_MAX_FILE_SIZE)
+        .filter(|entry| {
+            let name = entry.path.rsplit(['\\', '/']).next().unwrap_or(&entry.path);
+            let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+            if BINARY_EXTENSIONS.contains(&extension.as_str()) {
+                config.get_bin_extension(name).is_some()
+            } else {
+                !SEARCH_SKIPPED_EXTENSIONS.contains(&extension.as_str())
+            }
+        })
+        .filter(|entry| validate_file_list_entry(&entry.path).is_ok())
+        .map(|entry| entry.path.clone())
+        .collect()
+}
+
+/// Scan the files extracted to `root` for `needle`, returning the internal paths
+/// of those that contain it. Files that turn out to be binary are skipped.
+pub(crate) fn search_extracted(root: &Path, candidates: &[String], needle: &str, config: &PboConfig) -> Vec<String> {
+    let case_sensitive = config.is_case_sensitive();
+    let needle = if case_sensitive { needle.to_string() } else { needle.to_lowercase() };
+
+    candidates.iter()
+        .filter(|path| {
+            // Debinarized files are written under their mapped name, e.g. config.cpp
+            let mapped = match path.rsplit_once(['\\', '/']) {
+                Some((dir, name)) => config.bin_target_name(name).map(|target| format!("{}\\{}", dir, target)),
+                None => config.bin_target_name(path),
+            };
+            let bytes = mapped
+                .and_then(|m| read_extracted_file(root, &m, config).ok())
+                .map_or_else(|| read_extracted_file(root, path, config), Ok);
+            let bytes = match bytes {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    debug!("Skipping {} in search: {}", path, e);
+                    return false;
+                }
+            };
+
+            if looks_binary(&bytes) {
+                trace!("Skipping binary file {} in search", path);
+                return false;
+            }
+
+            let text = String::from_utf8_lossy(&bytes);
+            if case_sensitive {
+                text.contains(&needle)
+            } else {
+                text.to_lowercase().contains(&needle)
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether `bytes` look like binary data: a NUL byte near the start
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8192).any(|&b| b == 0)
+}
+
+/// Decode a text file's contents as UTF-8 (dropping a BOM), falling back to
+/// Windows-1252 as older tools write it. Binary content is an `Encoding` error.
+pub(crate) fn decode_text(bytes: Vec<u8>, pbo_path: &Path, internal_path: &str) -> Result<String> {
+    if looks_binary(&bytes) {
+        return Err(PboError::Encoding {
+            context: format!("{} is binary, not text", internal_path),
+            path: pbo_path.to_path_buf(),
+        });
+    }
+
+    let bytes = match bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        Some(rest) => rest.to_vec(),
+        None => bytes,
+    };
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(text),
+        Err(e) => {
+            debug!("{} is not valid UTF-8, decoding as Windows-1252", internal_path);
+            let (text, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(e.as_bytes());
+            Ok(text.into_owned())
+        }
+    }
+}
+
+/// Move the files below `source` to the paths `mapper` returns for them,
+/// relative to `destination`. Mapped paths must stay inside `destination`;
+/// every path is checked before the first file is moved, so a bad mapping
+/// leaves `destination` untouched.
+fn move_mapped_files(source: &Path, destination: &Path, mapper: &dyn Fn(&str) -> Option<PathBuf>) -> Result<()> {
+    let files = WalkDir::new(source)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
+
+    let mut moves = Vec::new();
+    for entry in files {
+        let internal_path = internal_path(source, entry.path())?;
+        let Some(mapped) = mapper(&internal_path) else {
+            trace!("Path mapper skipped {}", internal_path);
+            continue;
+        };
+        if mapped.as_os_str().is_empty() || !mapped.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(PboError::ValidationFailed(format!(
+                "Path mapper returned {:?} for {}: expected a relative path inside the output directory",
+                mapped, internal_path
+            )));
+        }
+        moves.push((entry.into_path(), destination.join(mapped)));
+    }
+
+    for (from, to) in moves {
+        move_file(&from, &to)?;
+    }
+    Ok(())
+}
+
+/// Write the entry at `index` to `path` as stored, removing the file again
+/// when it cannot be written completely
+fn write_entry(reader: &mut NativePboReader, index: usize, path: &Path) -> Result<()> {
+    path.ensure_parent_exists()?;
+    let file = fs_err!(fs::File::create(path) => WriteFile, path)?;
+    let mut out = io::BufWriter::new(file);
+    let written = reader.extract_index_to(index, &mut out)
+        .and_then(|_| fs_err!(io::Write::flush(&mut out) => WriteFile, path));
+    if let Err(e) = written {
+        // Do not leave a truncated file behind
+        drop(out);
+        let _ = fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// SHA-256 of every file below `source`, keyed by its path relative to `source`
+/// with `\` separators
+fn hash_files(source: &Path) -> Result<HashMap<String, [u8; 32]>> {
+    let mut hashes = HashMap::new();
+    for entry in WalkDir::new(source).into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+        let path = entry.path();
+        let mut file = fs_err!(fs::File::open(path) => ReadFile, path)?;
+        let mut hasher = Sha256::new();
+        fs_err!(io::copy(&mut file, &mut hasher) => ReadFile, path)?;
+        hashes.insert(internal_path(source, path)?, hasher.finalize().into());
+    }
+    trace!("Hashed {} files below {:?}", hashes.len(), source);
+    Ok(hashes)
+}
+
+/// Path of `path` relative to `root`, joined with `\` as in the PBO header
+fn internal_path(root: &Path, path: &Path) -> Result<String> {
+    let relative = path.strip_prefix(root).map_err(|_| PboError::InvalidPath(path.to_path_buf()))?;
+    Ok(relative.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("\\"))
+}
+
+/// Locate an extracted file below `root` and read its bytes.
+///
+/// extractpbo may place files under prefix folders and debinarize them
+/// (e.g. `config.bin` becomes `config.cpp`), so an exact path suffix match is
+/// preferred and the name `config` converts a binary file to is accepted
+/// otherwise. No other file with the same stem is ever returned.
+pub(crate) fn read_extracted_file(root: &Path, internal_path: &str, config: &PboConfig) -> Result<Vec<u8>> {
+    let case_sensitive = config.is_case_sensitive();
+    let normalize = |s: &str| {
+        let s = s.replace('\\', "/");
+        if case_sensitive { s } else { s.to_lowercase() }
+    };
+    let wanted = normalize(internal_path);
+    let debinarized = match wanted.rsplit_once('/') {
+        Some((dir, name)) => config.bin_target_name(name).map(|target| format!("{}/{}", dir, normalize(&target))),
+        None => config.bin_target_name(&wanted).map(|target| normalize(&target)),
+    };
+
+    let files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+
+    let relative = |path: &Path| {
+        normalize(&path.strip_prefix(root).unwrap_or(path).to_string_lossy())
+    };
+
+    let find = |wanted: &str| files.iter().find(|path| {
+        let rel = relative(path);
+        rel == wanted || rel.ends_with(&format!("/{}", wanted))
+    });
+    let found = find(&wanted)
+        .or_else(|| debinarized.as_deref().and_then(find))
+        .ok_or(PboError::Extraction(ExtractError::NoFiles))?;
+
+    debug!("Reading extracted file {:?} for {}", found, internal_path);
+    fs::read(found).map_err(|e| PboError::FileSystem(FileSystemError::ReadFile {
+        path: found.clone(),
+        reason: e.to_string(),
+    }))
+}
+
+/// Space needed to extract `total` bytes, with `FREE_SPACE_MARGIN_PERCENT`
+/// on top rounded up, so small PBOs get a margin too
+fn required_space(total: u64) -> u64 {
+    total.saturating_add(total.saturating_mul(FREE_SPACE_MARGIN_PERCENT).div_ceil(100))
+}
+
+/// Remove a temp directory once an operation is done with it. A failure is
+/// only logged, so it never replaces the operation's own result.
+pub(crate) fn remove_temp_dir(temp_manager: &TempFileManager, temp_dir: &Path) {
+    if let Err(e) = temp_manager.cleanup_temp_dir(temp_dir) {
+        warn!("Failed to remove temp directory {:?}: {}", temp_dir, e);
+    }
+}
+
+/// A PBO handed to an extractor, see `PboApiGeneric::unwrap_pbo`
+struct UnwrappedPbo<'a> {
+    path: PathBuf,
+    /// Directory holding the decompressed copy, if the PBO was compressed
+    temp_dir: Option<PathBuf>,
+    temp_manager: &'a TempFileManager,
+}
+
+impl UnwrappedPbo<'_> {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether `path` is a decompressed copy rather than the caller's file
+    fn is_copy(&self) -> bool {
+        self.temp_dir.is_some()
+    }
+}
+
+impl Drop for UnwrappedPbo<'_> {
+    fn drop(&mut self) {
+        if let Some(temp_dir) = &self.temp_dir {
+            remove_temp_dir(self.temp_manager, temp_dir);
+        }
+    }
 }
 
 /// Main API for working with PBO files.
@@ -84,13 +434,26 @@ pub trait PboApiOps {
 ///     .build();
 /// ```
 #[derive(Debug, Clone)]
-pub struct PboApi {
+pub struct PboApiGeneric<E: ExtractorClone> {
     temp_manager: TempFileManager,
     config: Arc<PboConfig>,
-    extractor: Box<dyn ExtractorClone>,
+    extractor: E,
+    /// extractpbo runner used where a subprocess is always required (e.g. the async API)
+    process_extractor: DefaultExtractor,
+    packer: DefaultPacker,
     timeout: Duration,
+    /// Extra time allowed per gigabyte of PBO, set by `with_adaptive_timeout`
+    timeout_per_gb: Option<Duration>,
+    /// Maximum number of extractions `extract_many` runs at once
+    concurrency: usize,
+    /// Run every operation with `warnings_as_errors` off, see `PboApiBuilder::lenient`
+    lenient: bool,
 }
 
+/// PBO API dispatching to its extractor through a trait object, so the
+/// extractor (extractpbo or the native reader) can be chosen at runtime.
+pub type PboApi = PboApiGeneric<Box<dyn ExtractorClone>>;
+
 impl PboApi {
     pub fn builder() -> PboApiBuilder {
         PboApiBuilder::new()
@@ -101,17 +464,25 @@ impl PboApi {
             .with_timeout(timeout_seconds)
             .build()
     }
+}
+
+impl<E: ExtractorClone> PboApiGeneric<E> {
+    /// Configuration the API was built with
+    pub fn config(&self) -> &PboConfig {
+        &self.config
+    }
 
     pub fn extract_prefix(&self, output: &str) -> Option<String> {
-        output
-            .lines()
-            .find(|line| line.starts_with("prefix="))
-            .and_then(|line| {
-                line.split('=')
-                    .nth(1)
-                    .map(|prefix| prefix.trim().trim_end_matches(';').to_string())
-            })
-            .filter(|prefix| !prefix.is_empty())
+        parse_prefix(output)
+    }
+
+    /// `options` with the API-wide settings applied: warnings never count as
+    /// errors on a lenient API
+    fn resolve_options(&self, mut options: ExtractOptions) -> ExtractOptions {
+        if self.lenient {
+            options.warnings_as_errors = false;
+        }
+        options
     }
 
     fn validate_pbo_exists(&self, pbo_path: &Path) -> Result<()> {
@@ -121,7 +492,537 @@ impl PboApi {
         Ok(())
     }
 
+    /// Extract files, passing each line of extractpbo output (such as
+    /// `Extracting config.bin...`) to `on_line` as it arrives. The configured
+    /// timeout still applies; extractpbo is killed when it expires.
+    pub fn extract_with_callback(
+        &self,
+        pbo_path: &Path,
+        output_dir: &Path,
+        options: ExtractOptions,
+        on_line: impl FnMut(&str),
+    ) -> Result<ExtractResult> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.validate_output_dir(output_dir)?;
+        Self::validate_file_filter(&options)?;
+        options.check_output_dir(output_dir)?;
+        let options = self.resolve_options(options);
+        debug!("Extracting files with streamed output, options: {:?}", options);
+
+        let pbo = self.unwrap_pbo(pbo_path)?;
+        let manifest = options.write_manifest.clone();
+        let result = if options.atomic {
+            self.extract_atomically(output_dir, |temp_dir| {
+                self.process_extractor.extract_with_callback(pbo.path(), temp_dir, options, on_line)
+            })
+        } else {
+            self.process_extractor.extract_with_callback(pbo.path(), output_dir, options, on_line)
+        };
+        Self::ensure_success(Self::record_manifest_source(&pbo, pbo_path, manifest.as_deref(), result)?)
+    }
+
+    /// Command line extractpbo would run to extract `pbo_path` with `options`,
+    /// without running it. See [`DefaultExtractor::build_command_line`].
+    pub fn command_preview(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<Vec<String>> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.validate_output_dir(output_dir)?;
+        Self::validate_file_filter(&options)?;
+        self.process_extractor.build_command_line(pbo_path, output_dir, self.resolve_options(options))
+    }
+
+    /// Find the files in a PBO whose contents contain `needle`, e.g. to tell which
+    /// mod defines a class. Returns the matching internal paths.
+    ///
+    /// Only text-like files up to `SEARCH_MAX_FILE_SIZE` bytes are extracted, into a
+    /// temporary directory that is removed afterwards. Files with a bin mapping such
+    /// as `config.bin` are searched in their converted form. Matching follows the
+    /// configured case sensitivity.
+    pub fn search_contents(&self, pbo_path: &Path, needle: &str) -> Result<Vec<String>> {
+        self.validate_pbo_exists(pbo_path)?;
+        if needle.is_empty() {
+            return Err(PboError::ValidationFailed("Search text cannot be empty".to_string()));
+        }
+
+        let reader = self.open_reader(pbo_path)?;
+        let candidates = search_candidates(reader.header_entries(), &self.config);
+        debug!("Searching {} of {} files for {:?}", candidates.len(), reader.header_entries().len(), needle);
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let temp_dir = self.temp_manager.create_temp_dir()?;
+        let files: Vec<&str> = candidates.iter().map(String::as_str).collect();
+        let result = self.extract_file_list(pbo_path, &temp_dir, &files)
+            .map(|_| search_extracted(&temp_dir, &candidates, needle, &self.config));
+
+        remove_temp_dir(&self.temp_manager, &temp_dir);
+        result
+    }
+
+    /// [`PboApiOps::list_with_options`] with `timeout` instead of the API's
+    /// timeout for this call only
+    pub fn list_with_options_timeout(&self, pbo_path: &Path, options: ExtractOptions, timeout: Duration) -> Result<ExtractResult> {
+        self.list_with_options(pbo_path, ExtractOptions { timeout: Some(timeout), ..options })
+    }
+
+    /// [`PboApiOps::extract_with_options`] with `timeout` instead of the API's
+    /// timeout for this call only
+    pub fn extract_with_options_timeout(
+        &self,
+        pbo_path: &Path,
+        output_dir: &Path,
+        options: ExtractOptions,
+        timeout: Duration,
+    ) -> Result<ExtractResult> {
+        self.extract_with_options(pbo_path, output_dir, ExtractOptions { timeout: Some(timeout), ..options })
+    }
+
+    /// Extract the files with any of the given extensions, e.g. `&["paa", "bin"]`.
+    /// An empty slice extracts all files.
+    pub fn extract_by_extensions(&self, pbo_path: &Path, output_dir: &Path, extensions: &[&str]) -> Result<ExtractResult> {
+        let file_filter = extension_filter(extensions)?;
+        self.extract_files(pbo_path, output_dir, file_filter.as_deref())
+    }
+
+    /// Extract all files to a temp directory, then move each one to the path
+    /// returned by `mapper`, relative to `output_dir`. Files for which `mapper`
+    /// returns `None` are dropped.
+    ///
+    /// `mapper` receives the path of each extracted file below the PBO prefix,
+    /// with `\` separators as in the PBO header. Debinarized files carry their
+    /// new name, e.g. `config.cpp` for `config.bin`.
+    pub fn extract_with_path_mapper(
+        &self,
+        pbo_path: &Path,
+        output_dir: &Path,
+        mapper: impl Fn(&str) -> Option<PathBuf>,
+    ) -> Result<ExtractResult> {
+        self.validate_output_dir(output_dir)?;
+        let options = ExtractOptions {
+            layout: OutputLayout::FlattenPrefix,
+            ..ExtractOptions::for_extraction()
+        };
+        options.check_output_dir(output_dir)?;
+
+        let temp_dir = self.temp_manager.create_temp_dir()?;
+        debug!("Extracting {:?} for path mapping via {:?}", pbo_path, temp_dir);
+
+        let result = self.extract_with_options(pbo_path, &temp_dir, options).and_then(|result| {
+            move_mapped_files(&temp_dir, output_dir, &mapper)?;
+            Ok(result)
+        });
+
+        remove_temp_dir(&self.temp_manager, &temp_dir);
+        result
+    }
+
+    /// Write the entry at `index` in the PBO header (see
+    /// [`NativePboReader::header_entries`]) below `output_dir`, at its path
+    /// inside the PBO, and return the written file's path.
+    ///
+    /// The entry is read natively and written as stored, without debinarizing,
+    /// so names that cannot be expressed in a `-F=` filter can still be extracted.
+    /// Fails with `ExtractError::NoFiles` when `index` is out of range.
+    pub fn extract_entry_by_index(&self, pbo_path: &Path, index: usize, output_dir: &Path) -> Result<PathBuf> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.validate_output_dir(output_dir)?;
+        let mut reader = self.open_reader(pbo_path)?;
+        let entry_path = reader.header_entries()
+            .get(index)
+            .map(|entry| entry.path.clone())
+            .ok_or(PboError::Extraction(ExtractError::NoFiles))?;
+
+        let relative = entry_relative_path(&entry_path).ok_or_else(|| PboError::ValidationFailed(format!(
+            "Entry {} is stored as {:?}, which is not a path inside the output directory", index, entry_path
+        )))?;
+
+        let path = output_dir.join(relative);
+        debug!("Extracting entry {} of {:?} to {:?}", index, pbo_path, path);
+        write_entry(&mut reader, index, &path)?;
+        Ok(path)
+    }
+
+    /// Extract only the mod metadata files at the PBO root, such as `mod.cpp`
+    /// or `readme.txt`, and return the paths they were written to below
+    /// `output_dir`. The names are taken from [`PboConfig::metadata_files`].
+    ///
+    /// Entries are read natively and written as stored, so the rest of the PBO
+    /// is never unpacked. A PBO without metadata files yields an empty list.
+    pub fn extract_metadata_files(&self, pbo_path: &Path, output_dir: &Path) -> Result<Vec<PathBuf>> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.validate_output_dir(output_dir)?;
+        let mut reader = self.open_reader(pbo_path)?;
+        let metadata: Vec<(usize, String)> = reader.header_entries()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !entry.path.contains(['\\', '/']) && self.config.is_metadata_file(&entry.path))
+            .map(|(index, entry)| (index, entry.path.clone()))
+            .collect();
+        debug!("Found {} metadata files in {:?}", metadata.len(), pbo_path);
+
+        let mut written = Vec::with_capacity(metadata.len());
+        for (index, name) in metadata {
+            let path = output_dir.join(name);
+            write_entry(&mut reader, index, &path)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// Extract all files like [`PboApiOps::extract_files`] and return the SHA-256
+    /// of each one, keyed by its path below `output_dir` with `\` separators,
+    /// e.g. to deduplicate assets shared by several mods.
+    ///
+    /// The files are extracted to a temp directory and hashed there before they
+    /// are moved, so files already in `output_dir` are neither hashed nor
+    /// touched when the extraction fails.
+    pub fn extract_and_hash(&self, pbo_path: &Path, output_dir: &Path) -> Result<HashMap<String, [u8; 32]>> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.validate_output_dir(output_dir)?;
+        let options = self.resolve_options(ExtractOptions::for_extraction());
+        options.check_output_dir(output_dir)?;
+
+        let pbo = self.unwrap_pbo(pbo_path)?;
+        let mut hashes = HashMap::new();
+        let result = self.extract_atomically(output_dir, |temp_dir| {
+            let result = self.extractor.extract_with_options(pbo.path(), temp_dir, options)?;
+            if result.is_success() {
+                hashes = hash_files(temp_dir)?;
+            }
+            Ok(result)
+        })?;
+        Self::ensure_success(result)?;
+        Ok(hashes)
+    }
+
+    /// Extract a batch of `(pbo_path, output_dir)` jobs on a bounded pool of
+    /// worker threads (see [`PboApiBuilder::with_concurrency`]).
+    ///
+    /// Every job runs with the configured timeout, and a failing job does not stop
+    /// the others. Results are returned in job order, paired with their PBO path.
+    pub fn extract_many(&self, jobs: &[(PathBuf, PathBuf)], options: ExtractOptions) -> Vec<(PathBuf, Result<ExtractResult>)> {
+        debug!("Extracting {} PBOs", jobs.len());
+        let results = self.run_parallel(jobs, |(pbo_path, output_dir)| {
+            let result = self.extract_with_options(pbo_path, output_dir, options.clone());
+            if let Err(e) = &result {
+                warn!("Failed to extract {:?}: {}", pbo_path, e);
+            }
+            result
+        });
+
+        jobs.iter()
+            .zip(results)
+            .map(|((pbo_path, _), result)| {
+                let result = result.unwrap_or_else(|| Err(PboError::Extraction(ExtractError::Canceled(
+                    "Extraction did not run".to_string()
+                ))));
+                (pbo_path.clone(), result)
+            })
+            .collect()
+    }
+
+    /// Read the prefix of every PBO, XBO and IFA file below `dir`, listing them
+    /// on the same worker pool as [`PboApi::extract_many`] with the configured
+    /// timeout. Results are sorted by path; a failing file does not stop the others.
+    pub fn scan_prefixes(&self, dir: &Path) -> Vec<(PathBuf, Result<Option<String>>)> {
+        let mut pbos: Vec<PathBuf> = WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|entry| entry.map_err(|e| warn!("Skipping unreadable entry in {:?}: {}", dir, e)).ok())
+            .filter(|entry| entry.file_type().is_file() && is_pbo_path(entry.path()))
+            .map(|entry| entry.into_path())
+            .collect();
+        pbos.sort();
+        debug!("Scanning prefixes of {} PBOs in {:?}", pbos.len(), dir);
+
+        let results = self.run_parallel(&pbos, |pbo_path| {
+            let result = self.list_contents(pbo_path).map(|listing| listing.get_prefix());
+            if let Err(e) = &result {
+                warn!("Failed to read prefix of {:?}: {}", pbo_path, e);
+            }
+            result
+        });
+
+        pbos.into_iter()
+            .zip(results)
+            .map(|(pbo_path, result)| {
+                let result = result.unwrap_or_else(|| Err(PboError::Extraction(ExtractError::Canceled(
+                    "Prefix scan did not run".to_string()
+                ))));
+                (pbo_path, result)
+            })
+            .collect()
+    }
+
+    /// Run `job` for every item on a bounded pool of worker threads (see
+    /// [`PboApiBuilder::with_concurrency`]). Results are in item order; `None`
+    /// marks an item whose job never ran.
+    fn run_parallel<T: Sync, R: Send>(&self, items: &[T], job: impl Fn(&T) -> Result<R> + Sync) -> Vec<Option<Result<R>>> {
+        let workers = self.concurrency.min(items.len());
+        debug!("Running {} jobs with {} workers", items.len(), workers);
+
+        let next_item = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<R>>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next_item.fetch_add(1, Ordering::SeqCst);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+
+                    let result = job(item);
+                    if let Ok(mut results) = results.lock() {
+                        results[index] = Some(result);
+                    }
+                });
+            }
+        });
+
+        results.into_inner().unwrap_or_default()
+    }
+
+    /// Check which Mikero tools can be run, looking at the overridden paths or
+    /// searching PATH. Nothing is executed.
+    pub fn check_tools(&self) -> ToolAvailability {
+        let tools = ToolAvailability {
+            extractpbo: self.process_extractor.is_available(),
+            makepbo: self.packer.is_available(),
+        };
+        debug!("Tool availability: {:?}", tools);
+        tools
+    }
+
+    /// Total size of the PBO's files once extracted, read from the header without
+    /// extracting anything. Useful for checking free disk space beforehand.
+    pub fn total_size(&self, pbo_path: &Path) -> Result<u64> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.open_reader(pbo_path)?.total_size()
+    }
+
+    /// Whether the volume holding `output_dir` has room for the PBO's files
+    /// once extracted, plus a margin of `FREE_SPACE_MARGIN_PERCENT`. See
+    /// [`PboApiGeneric::check_space`] for an error describing the shortfall.
+    ///
+    /// `output_dir` does not need to exist yet: the space of the closest
+    /// existing ancestor is checked.
+    pub fn can_extract(&self, pbo_path: &Path, output_dir: &Path) -> Result<bool> {
+        let (required, available) = self.space_for(pbo_path, output_dir)?;
+        Ok(available >= required)
+    }
+
+    /// Like [`PboApiGeneric::can_extract`], but a shortfall fails with
+    /// `FileSystemError::InsufficientSpace` so it can be shown as is
+    pub fn check_space(&self, pbo_path: &Path, output_dir: &Path) -> Result<()> {
+        let (required, available) = self.space_for(pbo_path, output_dir)?;
+        if available < required {
+            return Err(PboError::FileSystem(FileSystemError::InsufficientSpace {
+                path: output_dir.to_path_buf(),
+                required,
+                available,
+            }));
+        }
+        Ok(())
+    }
+
+    /// Bytes needed to extract the PBO into `output_dir` and bytes available there
+    fn space_for(&self, pbo_path: &Path, output_dir: &Path) -> Result<(u64, u64)> {
+        let required = required_space(self.total_size(pbo_path)?);
+        let volume_path = output_dir.ancestors()
+            .find(|dir| dir.is_dir())
+            .unwrap_or(Path::new("."));
+        let available = fs_err!(fs2::available_space(volume_path) => Read, volume_path)?;
+        debug!("{} bytes needed to extract {:?}, {} available in {:?}", required, pbo_path, available, volume_path);
+        Ok((required, available))
+    }
+
+    /// Whether the PBO's content is binarized, judged from its header without
+    /// extracting anything. Returns `false` when it cannot be determined.
+    /// See [`NativePboReader::is_binarized`] for the heuristics.
+    pub fn is_binarized(&self, pbo_path: &Path) -> Result<bool> {
+        self.validate_pbo_exists(pbo_path)?;
+        Ok(self.open_reader(pbo_path)?.is_binarized())
+    }
+
+    /// Extract a single text file such as `config.cpp` or an `.sqf` script and
+    /// decode it as UTF-8 or Windows-1252. Binary files fail with `PboError::Encoding`.
+    pub fn extract_text_file(&self, pbo_path: &Path, internal_path: &str) -> Result<String> {
+        let bytes = self.extract_file_to_memory(pbo_path, internal_path)?;
+        decode_text(bytes, pbo_path, internal_path)
+    }
+
+    /// Compare the file lists of an older and a newer version of a PBO without
+    /// extracting either. Entries whose sizes differ are reported as `changed`
+    /// when the listings include sizes.
+    pub fn diff_contents(&self, old_pbo: &Path, new_pbo: &Path) -> Result<PboDiff> {
+        let old = self.list_contents(old_pbo)?.get_entries();
+        let new = self.list_contents(new_pbo)?.get_entries();
+        debug!("Comparing {} entries of {:?} with {} entries of {:?}", old.len(), old_pbo, new.len(), new_pbo);
+        Ok(diff_entries(&old, &new, self.config.is_case_sensitive()))
+    }
+
+    /// Whether the PBO contains a file at `internal_path`. Either separator may
+    /// be used, and the comparison ignores case unless the configuration is
+    /// case sensitive.
+    pub fn contains_file(&self, pbo_path: &Path, internal_path: &str) -> Result<bool> {
+        let normalize = |s: &str| {
+            let s = s.trim().replace('/', "\\");
+            if self.config.is_case_sensitive() { s } else { s.to_lowercase() }
+        };
+        let wanted = normalize(internal_path);
+        let files = self.list_contents(pbo_path)?.get_file_list();
+        Ok(files.iter().any(|file| normalize(file) == wanted))
+    }
+
+    /// Iterate over the PBO's files natively, yielding each one's internal path
+    /// and decompressed contents in stored order. Entries are read one at a time
+    /// as the iterator advances, so only a single file is held in memory.
+    pub fn entries_iter(&self, pbo_path: &Path) -> Result<impl Iterator<Item = Result<(String, Vec<u8>)>>> {
+        self.validate_pbo_exists(pbo_path)?;
+        Ok(self.open_reader(pbo_path)?.into_contents())
+    }
+
+    /// The SHA1 stored at the end of the PBO, which `.bisign` signatures sign
+    /// directly. Fails with `PboError::InvalidPbo` when the PBO has no checksum.
+    /// See [`NativePboReader::name_hash`] and [`NativePboReader::file_hash`] for
+    /// the other hashes a signature covers.
+    pub fn public_hash(&self, pbo_path: &Path) -> Result<[u8; 20]> {
+        self.validate_pbo_exists(pbo_path)?;
+        stored_checksum_required(&mut self.open_reader(pbo_path)?, pbo_path)
+    }
+
+    /// Verify the PBO's stored SHA1 natively, without invoking extractpbo.
+    ///
+    /// Returns `Ok(true)` when the checksum matches, `Ok(false)` when the PBO
+    /// has no checksum, and `ExtractError::ChecksumFailed` when they differ.
+    pub fn verify_checksum(&self, pbo_path: &Path) -> Result<bool> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.open_reader(pbo_path)?.verify_checksum()
+    }
+
+    fn validate_file_filter(options: &ExtractOptions) -> Result<()> {
+        if let Some(filter) = &options.file_filter {
+            if filter.trim().is_empty() {
+                return Err(PboError::ValidationFailed("File filter cannot be empty".to_string()));
+            }
+            
+            // Regex filters are always validated; for glob filters, patterns without
+            // wildcards are still checked as regex to keep existing behavior
+            if options.filter_syntax == FilterSyntax::Regex
+                || (!filter.contains('*') && !filter.contains('?'))
+            {
+                // If it's not a glob pattern, treat it as regex and validate it
+                if let Err(_) = regex::Regex::new(filter) {
+                    return Err(PboError::ValidationFailed(format!("Invalid file filter pattern: {}", filter)));
+                }
+            }
+        }
+        if options.exclude_filter.as_ref().is_some_and(|filter| filter.trim().is_empty()) {
+            return Err(PboError::ValidationFailed("Exclude filter cannot be empty".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Convert an unsuccessful result into a `CommandFailed` error
+    fn ensure_success(result: ExtractResult) -> Result<ExtractResult> {
+        if !result.is_success() {
+            debug!("PBO operation failed: {}", result);
+            return Err(PboError::Extraction(ExtractError::CommandFailed {
+                cmd: "extractpbo".to_string(),
+                reason: result.get_error_message()
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            }));
+        }
+        Ok(result)
+    }
+
+    /// Run a validated extraction on the configured extractor. The extractor
+    /// enforces the timeout by killing extractpbo once it expires.
+    fn run_extraction(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        options.check_output_dir(output_dir)?;
+        let options = self.resolve_options(options);
+        debug!("Extracting files with options: {:?}", options);
+        let result = if options.atomic {
+            self.extract_atomically(output_dir, |temp_dir| {
+                self.extractor.extract_with_options(pbo_path, temp_dir, options)
+            })?
+        } else {
+            self.extractor.extract_with_options(pbo_path, output_dir, options)?
+        };
+        Self::ensure_success(result)
+    }
+
+    /// Open the PBO with the native reader, decompressing a gzip or zstd
+    /// compressed one up to the configured `max_decompressed_size`
+    fn open_reader(&self, pbo_path: &Path) -> Result<NativePboReader> {
+        NativePboReader::open_with_limit(pbo_path, self.config.max_decompressed_size())
+    }
+
+    /// The PBO at `pbo_path` as extractpbo can read it: the file itself, or,
+    /// when it is compressed with gzip or zstd, a decompressed copy in a temp
+    /// directory that is removed when the returned value is dropped
+    fn unwrap_pbo(&self, pbo_path: &Path) -> Result<UnwrappedPbo<'_>> {
+        let mut unwrapped = UnwrappedPbo {
+            path: pbo_path.to_path_buf(),
+            temp_dir: None,
+            temp_manager: &self.temp_manager,
+        };
+        let Some(wrapper) = compressed::detect_wrapper(pbo_path)? else {
+            return Ok(unwrapped);
+        };
+
+        let temp_dir = self.temp_manager.create_temp_dir()?;
+        unwrapped.temp_dir = Some(temp_dir.clone());
+        unwrapped.path = compressed::unwrap_to(pbo_path, wrapper, &temp_dir, self.config.max_decompressed_size())?;
+        Ok(unwrapped)
+    }
+
+    /// Point the manifest of a successful extraction from a decompressed copy
+    /// back at the compressed PBO the caller passed
+    fn record_manifest_source(
+        pbo: &UnwrappedPbo<'_>,
+        original: &Path,
+        manifest: Option<&Path>,
+        result: Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        let result = result?;
+        if let Some(manifest) = manifest.filter(|_| pbo.is_copy() && result.is_success()) {
+            ExtractManifest::set_source(manifest, original)?;
+        }
+        Ok(result)
+    }
+
+    /// Run an extraction into a fresh temp directory and move its files into
+    /// `output_dir` only if it succeeded. The temp directory is always removed.
+    fn extract_atomically(
+        &self,
+        output_dir: &Path,
+        run: impl FnOnce(&Path) -> Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        let temp_dir = self.temp_manager.create_temp_dir()?;
+        debug!("Extracting atomically via {:?}", temp_dir);
+
+        let result = run(&temp_dir).and_then(|result| {
+            if result.is_success() {
+                move_dir_contents(&temp_dir, output_dir)?;
+            }
+            Ok(result)
+        });
+
+        remove_temp_dir(&self.temp_manager, &temp_dir);
+        result
+    }
+
+    /// Reject output paths that cannot become a directory. Unlike
+    /// `FileOperation::ensure_directory` this never creates the directory, which
+    /// is left to the extraction and `ExtractOptions::create_output_dir`.
     fn validate_output_dir(&self, output_dir: &Path) -> Result<()> {
+        if output_dir.exists() && !output_dir.is_dir() {
+            return Err(PboError::FileSystem(FileSystemError::CreateDir {
+                path: output_dir.to_path_buf(),
+                reason: "output path is a file".to_string(),
+            }));
+        }
         if !output_dir.exists() {
             // Try to create it
             if let Some(parent) = output_dir.parent() {
@@ -132,66 +1033,74 @@ impl PboApi {
         }
         Ok(())
     }
+}
 
-    fn with_timeout<T, F>(&self, operation: F) -> Result<T>
-    where
-        F: FnOnce() -> Result<T> + Send + 'static,
-        T: Send + 'static,
-    {
-        let (tx, rx) = mpsc::channel();
-        let (cancel_tx, cancel_rx) = mpsc::channel();
-        let timeout = self.timeout;
-
-        let handle = thread::spawn(move || {
-            // Set up cancellation check
-            let start = std::time::Instant::now();
-            let result = operation();
-
-            // Check for cancellation periodically
-            if cancel_rx.try_recv().is_ok() {
-                debug!("Operation was canceled after {} ms", start.elapsed().as_millis());
-                return;
-            }
+/// Async operations backed by tokio.
+///
+/// These always run extractpbo through `tokio::process`, even when the API was
+/// built with the native reader. The subprocess is killed when the timeout elapses.
+#[cfg(feature = "async")]
+impl<E: ExtractorClone> PboApiGeneric<E> {
+    /// Async version of [`PboApiOps::list_contents`]
+    pub async fn list_contents_async(&self, pbo_path: &Path) -> Result<ExtractResult> {
+        self.list_with_options_async(pbo_path, ExtractOptions::for_listing()).await
+    }
 
-            // Try to send result back
-            if tx.send(result).is_err() {
-                warn!("Failed to send operation result - receiver dropped");
-            }
-        });
+    /// Async version of [`PboApiOps::list_with_options`]
+    pub async fn list_with_options_async(&self, pbo_path: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        self.validate_pbo_exists(pbo_path)?;
+        let options = self.resolve_options(options);
+        debug!("Listing contents of PBO asynchronously with options: {:?}", options);
 
-        // Wait for result with timeout
-        let result = match rx.recv_timeout(timeout) {
+        let pbo = self.unwrap_pbo(pbo_path)?;
+        let timeout = options.timeout.unwrap_or_else(|| scaled_timeout(self.timeout, self.timeout_per_gb, pbo_path));
+        let operation = self.process_extractor.list_with_options_async(pbo.path(), options);
+        match tokio::time::timeout(timeout, operation).await {
+            Ok(result) => Self::ensure_success(result?),
+            Err(_) => Err(PboError::Timeout(timeout)),
+        }
+    }
+
+    /// Async version of [`PboApiOps::extract_with_options`]
+    pub async fn extract_with_options_async(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.validate_output_dir(output_dir)?;
+        Self::validate_file_filter(&options)?;
+        options.check_output_dir(output_dir)?;
+        let options = self.resolve_options(options);
+        debug!("Extracting files asynchronously with options: {:?}", options);
+
+        let pbo = self.unwrap_pbo(pbo_path)?;
+        let manifest = options.write_manifest.clone();
+        let temp_dir = if options.atomic { Some(self.temp_manager.create_temp_dir()?) } else { None };
+        let target_dir = temp_dir.as_deref().unwrap_or(output_dir);
+
+        let timeout = options.timeout.unwrap_or_else(|| scaled_timeout(self.timeout, self.timeout_per_gb, pbo_path));
+        let operation = self.process_extractor.extract_with_options_async(pbo.path(), target_dir, options);
+        let result = match tokio::time::timeout(timeout, operation).await {
             Ok(result) => result,
-            Err(mpsc::RecvTimeoutError::Timeout) => {
-                debug!("Operation timed out after {} seconds", timeout.as_secs());
-                // Try to cancel the operation
-                if let Err(e) = cancel_tx.send(()) {
-                    warn!("Failed to send cancellation signal: {}", e);
-                }
-                
-                // Give the thread a short time to clean up
-                let _ = thread::spawn(move || {
-                    thread::sleep(Duration::from_secs(1));
-                    if let Err(e) = handle.join() {
-                        warn!("Operation thread did not terminate cleanly: {:?}", e);
+            Err(_) => Err(PboError::Timeout(timeout)),
+        };
+
+        // See `extract_atomically`; the extraction future has finished or been dropped here
+        let result = match &temp_dir {
+            Some(temp_dir) => {
+                let moved = result.and_then(|result| {
+                    if result.is_success() {
+                        move_dir_contents(temp_dir, output_dir)?;
                     }
+                    Ok(result)
                 });
-
-                Err(PboError::Timeout(timeout.as_secs() as u32))
-            }
-            Err(mpsc::RecvTimeoutError::Disconnected) => {
-                warn!("Operation thread terminated unexpectedly");
-                Err(PboError::Extraction(ExtractError::Canceled(
-                    "Operation thread terminated unexpectedly".to_string()
-                )))
+                remove_temp_dir(&self.temp_manager, temp_dir);
+                moved
             }
+            None => result,
         };
-
-        result
+        Self::ensure_success(Self::record_manifest_source(&pbo, pbo_path, manifest.as_deref(), result)?)
     }
 }
 
-impl PboApiOps for PboApi {
+impl<E: ExtractorClone> PboApiOps for PboApiGeneric<E> {
     fn list_contents(&self, pbo_path: &Path) -> Result<ExtractResult> {
         let options = ExtractOptions {
             no_pause: true,
@@ -223,66 +1132,69 @@ impl PboApiOps for PboApi {
 
     fn list_with_options(&self, pbo_path: &Path, options: ExtractOptions) -> Result<ExtractResult> {
         self.validate_pbo_exists(pbo_path)?;
-        let pbo_path = pbo_path.to_owned();
-        let extractor = self.extractor.clone();
-        let options = options.clone();
-        
-        self.with_timeout(move || {
-            debug!("Listing contents of PBO with options: {:?}", options);
-            let result = extractor.list_with_options(&pbo_path, options)?;
-            
-            if !result.is_success() {
-                debug!("PBO listing failed: {}", result);
-                return Err(PboError::Extraction(ExtractError::CommandFailed {
-                    cmd: "extractpbo".to_string(),
-                    reason: result.get_error_message()
-                        .unwrap_or_else(|| "Unknown error".to_string()),
-                }));
-            }
-            
-            Ok(result)
-        })
+        let options = self.resolve_options(options);
+        debug!("Listing contents of PBO with options: {:?}", options);
+        let pbo = self.unwrap_pbo(pbo_path)?;
+        let result = self.extractor.list_with_options(pbo.path(), options)?;
+        Self::ensure_success(result)
     }
 
     fn extract_with_options(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
         self.validate_pbo_exists(pbo_path)?;
         self.validate_output_dir(output_dir)?;
         
-        // Validate file filter
-        if let Some(filter) = &options.file_filter {
-            if filter.trim().is_empty() {
-                return Err(PboError::ValidationFailed("File filter cannot be empty".to_string()));
-            }
-            
-            // Validate regex patterns specifically (patterns that don't use glob wildcards)
-            if !filter.contains('*') && !filter.contains('?') {
-                // If it's not a glob pattern, treat it as regex and validate it
-                if let Err(_) = regex::Regex::new(filter) {
-                    return Err(PboError::ValidationFailed(format!("Invalid file filter pattern: {}", filter)));
-                }
-            }
+        Self::validate_file_filter(&options)?;
+        let pbo = self.unwrap_pbo(pbo_path)?;
+        let manifest = options.write_manifest.clone();
+        let result = self.run_extraction(pbo.path(), output_dir, options);
+        Self::record_manifest_source(&pbo, pbo_path, manifest.as_deref(), result)
+    }
+
+    fn extract_file_to_memory(&self, pbo_path: &Path, internal_path: &str) -> Result<Vec<u8>> {
+        let temp_dir = self.temp_manager.create_temp_dir()?;
+        debug!("Extracting {} to memory via {:?}", internal_path, temp_dir);
+
+        let result = self.extract_files(pbo_path, &temp_dir, Some(internal_path))
+            .and_then(|_| read_extracted_file(&temp_dir, internal_path, &self.config));
+
+        remove_temp_dir(&self.temp_manager, &temp_dir);
+        result
+    }
+
+    fn extract_file_list(&self, pbo_path: &Path, output_dir: &Path, files: &[&str]) -> Result<ExtractResult> {
+        self.validate_pbo_exists(pbo_path)?;
+        self.validate_output_dir(output_dir)?;
+
+        let file_list = match_file_list(pbo_path, files, &self.config)?;
+        if file_list.found.is_empty() {
+            return Err(PboError::Extraction(ExtractError::NoFiles));
         }
-        
-        let pbo_path = pbo_path.to_owned();
-        let output_dir = output_dir.to_owned();
-        let extractor = self.extractor.clone();
-        let options = options.clone();
-        
-        self.with_timeout(move || {
-            debug!("Extracting files with options: {:?}", options);
-            let result = extractor.extract_with_options(&pbo_path, &output_dir, options)?;
-            
-            if !result.is_success() {
-                debug!("PBO extraction failed: {}", result);
-                return Err(PboError::Extraction(ExtractError::CommandFailed {
-                    cmd: "extractpbo".to_string(),
-                    reason: result.get_error_message()
-                        .unwrap_or_else(|| "Unknown error".to_string()),
-                }));
-            }
-            
-            Ok(result)
-        })
+
+        // The explicit list bypasses validate_file_filter: exact paths are not regexes
+        let options = ExtractOptions {
+            file_filter: Some(file_list.filter),
+            ..ExtractOptions::for_extraction()
+        };
+        let pbo = self.unwrap_pbo(pbo_path)?;
+        let mut result = self.run_extraction(pbo.path(), output_dir, options)?;
+        result.found_files = file_list.found;
+        result.missing_files = file_list.missing;
+        Ok(result)
+    }
+}
+
+impl<E: ExtractorClone> PboPackOps for PboApiGeneric<E> {
+    fn pack(&self, source_dir: &Path, output_pbo: &Path, options: PackOptions) -> Result<ExtractResult> {
+        debug!("Packing {:?} into {:?} with options: {:?}", source_dir, output_pbo, options);
+        let result = self.packer.pack(source_dir, output_pbo, options)?;
+        if !result.is_success() {
+            return Err(PboError::Extraction(ExtractError::CommandFailed {
+                cmd: "makepbo".to_string(),
+                reason: result.get_error_message()
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            }));
+        }
+        Ok(result)
     }
 }
 
@@ -301,11 +1213,28 @@ impl PboApiOps for PboApi {
 /// let api = PboApi::builder()
 ///     .with_timeout(60)  // 60 second timeout
 ///     .build();
+///
+/// // List contents from the PBO header without invoking extractpbo
+/// let native = PboApi::builder()
+///     .with_native_reader()
+///     .build();
 /// ```
 #[derive(Default)]
 pub struct PboApiBuilder {
     config: Option<PboConfig>,
     timeout: Option<Duration>,
+    timeout_per_gb: Option<Duration>,
+    native_reader: bool,
+    hybrid: bool,
+    extractpbo_path: Option<PathBuf>,
+    makepbo_path: Option<PathBuf>,
+    concurrency: Option<usize>,
+    temp_base: Option<PathBuf>,
+    env: Vec<(String, String)>,
+    lenient: bool,
+    wine: Option<Option<PathBuf>>,
+    output_log_level: Option<Level>,
+    min_extractpbo_version: Option<String>,
 }
 
 impl PboApiBuilder {
@@ -318,17 +1247,421 @@ impl PboApiBuilder {
         self
     }
 
-    pub fn with_timeout(mut self, seconds: u32) -> Self {
-        self.timeout = Some(Duration::from_secs(u64::from(seconds.max(1))));
+    pub fn with_timeout(self, seconds: u32) -> Self {
+        self.with_timeout_duration(Duration::from_secs(u64::from(seconds.max(1))))
+    }
+
+    /// Like `with_timeout`, with sub-second precision. A zero timeout is
+    /// raised to one millisecond.
+    pub fn with_timeout_duration(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout.max(Duration::from_millis(1)));
+        self
+    }
+
+    /// Scale the timeout with the size of each PBO: `base_secs` plus `secs_per_gb`
+    /// for every whole gigabyte, so a 4 GB PBO gets `base_secs + 4 * secs_per_gb`.
+    /// Packing keeps the flat `base_secs` timeout.
+    pub fn with_adaptive_timeout(mut self, base_secs: u32, secs_per_gb: u32) -> Self {
+        self = self.with_timeout(base_secs);
+        self.timeout_per_gb = Some(Duration::from_secs(u64::from(secs_per_gb)));
+        self
+    }
+
+    /// Use the extractpbo binary at `path` instead of looking it up on PATH
+    pub fn with_extractpbo_path(mut self, path: PathBuf) -> Self {
+        self.extractpbo_path = Some(path);
+        self
+    }
+
+    /// Set an environment variable for every extractpbo process the API runs,
+    /// e.g. `WINEPREFIX`, without touching the current process environment
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Run extractpbo under Wine, using the binary at `wine_path` or `wine` on PATH.
+    /// Paths are translated to Windows paths with `winepath`, and failures reported
+    /// by Wine surface as `PboError::Wine`. Has no effect on Windows; makepbo still
+    /// runs directly.
+    pub fn with_wine(mut self, wine_path: Option<PathBuf>) -> Self {
+        self.wine = Some(wine_path);
+        self
+    }
+
+    /// Treat extractpbo warnings as success for every operation of the API,
+    /// including `list_contents` and `extract_files`: extractpbo runs without -W
+    /// and `ExtractOptions::warnings_as_errors` is ignored
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Log the stdout and stderr of every extractpbo run at `level` instead of
+    /// trace. Output of a run with a nonzero exit code is always logged at warn
+    /// or above.
+    pub fn with_output_log_level(mut self, level: Level) -> Self {
+        self.output_log_level = Some(level);
+        self
+    }
+
+    /// Report a failed extractpbo run as `PboError::IncompatibleTool` when the
+    /// binary is older than `version`. The version is probed once, on the
+    /// first failure.
+    pub fn with_min_extractpbo_version(mut self, version: impl Into<String>) -> Self {
+        self.min_extractpbo_version = Some(version.into());
+        self
+    }
+
+    /// Run at most `workers` extractions at once in `extract_many`.
+    /// Defaults to the number of available CPUs.
+    pub fn with_concurrency(mut self, workers: usize) -> Self {
+        self.concurrency = Some(workers.max(1));
+        self
+    }
+
+    /// Use the makepbo binary at `path` instead of looking it up on PATH
+    pub fn with_makepbo_path(mut self, path: PathBuf) -> Self {
+        self.makepbo_path = Some(path);
+        self
+    }
+
+    /// Create temporary directories (atomic extraction, in-memory reads, content
+    /// search) under `path` instead of the system temp dir. The directory must
+    /// exist and be writable when `build` is called.
+    pub fn with_temp_base(mut self, path: PathBuf) -> Self {
+        self.temp_base = Some(path);
+        self
+    }
+
+    /// Answer listings by parsing the PBO header natively instead of running extractpbo
+    pub fn with_native_reader(mut self) -> Self {
+        self.native_reader = true;
+        self
+    }
+
+    /// Like `with_native_reader`, but list PBOs the native reader cannot parse
+    /// with extractpbo instead of failing. Takes precedence over `with_native_reader`.
+    pub fn with_hybrid(mut self) -> Self {
+        self.hybrid = true;
         self
     }
 
     pub fn build(self) -> PboApi {
-        PboApi {
-            temp_manager: TempFileManager::new(),
-            config: Arc::new(self.config.unwrap_or_default()),
-            extractor: Box::new(DefaultExtractor::new()),
-            timeout: self.timeout.unwrap_or_else(|| Duration::from_secs(u64::from(DEFAULT_TIMEOUT))),
+        let native_reader = self.native_reader;
+        let hybrid = self.hybrid;
+        self.build_with(|default_extractor| -> Box<dyn ExtractorClone> {
+            if hybrid {
+                Box::new(HybridExtractor::with_fallback(default_extractor))
+            } else if native_reader {
+                Box::new(NativeExtractor::with_fallback(default_extractor))
+            } else {
+                Box::new(default_extractor)
+            }
+        })
+    }
+
+    /// Build an API that calls extractpbo through [`DefaultExtractor`] directly,
+    /// without dynamic dispatch. `with_native_reader` has no effect here.
+    pub fn build_default(self) -> PboApiGeneric<DefaultExtractor> {
+        self.build_with(|default_extractor| default_extractor)
+    }
+
+    /// Build an API around the extractor returned by `make`, which receives the
+    /// configured extractpbo runner (timeout, binary path and config applied)
+    pub fn build_with<E: ExtractorClone>(self, make: impl FnOnce(DefaultExtractor) -> E) -> PboApiGeneric<E> {
+        let config = Arc::new(self.config.unwrap_or_default());
+        let timeout = self.timeout.unwrap_or_else(|| Duration::from_secs(u64::from(DEFAULT_TIMEOUT)));
+        let mut default_extractor = DefaultExtractor::with_config(config.clone()).with_timeout(timeout);
+        if let Some(per_gb) = self.timeout_per_gb {
+            default_extractor = default_extractor.with_timeout_per_gb(per_gb);
+        }
+        if let Some(path) = self.extractpbo_path {
+            default_extractor = default_extractor.with_extractpbo_path(path);
+        }
+        for (key, value) in self.env {
+            default_extractor = default_extractor.with_env(key, value);
+        }
+        if let Some(wine_path) = self.wine {
+            default_extractor = default_extractor.with_wine(wine_path);
         }
+        default_extractor = default_extractor.with_lenient(self.lenient);
+        if let Some(level) = self.output_log_level {
+            default_extractor = default_extractor.with_output_log_level(level);
+        }
+        if let Some(version) = self.min_extractpbo_version {
+            default_extractor = default_extractor.with_min_tool_version(version);
+        }
+
+        let mut packer = DefaultPacker::with_config(config.clone()).with_timeout(timeout);
+        if let Some(path) = self.makepbo_path {
+            packer = packer.with_makepbo_path(path);
+        }
+
+        let extractor = make(default_extractor.clone());
+        let temp_manager = match self.temp_base {
+            Some(base) => TempFileManager::with_base(base, Duration::from_secs(TEMP_DIR_MAX_AGE_SECS)),
+            None => TempFileManager::new(),
+        };
+
+        PboApiGeneric {
+            temp_manager,
+            config,
+            extractor,
+            process_extractor: default_extractor,
+            packer,
+            timeout,
+            timeout_per_gb: self.timeout_per_gb,
+            concurrency: self.concurrency.unwrap_or_else(|| {
+                thread::available_parallelism().map_or(1, |workers| workers.get())
+            }),
+            lenient: self.lenient,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_can_extract() {
+        let temp = tempdir().unwrap();
+        let api = PboApi::new(30);
+        let pbo = Path::new("tests/data/mirrorform.pbo");
+
+        assert!(api.can_extract(pbo, temp.path()).unwrap());
+        assert!(api.can_extract(pbo, &temp.path().join("not/created/yet")).unwrap());
+        assert!(!temp.path().join("not").exists());
+        assert!(matches!(api.can_extract(Path::new("missing.pbo"), temp.path()), Err(PboError::InvalidPath(_))));
+        assert!(api.check_space(pbo, temp.path()).is_ok());
+
+        // 100000 entries claiming 4 GiB each are more than any test volume holds
+        let names: Vec<String> = (0..100_000).map(|i| format!("data\\{}.bin", i)).collect();
+        let files: Vec<(&str, &[u8])> = names.iter().map(|name| (name.as_str(), &b""[..])).collect();
+        let mut data = crate::test_utils::build_pbo(&[], &files);
+        let mut offset = 22;
+        for name in &names {
+            offset += name.len() + 1;
+            data[offset + 4..offset + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+            offset += 20;
+        }
+        let huge = temp.path().join("huge.pbo");
+        std::fs::write(&huge, data).unwrap();
+        assert!(!api.can_extract(&huge, temp.path()).unwrap());
+        assert!(matches!(
+            api.check_space(&huge, temp.path()),
+            Err(PboError::FileSystem(FileSystemError::InsufficientSpace { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_required_space() {
+        assert_eq!(required_space(0), 0);
+        assert_eq!(required_space(50), 55);
+        assert_eq!(required_space(99), 109);
+        assert_eq!(required_space(1000), 1100);
+    }
+
+    #[test]
+    fn test_extract_metadata_files() {
+        let temp = tempdir().unwrap();
+        let pbo = temp.path().join("mod.pbo");
+        fs::write(&pbo, crate::test_utils::build_pbo(&[("prefix", "tc\\mirrorform")], &[
+            ("Mod.cpp", b"name = \"Mirrorform\";"),
+            ("config.cpp", b"class CfgPatches {};"),
+            ("data\\readme.txt", b"not at the root"),
+            ("readme.txt", b"Mirrorform uniform"),
+        ])).unwrap();
+
+        let output_dir = temp.path().join("out");
+        let api = PboApi::new(30);
+        let written = api.extract_metadata_files(&pbo, &output_dir).unwrap();
+        assert_eq!(written, vec![output_dir.join("Mod.cpp"), output_dir.join("readme.txt")]);
+        assert_eq!(fs::read_to_string(&written[1]).unwrap(), "Mirrorform uniform");
+        assert!(!output_dir.join("config.cpp").exists() && !output_dir.join("data").exists());
+
+        let config = PboConfig::builder().metadata_files(["config.cpp"]).build();
+        let api = PboApi::builder().with_config(config).build();
+        let written = api.extract_metadata_files(&pbo, &temp.path().join("config")).unwrap();
+        assert_eq!(written, vec![temp.path().join("config").join("config.cpp")]);
+
+        let none = api.extract_metadata_files(Path::new("tests/data/mirrorform.pbo"), &output_dir).unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_output_dir_is_file() {
+        let temp = tempdir().unwrap();
+        let output_file = temp.path().join("output.txt");
+        fs::write(&output_file, b"not a directory").unwrap();
+
+        let api = PboApi::new(30);
+        match api.extract_files(&crate::test_utils::get_test_pbo_path(), &output_file, None) {
+            Err(PboError::FileSystem(FileSystemError::CreateDir { path, reason })) => {
+                assert_eq!(path, output_file);
+                assert_eq!(reason, "output path is a file");
+            }
+            other => panic!("Expected CreateDir error, got {:?}", other),
+        }
+        assert!(api.validate_output_dir(&temp.path().join("missing")).is_ok());
+    }
+
+    #[test]
+    fn test_read_extracted_file_under_prefix() {
+        let temp = tempdir().unwrap();
+        let nested = temp.path().join("tc/mirrorform/uniform");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("mirror.rvmat"), b"rvmat").unwrap();
+        fs::write(temp.path().join("$PBOPREFIX$.txt"), b"tc\\mirrorform").unwrap();
+
+        let bytes = read_extracted_file(temp.path(), "uniform\\Mirror.rvmat", &PboConfig::default()).unwrap();
+        assert_eq!(bytes, b"rvmat");
+        let case_sensitive = PboConfig::builder().case_sensitive(true).build();
+        assert!(read_extracted_file(temp.path(), "uniform\\Mirror.rvmat", &case_sensitive).is_err());
+    }
+
+    #[test]
+    fn test_read_extracted_file_debinarized() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("config.cpp"), b"class CfgPatches {};").unwrap();
+
+        let bytes = read_extracted_file(temp.path(), "config.bin", &PboConfig::default()).unwrap();
+        assert_eq!(bytes, b"class CfgPatches {};");
+
+        // Only the known rename counts, not any file sharing the stem
+        fs::write(temp.path().join("logo.paa"), b"paa").unwrap();
+        assert!(read_extracted_file(temp.path(), "logo.jpg", &PboConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_match_file_list() {
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        let matched = match_file_list(&pbo_path, &["config.bin", "uniform/Mirror.p3d", "missing.sqf"], &PboConfig::default()).unwrap();
+
+        assert_eq!(matched.filter, "config.bin,uniform\\mirror.p3d");
+        assert_eq!(matched.found, vec!["config.bin", "uniform/Mirror.p3d"]);
+        assert_eq!(matched.missing, vec!["missing.sqf"]);
+
+        let config = PboConfig::builder().case_sensitive(true).build();
+        let matched = match_file_list(&pbo_path, &["uniform/Mirror.p3d"], &config).unwrap();
+        assert_eq!(matched.missing, vec!["uniform/Mirror.p3d"]);
+    }
+
+    #[cfg(feature = "compressed")]
+    #[test]
+    fn test_match_file_list_size_limit() {
+        let temp = tempdir().unwrap();
+        let data = fs::read(crate::test_utils::get_test_pbo_path()).unwrap();
+        let pbo_path = temp.path().join("mirrorform.pbo.zst");
+        fs::write(&pbo_path, zstd::encode_all(&data[..], 0).unwrap()).unwrap();
+
+        let config = PboConfig::builder().max_decompressed_size(data.len() as u64 - 1).build();
+        assert!(matches!(match_file_list(&pbo_path, &["config.bin"], &config), Err(PboError::ValidationFailed(_))));
+        assert!(match_file_list(&pbo_path, &["config.bin"], &PboConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_match_file_list_rejects_invalid_entries() {
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        for entry in ["a.paa,b.paa", "*.paa", "", "bad|name.sqf"] {
+            assert!(
+                matches!(match_file_list(&pbo_path, &[entry], &PboConfig::default()), Err(PboError::ValidationFailed(_))),
+                "Entry {:?} should be rejected", entry
+            );
+        }
+    }
+
+    fn header_entry(path: &str, size: u32) -> HeaderEntry {
+        HeaderEntry {
+            path: path.to_string(),
+            packing_method: 0,
+            original_size: 0,
+            reserved: 0,
+            timestamp: 0,
+            data_size: size,
+        }
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let pbo = Path::new("test.pbo");
+        assert_eq!(decode_text(b"class CfgPatches {};".to_vec(), pbo, "config.cpp").unwrap(), "class CfgPatches {};");
+        assert_eq!(decode_text(b"\xEF\xBB\xBFhint \"\xC3\xA9\";".to_vec(), pbo, "init.sqf").unwrap(), "hint \"\u{e9}\";");
+        assert_eq!(decode_text(b"caf\xE9 \x80 \x93".to_vec(), pbo, "init.sqf").unwrap(), "caf\u{e9} \u{20AC} \u{201C}");
+        assert!(matches!(
+            decode_text(b"\x89PNG\r\n\x1a\n\0\0".to_vec(), pbo, "logo.png"),
+            Err(PboError::Encoding { .. })
+        ));
+    }
+
+    #[test]
+    fn test_temp_base() {
+        let base = tempdir().unwrap();
+        let api = PboApi::builder()
+            .with_temp_base(base.path().to_path_buf())
+            .build();
+
+        let temp_dir = api.temp_manager.create_temp_dir().unwrap();
+        assert!(temp_dir.starts_with(base.path()));
+        api.temp_manager.cleanup_temp_dir(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_extension_filter() {
+        assert_eq!(extension_filter(&["paa", ".cpp", "bin"]).unwrap().as_deref(), Some("*.paa,*.cpp,*.bin"));
+        assert_eq!(extension_filter(&[]).unwrap(), None);
+        for invalid in ["", "*.paa", "p/a", "paa,cpp"] {
+            assert!(extension_filter(&[invalid]).is_err(), "{:?} should be rejected", invalid);
+        }
+    }
+
+    #[test]
+    fn test_search_candidates() {
+        let entries = [
+            header_entry("config.bin", 2048),
+            header_entry("data\\texheaders.bin", 64),
+            header_entry("data\\other.bin", 64),
+            header_entry("uniform\\mirror.p3d", 2048),
+            header_entry("logo.PAA", 2048),
+            header_entry("scripts\\fn_init.sqf", 512),
+            header_entry("scripts\\huge.sqf", (SEARCH_MAX_FILE_SIZE + 1) as u32),
+            header_entry("a,b.sqf", 16),
+        ];
+
+        let candidates = search_candidates(&entries, &PboConfig::default());
+        assert_eq!(candidates, vec!["config.bin", "data\\texheaders.bin", "scripts\\fn_init.sqf"]);
+    }
+
+    #[test]
+    fn test_search_extracted() {
+        let temp = tempdir().unwrap();
+        let root = temp.path().join("tc/mirrorform");
+        fs::create_dir_all(root.join("scripts")).unwrap();
+        fs::write(root.join("config.cpp"), b"class CfgPatches { class TC_MirrorForm {}; };").unwrap();
+        fs::write(root.join("scripts/fn_init.sqf"), b"hint \"tc_mirrorform loaded\";").unwrap();
+        fs::write(root.join("scripts/blob.sqf"), b"TC_MirrorForm\0\0binary").unwrap();
+
+        let candidates = vec![
+            "config.bin".to_string(),
+            "scripts\\fn_init.sqf".to_string(),
+            "scripts\\blob.sqf".to_string(),
+            "scripts\\missing.sqf".to_string(),
+        ];
+
+        let matches = search_extracted(temp.path(), &candidates, "TC_MirrorForm", &PboConfig::default());
+        assert_eq!(matches, vec!["config.bin", "scripts\\fn_init.sqf"]);
+
+        let config = PboConfig::builder().case_sensitive(true).build();
+        let matches = search_extracted(temp.path(), &candidates, "TC_MirrorForm", &config);
+        assert_eq!(matches, vec!["config.bin"]);
+    }
+
+    #[test]
+    fn test_read_extracted_file_missing() {
+        let temp = tempdir().unwrap();
+        let result = read_extracted_file(temp.path(), "missing.sqf", &PboConfig::default());
+        assert!(matches!(result, Err(PboError::Extraction(ExtractError::NoFiles))));
     }
 }
@@ -1,12 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "config-file")]
+use std::path::Path;
+#[cfg(feature = "config-file")]
+use crate::error::types::{Result, PboError, FileSystemError};
+use crate::fs::TraversalPolicy;
+use super::constants::{BAD_PBO_INDICATORS, DEFAULT_MAX_DECOMPRESSED_SIZE, DEFAULT_MAX_PATH_LENGTH, DEFAULT_METADATA_FILES, KNOWN_WARNINGS, RESIDUAL_BYTES_INDICATOR};
 
 #[derive(Debug, Clone)]
 pub struct PboConfig {
     bin_file_types: HashMap<String, String>,
     bad_pbo_indicators: Vec<String>,
+    warning_patterns: Vec<String>,
     case_sensitive: bool,
     ignore_path_validation: bool,
+    max_path_length: Option<usize>,
     max_retries: u32,
+    normalize_separators: bool,
+    sniff_bin_content: bool,
+    allowed_special_chars: HashSet<char>,
+    traversal_policy: TraversalPolicy,
+    check_reserved_names: bool,
+    metadata_files: Vec<String>,
+    max_decompressed_size: u64,
 }
 
 impl PboConfig {
@@ -23,10 +38,40 @@ impl PboConfig {
         self.bin_file_types.get(&lookup_name).map(|s| s.as_str())
     }
 
+    /// File name a mapped binary file is converted to. Mappings may name the
+    /// whole target file (`config.cpp`) or only its extension (`cpp`, `.txt`).
+    pub fn bin_target_name(&self, filename: &str) -> Option<String> {
+        let target = self.get_bin_extension(filename)?;
+        let stem = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem);
+        let name = match target.strip_prefix('.') {
+            Some(extension) => format!("{}.{}", stem, extension),
+            None if target.contains('.') => target.to_string(),
+            None => format!("{}.{}", stem, target),
+        };
+        Some(name)
+    }
+
+    /// Whether `message` contains one of the bad PBO indicators.
+    ///
+    /// The defaults are the whole of `BAD_PBO_INDICATORS`, which
+    /// `ExtractResult::is_success` has always checked. Earlier versions only
+    /// used its header and checksum entries here, so output such as
+    /// `Cannot open` or `Error` now also fails the extractor's own validation
+    /// with `PboError::ValidationFailed`.
     pub fn is_bad_pbo(&self, message: &str) -> bool {
         self.bad_pbo_indicators.iter().any(|i| message.contains(i))
     }
 
+    /// Whether an output line is a known, non-fatal warning
+    pub fn is_warning(&self, line: &str) -> bool {
+        self.warning_patterns.iter().any(|w| line.contains(w))
+    }
+
+    /// Whether an output line signals failure. Warning lines never do.
+    pub fn is_error_line(&self, line: &str) -> bool {
+        !self.is_warning(line) && self.is_bad_pbo(line)
+    }
+
     pub fn is_case_sensitive(&self) -> bool {
         self.case_sensitive
     }
@@ -35,18 +80,85 @@ impl PboConfig {
         self.ignore_path_validation
     }
 
+    /// Longest path accepted by path validation, or `None` for no limit.
+    /// Falls back to the platform default when not configured.
+    pub fn max_path_length(&self) -> Option<usize> {
+        self.max_path_length.or(DEFAULT_MAX_PATH_LENGTH)
+    }
+
     pub fn max_retries(&self) -> u32 {
         self.max_retries
     }
+
+    /// Whether parsed file paths have `\` converted to `/`
+    pub fn normalize_separators(&self) -> bool {
+        self.normalize_separators
+    }
+
+    /// Characters the path and filter validators accept even though they are on
+    /// one of their blacklists, e.g. `$` for `$PBOPREFIX$.txt`
+    pub fn allowed_special_chars(&self) -> &HashSet<char> {
+        &self.allowed_special_chars
+    }
+
+    /// Whether `c` is on `blacklist` and not explicitly allowed
+    pub(crate) fn rejects_char(&self, c: char, blacklist: &[char]) -> bool {
+        blacklist.contains(&c) && !self.allowed_special_chars.contains(&c)
+    }
+
+    /// How `PathSanitizer::sanitize` handles `..` components. Path validation
+    /// always rejects them, since it does not clean up the path it checks.
+    pub fn traversal_policy(&self) -> TraversalPolicy {
+        self.traversal_policy
+    }
+
+    /// Whether path validation rejects Windows device names such as `CON`.
+    /// On by default on Windows only.
+    pub fn check_reserved_names(&self) -> bool {
+        self.check_reserved_names
+    }
+
+    /// Whether extensionless files without a bin mapping are checked for the
+    /// rapified config magic and converted to `.cpp` when it matches
+    pub fn sniff_bin_content(&self) -> bool {
+        self.sniff_bin_content
+    }
+
+    /// Names of the root files `PboApi::extract_metadata_files` extracts
+    pub fn metadata_files(&self) -> &[String] {
+        &self.metadata_files
+    }
+
+    /// Largest size in bytes a gzip or zstd compressed PBO may decompress to
+    pub fn max_decompressed_size(&self) -> u64 {
+        self.max_decompressed_size
+    }
+
+    /// Whether `filename` is one of the `metadata_files`, ignoring case unless
+    /// the configuration is case sensitive
+    pub fn is_metadata_file(&self, filename: &str) -> bool {
+        self.metadata_files.iter().any(|name| {
+            if self.case_sensitive { name == filename } else { name.eq_ignore_ascii_case(filename) }
+        })
+    }
 }
 
 #[derive(Default)]
 pub struct PboConfigBuilder {
     bin_file_types: HashMap<String, String>,
     bad_pbo_indicators: Vec<String>,
+    warning_patterns: Vec<String>,
     case_sensitive: bool,
     ignore_path_validation: bool,
+    max_path_length: Option<usize>,
     max_retries: u32,
+    normalize_separators: bool,
+    sniff_bin_content: bool,
+    allowed_special_chars: HashSet<char>,
+    traversal_policy: TraversalPolicy,
+    check_reserved_names: bool,
+    metadata_files: Vec<String>,
+    max_decompressed_size: u64,
 }
 
 impl PboConfigBuilder {
@@ -55,6 +167,9 @@ impl PboConfigBuilder {
             case_sensitive: false,
             ignore_path_validation: false,
             max_retries: 3,
+            normalize_separators: true,
+            check_reserved_names: cfg!(windows),
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
             ..Default::default()
         };
 
@@ -72,13 +187,10 @@ impl PboConfigBuilder {
             builder.bin_file_types.insert(bin.to_string(), ext.to_string());
         }
 
-        // Set default bad PBO indicators
-        builder.bad_pbo_indicators = vec![
-            "DePbo:Pbo unknown header type",
-            "Bad Sha detected",
-            "Bad Sha",
-            "this warning is set as an error",
-        ].into_iter().map(String::from).collect();
+        // Set default bad PBO indicators and known warnings
+        builder.bad_pbo_indicators = BAD_PBO_INDICATORS.iter().map(|s| s.to_string()).collect();
+        builder.warning_patterns = KNOWN_WARNINGS.iter().map(|s| s.to_string()).collect();
+        builder.metadata_files = DEFAULT_METADATA_FILES.iter().map(|s| s.to_string()).collect();
 
         builder
     }
@@ -98,6 +210,43 @@ impl PboConfigBuilder {
         self
     }
 
+    /// Make output lines containing `pattern` fail the operation, even if they
+    /// would otherwise be recognised as a known warning
+    pub fn treat_warning_as_error(mut self, pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        self.warning_patterns.retain(|w| !w.contains(&pattern) && !pattern.contains(w.as_str()));
+        self.bad_pbo_indicators.push(pattern);
+        self
+    }
+
+    /// Downgrade output lines containing `pattern` to warnings, so they no longer
+    /// fail the operation
+    pub fn ignore_indicator(mut self, pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        self.bad_pbo_indicators.retain(|i| *i != pattern);
+        self.warning_patterns.push(pattern);
+        self
+    }
+
+    /// Report residual bytes after the last entry as a warning instead of
+    /// failing, e.g. to inspect slightly corrupt PBOs. The byte count is
+    /// available from `ExtractResult::residual_bytes`.
+    pub fn tolerate_residual_bytes(self) -> Self {
+        self.ignore_indicator(RESIDUAL_BYTES_INDICATOR)
+    }
+
+    /// Replace the default metadata file names, e.g. `["mod.cpp", "credits.txt"]`
+    pub fn metadata_files(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.metadata_files = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Extract `name` along with the default metadata files
+    pub fn add_metadata_file(mut self, name: impl Into<String>) -> Self {
+        self.metadata_files.push(name.into());
+        self
+    }
+
     pub fn case_sensitive(mut self, sensitive: bool) -> Self {
         self.case_sensitive = sensitive;
         self
@@ -108,19 +257,135 @@ impl PboConfigBuilder {
         self
     }
 
+    /// Override the platform path length limit, e.g. on Windows with long
+    /// path support enabled
+    pub fn max_path_length(mut self, length: usize) -> Self {
+        self.max_path_length = Some(length);
+        self
+    }
+
     pub fn max_retries(mut self, retries: u32) -> Self {
         self.max_retries = retries;
         self
     }
 
+    /// Keep the PBO's own `\` separators in parsed file lists instead of
+    /// converting them to `/`, e.g. for passing paths to Windows APIs
+    pub fn normalize_separators(mut self, normalize: bool) -> Self {
+        self.normalize_separators = normalize;
+        self
+    }
+
+    /// Accept `chars` in paths and file filters although the validators reject
+    /// them by default, e.g. `['$']` for `$PBOPREFIX$.txt`. Path traversal and
+    /// control characters are still rejected.
+    pub fn allow_special_chars(mut self, chars: impl IntoIterator<Item = char>) -> Self {
+        self.allowed_special_chars.extend(chars);
+        self
+    }
+
+    /// Reject or strip `..` components in paths cleaned by `PathSanitizer`
+    pub fn traversal_policy(mut self, policy: TraversalPolicy) -> Self {
+        self.traversal_policy = policy;
+        self
+    }
+
+    /// Check paths for Windows device names on any platform, or stop checking
+    /// them on Windows, e.g. when extracting to a filesystem that allows them
+    pub fn check_reserved_names(mut self, check: bool) -> Self {
+        self.check_reserved_names = check;
+        self
+    }
+
+    /// Also convert extensionless files with no bin mapping, e.g. a `config`
+    /// written by some tools, when their contents start with the rapified config
+    /// magic. Off by default, as it renames files based on their contents.
+    pub fn sniff_bin_content(mut self, sniff: bool) -> Self {
+        self.sniff_bin_content = sniff;
+        self
+    }
+
+    /// Fail instead of decompressing a gzip or zstd compressed PBO past
+    /// `bytes`, e.g. to lower `DEFAULT_MAX_DECOMPRESSED_SIZE` for untrusted input
+    pub fn max_decompressed_size(mut self, bytes: u64) -> Self {
+        self.max_decompressed_size = bytes;
+        self
+    }
+
     pub fn build(self) -> PboConfig {
         PboConfig {
             bin_file_types: self.bin_file_types,
             bad_pbo_indicators: self.bad_pbo_indicators,
+            warning_patterns: self.warning_patterns,
             case_sensitive: self.case_sensitive,
             ignore_path_validation: self.ignore_path_validation,
+            max_path_length: self.max_path_length,
             max_retries: self.max_retries,
+            normalize_separators: self.normalize_separators,
+            sniff_bin_content: self.sniff_bin_content,
+            allowed_special_chars: self.allowed_special_chars,
+            traversal_policy: self.traversal_policy,
+            check_reserved_names: self.check_reserved_names,
+            metadata_files: self.metadata_files,
+            max_decompressed_size: self.max_decompressed_size,
+        }
+    }
+}
+
+/// Settings read by [`PboConfig::from_toml_file`]. Every field is optional.
+#[cfg(feature = "config-file")]
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    bin_file_types: HashMap<String, String>,
+    bad_pbo_indicators: Vec<String>,
+    case_sensitive: Option<bool>,
+    max_retries: Option<u32>,
+}
+
+#[cfg(feature = "config-file")]
+impl PboConfig {
+    /// Load a configuration from a TOML file such as
+    ///
+    /// ```toml
+    /// case_sensitive = false
+    /// max_retries = 5
+    /// bad_pbo_indicators = ["no shakey on arma"]
+    ///
+    /// [bin_file_types]
+    /// "rvmat.bin" = "rvmat"
+    /// ```
+    ///
+    /// Bin mappings and bad indicators are added to the defaults; settings the
+    /// file leaves out keep their default values.
+    pub fn from_toml_file(path: &Path) -> Result<PboConfig> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            PboError::FileSystem(FileSystemError::ReadFile {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+        })?;
+        let file: ConfigFile = toml::from_str(&contents).map_err(|e| PboError::InvalidConfig {
+            path: path.to_path_buf(),
+            line: e.span().map(|span| contents.as_bytes()[..span.start].iter().filter(|&&b| b == b'\n').count() + 1),
+            reason: e.message().to_string(),
+        })?;
+
+        // Case sensitivity decides how bin mapping keys are stored, so it goes first
+        let mut builder = PboConfig::builder();
+        if let Some(case_sensitive) = file.case_sensitive {
+            builder = builder.case_sensitive(case_sensitive);
+        }
+        if let Some(max_retries) = file.max_retries {
+            builder = builder.max_retries(max_retries);
+        }
+        for (bin_file, target) in file.bin_file_types {
+            builder = builder.add_bin_mapping(bin_file, target);
+        }
+        for indicator in file.bad_pbo_indicators {
+            builder = builder.add_bad_indicator(indicator);
         }
+        Ok(builder.build())
     }
 }
 
@@ -140,6 +405,13 @@ mod tests {
         assert!(!config.is_case_sensitive());
         assert!(!config.should_ignore_path_validation());
         assert_eq!(config.max_retries(), 3);
+        assert!(config.normalize_separators());
+        assert!(!config.sniff_bin_content());
+        assert!(config.allowed_special_chars().is_empty());
+        assert_eq!(config.traversal_policy(), TraversalPolicy::Reject);
+        assert_eq!(config.check_reserved_names(), cfg!(windows));
+        assert!(config.is_metadata_file("Mod.cpp"));
+        assert!(!config.is_metadata_file("config.cpp"));
         assert_eq!(config.get_bin_extension("config.bin"), Some("config.cpp"));
         assert_eq!(config.get_bin_extension("unknown.bin"), None);
     }
@@ -188,12 +460,72 @@ mod tests {
         assert!(config.should_ignore_path_validation());
     }
 
+    #[test]
+    fn test_max_path_length() {
+        assert_eq!(PboConfig::default().max_path_length(), DEFAULT_MAX_PATH_LENGTH);
+
+        let config = PboConfig::builder()
+            .max_path_length(1024)
+            .build();
+        assert_eq!(config.max_path_length(), Some(1024));
+    }
+
     #[test]
     fn test_default_bad_indicators() {
         let config = PboConfig::default();
         assert!(config.is_bad_pbo("DePbo:Pbo unknown header type"));
         assert!(config.is_bad_pbo("Bad Sha detected"));
         assert!(!config.is_bad_pbo("Normal message"));
+
+        // The defaults cover every indicator, not just header and checksum failures
+        for indicator in BAD_PBO_INDICATORS {
+            assert!(config.is_bad_pbo(indicator), "{} is not a default indicator", indicator);
+        }
+        assert!(config.is_bad_pbo("Cannot open mirrorform.pbo"));
+        assert!(config.is_bad_pbo("residual bytes in file: 12"));
+    }
+
+    #[test]
+    fn test_warning_classification() {
+        let config = PboConfig::default();
+        assert!(config.is_warning("Warning: arma pbo is missing a prefix"));
+        assert!(!config.is_error_line("Warning: arma pbo is missing a prefix"));
+        assert!(config.is_error_line("residual bytes in file"));
+        assert!(!config.is_error_line("config.bin"));
+    }
+
+    #[test]
+    fn test_ignore_indicator() {
+        let config = PboConfig::builder()
+            .ignore_indicator("residual bytes in file")
+            .build();
+
+        assert!(config.is_warning("Error: residual bytes in file"));
+        assert!(!config.is_error_line("Error: residual bytes in file"));
+        assert!(config.is_error_line("Bad Sha detected"));
+    }
+
+    #[test]
+    fn test_treat_warning_as_error() {
+        let config = PboConfig::builder()
+            .treat_warning_as_error("missing a prefix")
+            .build();
+
+        assert!(!config.is_warning("arma pbo is missing a prefix"));
+        assert!(config.is_error_line("arma pbo is missing a prefix"));
+        assert!(config.is_warning("reserved field non zero"));
+    }
+
+    #[test]
+    fn test_bin_target_name() {
+        let config = PboConfig::builder()
+            .add_bin_mapping("custom.bin", "txt")
+            .add_bin_mapping("other.bin", ".hpp")
+            .build();
+        assert_eq!(config.bin_target_name("config.bin").as_deref(), Some("config.cpp"));
+        assert_eq!(config.bin_target_name("Custom.bin").as_deref(), Some("Custom.txt"));
+        assert_eq!(config.bin_target_name("other.bin").as_deref(), Some("other.hpp"));
+        assert_eq!(config.bin_target_name("readme.txt"), None);
     }
 
     #[test]
@@ -230,4 +562,39 @@ mod tests {
         assert!(config.is_case_sensitive());
         assert_eq!(config.max_retries(), 10);
     }
+
+    #[cfg(feature = "config-file")]
+    #[test]
+    fn test_from_toml_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("pbo_tools.toml");
+        std::fs::write(&path, r#"
+case_sensitive = true
+max_retries = 7
+bad_pbo_indicators = ["Custom bad indicator"]
+
+[bin_file_types]
+"Custom.bin" = "custom.txt"
+"#).unwrap();
+
+        let config = PboConfig::from_toml_file(&path).unwrap();
+        assert!(config.is_case_sensitive());
+        assert_eq!(config.max_retries(), 7);
+        assert!(config.is_bad_pbo("Custom bad indicator"));
+        assert!(config.is_bad_pbo("Bad Sha detected"));
+        assert_eq!(config.get_bin_extension("Custom.bin"), Some("custom.txt"));
+        assert_eq!(config.get_bin_extension("config.bin"), Some("config.cpp"));
+
+        std::fs::write(&path, "case_sensitive = true\nmax_retries = \"many\"").unwrap();
+        match PboConfig::from_toml_file(&path) {
+            Err(error @ PboError::InvalidConfig { line: Some(2), .. }) => {
+                assert!(error.to_string().contains(&format!("{}, line 2:", path.display())), "{}", error);
+            }
+            other => panic!("Expected InvalidConfig on line 2, got {:?}", other),
+        }
+        assert!(matches!(
+            PboConfig::from_toml_file(&temp.path().join("missing.toml")),
+            Err(PboError::FileSystem(FileSystemError::ReadFile { .. }))
+        ));
+    }
 }
@@ -9,6 +9,12 @@ pub const DEFAULT_BIN_MAPPINGS: &[(&str, &str)] = &[
     ("script.bin", "script.cpp"),
 ];
 
+/// Files at the PBO root describing the mod rather than holding its assets,
+/// extracted by `PboApi::extract_metadata_files` unless configured otherwise
+pub const DEFAULT_METADATA_FILES: &[&str] = &[
+    "mod.cpp", "meta.cpp", "readme.txt", "readme.md", "changelog.txt", "changelog.md", "license.txt",
+];
+
 /// Known warnings that should not fail the operation
 pub const KNOWN_WARNINGS: &[&str] = &[
     // Normal warning indicating non-standard header fields
@@ -21,6 +27,28 @@ pub const KNOWN_WARNINGS: &[&str] = &[
     "arma pbo is missing a prefix",
 ];
 
+/// Characters `FileOperation::is_safe_path` rejects unless the configuration
+/// allows them, see `PboConfigBuilder::allow_special_chars`
+pub const SUSPICIOUS_PATH_CHARS: &[char] = &['<', '>', '|', '*', '?', '"', '`', '$', '&', '{', '}', ';', '#', '='];
+
+/// Characters `FileOperation::validate_path_safety` rejects unless allowed
+pub const INVALID_PATH_CHARS: &[char] = &['<', '>', '|', '"', '*', '?'];
+
+/// Device names Windows reserves in every directory, with or without an extension
+pub const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Characters rejected in glob file filters and the destination path passed to
+/// extractpbo unless allowed
+pub const INVALID_FILTER_CHARS: &[char] = &['<', '>', '|', '"', '\''];
+
+/// Output reporting data left over after the last entry, preceded or followed
+/// by the number of bytes
+pub const RESIDUAL_BYTES_INDICATOR: &str = "residual bytes in file";
+
 /// Indicators that a PBO is corrupted or invalid
 pub const BAD_PBO_INDICATORS: &[&str] = &[
     // Unknown PBO header type
@@ -31,7 +59,7 @@ pub const BAD_PBO_INDICATORS: &[&str] = &[
     // Warning treated as error (configurable)
     "this warning is set as an error",
     // Corrupted file structure
-    "residual bytes in file",
+    RESIDUAL_BYTES_INDICATOR,
     // File access errors
     "Cannot open",
     // General operation failures
@@ -39,11 +67,133 @@ pub const BAD_PBO_INDICATORS: &[&str] = &[
     "Failed",
 ];
 
+/// Output reporting a SHA mismatch, tolerated with `ExtractOptions::skip_checksum`
+pub const CHECKSUM_FAILURE_INDICATORS: &[&str] = &[
+    "Bad Sha",
+];
+
+/// Output of Wine itself, rather than extractpbo, explaining why a run failed
+pub const WINE_FAILURE_INDICATORS: &[&str] = &[
+    // The Windows binary or one of its DLLs is missing
+    "wine: cannot find",
+    "wine: could not load",
+    // The prefix could not be created or is unusable
+    "wine: failed to",
+    "wine: Bad EXE format",
+    // extractpbo crashed inside Wine
+    "wine: Unhandled",
+];
+
+/// Binary used to run extractpbo under Wine when no path is given
+pub const DEFAULT_WINE_PROGRAM: &str = "wine";
+
+/// Seconds extractpbo is given to print its version before it is killed
+pub const VERSION_PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// Extensions left out of the file hash of version 2 `.bisign` signatures
+pub const SIGNATURE_V2_UNHASHED_EXTENSIONS: &[&str] = &[
+    "paa", "jpg", "p3d", "tga", "rvmat", "lip", "ogg", "wss", "png", "rtm", "pac", "fxy", "wrp",
+];
+
+/// Extensions covered by the file hash of version 3 `.bisign` signatures
+pub const SIGNATURE_V3_HASHED_EXTENSIONS: &[&str] = &[
+    "sqf", "inc", "bikb", "ext", "fsm", "sqm", "hpp", "cfg", "sqs", "h", "sqfc",
+];
+
 /// Default retry count for operations
 pub const DEFAULT_MAX_RETRIES: u32 = 3;
 
+/// Output of a failed extractpbo run that could not open the PBO, typically
+/// because another process (an antivirus scan, the game) holds it locked.
+/// Such runs are retried like transient spawn errors.
+pub const LOCKED_PBO_INDICATORS: &[&str] = &[
+    "Cannot open",
+    "being used by another process",
+    "sharing violation",
+];
+
+/// Longest `-F=` file list passed to a single extractpbo run. Longer explicit
+/// lists are split over several runs, keeping each command line well below
+/// the 32,767 character limit of Windows.
+pub const MAX_FILE_FILTER_LENGTH: usize = 16_000;
+
+/// Base delay between retries in milliseconds, multiplied by the attempt number
+pub const RETRY_BACKOFF_MS: u64 = 250;
+
+/// Default number of levels of nested PBOs followed by `recurse_nested`
+pub const DEFAULT_MAX_NESTED_DEPTH: usize = 4;
+
+/// Age after which leftover temp directories from other processes are purged
+pub const TEMP_DIR_MAX_AGE_SECS: u64 = 24 * 60 * 60;
+
+/// Path length limit used when `PboConfig` does not set one. Only Windows
+/// enforces the legacy MAX_PATH limit by default.
+pub const DEFAULT_MAX_PATH_LENGTH: Option<usize> = if cfg!(windows) { Some(260) } else { None };
+
 /// Common file extensions in PBOs
 pub const COMMON_PBO_EXTENSIONS: &[&str] = &["pbo", "xbo", "ifa"];
 
+/// Extension of encrypted PBOs, which neither extractpbo nor the native reader can unpack
+pub const ENCRYPTED_PBO_EXTENSION: &str = "ebo";
+
+/// Packing method of the leading header entry that introduces the properties block ("Vers")
+pub const PACKING_METHOD_VERSION: u32 = 0x5665_7273;
+
+/// Packing method of an LZSS-compressed entry ("Cprs")
+pub const PACKING_METHOD_COMPRESSED: u32 = 0x4370_7273;
+
+/// Packing method of an encrypted entry ("Encr")
+pub const PACKING_METHOD_ENCRYPTED: u32 = 0x456e_6372;
+
+/// Packing method of an uncompressed entry
+pub const PACKING_METHOD_UNCOMPRESSED: u32 = 0;
+
+/// Bytes of output an LZSS back-reference can reach back into
+pub const LZSS_WINDOW_SIZE: usize = 4096;
+
+/// Most bytes of output one byte of LZSS data can expand to: a flag byte and
+/// eight 2-byte back-references of 18 bytes each, 144 bytes from 17
+pub const LZSS_MAX_RATIO: u64 = 9;
+
+/// Largest buffer reserved up front when reading an entry into memory. The
+/// header sizes are not trusted beyond this; larger entries grow as they are read.
+pub const MAX_ENTRY_PREALLOCATION: u64 = 16 * 1024 * 1024;
+
+/// Size of the chunks entry contents are streamed in
+pub const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// First bytes of a gzip stream
+pub const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+
+/// First bytes of a zstd frame
+pub const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+
+/// Largest size a gzip or zstd compressed PBO may decompress to unless
+/// `PboConfigBuilder::max_decompressed_size` says otherwise, guarding against
+/// decompression bombs
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: u64 = 16 * 1024 * 1024 * 1024;
+
+/// Extensions dropped from the name of a decompressed PBO, e.g. `mod.pbo.gz`
+pub const COMPRESSED_PBO_EXTENSIONS: &[&str] = &["gz", "zst", "zstd"];
+
+/// First bytes of a rapified (binarized) config
+pub const RAPIFIED_MAGIC: &[u8] = b"\0raP";
+
+/// Extension given to extensionless files sniffed as rapified configs
+pub const SNIFFED_BIN_EXTENSION: &str = "cpp";
+
+/// Extra free space, as a percentage of the extracted size, required by
+/// `PboApi::can_extract` on top of the extracted size itself
+pub const FREE_SPACE_MARGIN_PERCENT: u64 = 10;
+
+/// Largest file, in bytes, that content search extracts and scans
+pub const SEARCH_MAX_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Extensions of binary formats that content search never scans
+pub const SEARCH_SKIPPED_EXTENSIONS: &[&str] = &[
+    "paa", "pac", "p3d", "rtm", "wrp", "wss", "ogg", "wav", "lip",
+    "jpg", "png", "tga", "bik", "fxy", "shp", "dbf", "bisign", "bikey",
+];
+
 /// Common binary file extensions that may need conversion
 pub const BINARY_EXTENSIONS: &[&str] = &["bin", "binpbo", "binconfig"];
\ No newline at end of file
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use super::reader::PboEntry;
+
+/// Difference between the file lists of two PBOs, see [`diff_entries`].
+///
+/// All lists are sorted. Paths in `added`, `common` and `changed` are spelled
+/// as in the newer PBO, those in `removed` as in the older one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PboDiff {
+    /// Files only in the newer PBO
+    pub added: Vec<String>,
+    /// Files only in the older PBO
+    pub removed: Vec<String>,
+    /// Files in both PBOs
+    pub common: Vec<String>,
+    /// Files in both PBOs whose sizes differ. Only detected when both
+    /// listings carry sizes.
+    pub changed: Vec<String>,
+}
+
+impl PboDiff {
+    /// Whether both PBOs list the same files with the same sizes
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare the entries of an older PBO (`old`) with those of a newer one (`new`).
+/// Paths are matched regardless of separator, and of case unless `case_sensitive`.
+pub fn diff_entries(old: &[PboEntry], new: &[PboEntry], case_sensitive: bool) -> PboDiff {
+    let key = |path: &str| {
+        let path = path.replace('\\', "/");
+        if case_sensitive { path } else { path.to_lowercase() }
+    };
+    let old_by_key: HashMap<String, &PboEntry> = old.iter().map(|e| (key(&e.path), e)).collect();
+    let new_by_key: HashMap<String, &PboEntry> = new.iter().map(|e| (key(&e.path), e)).collect();
+
+    let mut diff = PboDiff::default();
+    for (key, entry) in &new_by_key {
+        match old_by_key.get(key) {
+            Some(old_entry) => {
+                diff.common.push(entry.path.clone());
+                if let (Some(old_size), Some(new_size)) = (old_entry.size, entry.size) {
+                    if old_size != new_size {
+                        diff.changed.push(entry.path.clone());
+                    }
+                }
+            }
+            None => diff.added.push(entry.path.clone()),
+        }
+    }
+    diff.removed = old_by_key
+        .iter()
+        .filter(|(key, _)| !new_by_key.contains_key(*key))
+        .map(|(_, entry)| entry.path.clone())
+        .collect();
+
+    diff.added.sort();
+    diff.removed.sort();
+    diff.common.sort();
+    diff.changed.sort();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, size: Option<u64>) -> PboEntry {
+        PboEntry {
+            path: path.to_string(),
+            size,
+            timestamp: None,
+            packing_method: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_entries() {
+        let old = [
+            entry("config.bin", Some(100)),
+            entry("data\\logo.paa", Some(50)),
+            entry("old.sqf", Some(10)),
+            entry("readme.txt", None),
+        ];
+        let new = [
+            entry("config.bin", Some(120)),
+            entry("Data/Logo.paa", Some(50)),
+            entry("new.sqf", Some(10)),
+            entry("readme.txt", Some(5)),
+        ];
+
+        let diff = diff_entries(&old, &new, false);
+        assert_eq!(diff.added, vec!["new.sqf"]);
+        assert_eq!(diff.removed, vec!["old.sqf"]);
+        assert_eq!(diff.common, vec!["Data/Logo.paa", "config.bin", "readme.txt"]);
+        assert_eq!(diff.changed, vec!["config.bin"]);
+        assert!(!diff.is_empty());
+
+        let diff = diff_entries(&old, &new, true);
+        assert_eq!(diff.added, vec!["Data/Logo.paa", "new.sqf"]);
+        assert_eq!(diff.removed, vec!["data\\logo.paa", "old.sqf"]);
+
+        assert!(diff_entries(&old, &old, false).is_empty());
+    }
+}
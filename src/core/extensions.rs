@@ -0,0 +1,54 @@
+use std::path::Path;
+use super::constants::{COMMON_PBO_EXTENSIONS, ENCRYPTED_PBO_EXTENSION};
+
+/// Whether `path` has one of the `COMMON_PBO_EXTENSIONS`, ignoring case
+pub fn is_pbo_path(path: &Path) -> bool {
+    archive_extension(path).is_some()
+}
+
+/// Whether `path` has the encrypted PBO extension (`.ebo`), ignoring case
+pub fn is_encrypted_pbo_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(ENCRYPTED_PBO_EXTENSION))
+}
+
+/// Upper-case archive kind ("PBO", "XBO" or "IFA") for user-facing messages.
+/// Paths without a known extension are labelled "PBO".
+pub fn archive_label(path: &Path) -> String {
+    archive_extension(path)
+        .unwrap_or("pbo")
+        .to_uppercase()
+}
+
+fn archive_extension(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?;
+    COMMON_PBO_EXTENSIONS
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(ext))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pbo_extensions() {
+        assert!(is_pbo_path(Path::new("addon.pbo")));
+        assert!(is_pbo_path(Path::new("addon.XBO")));
+        assert!(is_pbo_path(Path::new("sounds.ifa")));
+        assert!(!is_pbo_path(Path::new("addon.zip")));
+        assert!(!is_pbo_path(Path::new("addon")));
+        assert!(is_encrypted_pbo_path(Path::new("addon.EBO")));
+        assert!(!is_encrypted_pbo_path(Path::new("addon.pbo")));
+    }
+
+    #[test]
+    fn test_archive_label() {
+        assert_eq!(archive_label(Path::new("addon.pbo")), "PBO");
+        assert_eq!(archive_label(Path::new("sounds.Ifa")), "IFA");
+        assert_eq!(archive_label(Path::new("addon.xbo")), "XBO");
+        assert_eq!(archive_label(Path::new("addon")), "PBO");
+    }
+}
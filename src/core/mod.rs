@@ -1,10 +1,20 @@
 pub mod api;
 pub mod config;
 pub mod constants;
+pub mod diff;
+pub mod extensions;
 pub mod pbo;
+pub mod reader;
+pub mod signature;
+pub mod tools;
 pub mod test_utils;
 
 pub use api::*;
 pub use config::*;
 pub use constants::*;
+pub use diff::*;
+pub use extensions::*;
 pub use pbo::*;
+pub use reader::*;
+pub use signature::BisignVersion;
+pub use tools::*;
@@ -1,9 +1,10 @@
 use std::path::Path;
+use std::sync::Arc;
 use super::config::PboConfig;
-use crate::error::types::{PboError, Result};
-use crate::extract::{ExtractorClone, DefaultExtractor, ExtractResult, ExtractOptions};
+use crate::error::types::{PboError, ExtractError, Result};
+use crate::extract::{ExtractorClone, DefaultExtractor, ExtractResult, ExtractOptions, parse_prefix};
 use crate::fs::TempFileManager;
-use super::api::PboApiOps;
+use super::api::{PboApiOps, read_extracted_file, match_file_list, remove_temp_dir};
 
 #[derive(Debug, Clone)]
 pub struct PboCore {
@@ -14,10 +15,11 @@ pub struct PboCore {
 
 impl PboCore {
     pub fn new(config: Option<PboConfig>) -> Self {
+        let config = config.unwrap_or_default();
         Self {
             temp_manager: TempFileManager::new(),
-            config: config.unwrap_or_default(),
-            extractor: Box::new(DefaultExtractor::new()),
+            extractor: Box::new(DefaultExtractor::with_config(Arc::new(config.clone()))),
+            config,
         }
     }
 
@@ -29,10 +31,7 @@ impl PboCore {
     }
 
     pub fn extract_prefix(&self, output: &str) -> Option<String> {
-        output
-            .lines()
-            .find(|line| line.starts_with("prefix="))
-            .map(|line| line.split('=').nth(1).unwrap_or("").trim().trim_end_matches(';').to_string())
+        parse_prefix(output)
     }
 }
 
@@ -78,4 +77,30 @@ impl PboApiOps for PboCore {
         self.validate_pbo_exists(pbo_path)?;
         self.extractor.extract_with_options(pbo_path, output_dir, options)
     }
+
+    fn extract_file_to_memory(&self, pbo_path: &Path, internal_path: &str) -> Result<Vec<u8>> {
+        let temp_dir = self.temp_manager.create_temp_dir()?;
+        let result = self.extract_files(pbo_path, &temp_dir, Some(internal_path))
+            .and_then(|_| read_extracted_file(&temp_dir, internal_path, &self.config));
+
+        remove_temp_dir(&self.temp_manager, &temp_dir);
+        result
+    }
+
+    fn extract_file_list(&self, pbo_path: &Path, output_dir: &Path, files: &[&str]) -> Result<ExtractResult> {
+        self.validate_pbo_exists(pbo_path)?;
+        let file_list = match_file_list(pbo_path, files, &self.config)?;
+        if file_list.found.is_empty() {
+            return Err(PboError::Extraction(ExtractError::NoFiles));
+        }
+
+        let options = ExtractOptions {
+            file_filter: Some(file_list.filter),
+            ..ExtractOptions::for_extraction()
+        };
+        let mut result = self.extractor.extract_with_options(pbo_path, output_dir, options)?;
+        result.found_files = file_list.found;
+        result.missing_files = file_list.missing;
+        Ok(result)
+    }
 }
\ No newline at end of file
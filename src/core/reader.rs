@@ -0,0 +1,1228 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use log::{debug, trace};
+use sha1::{Digest, Sha1};
+use crate::error::types::{Result, PboError, ExtractError, FileSystemError};
+use crate::fs::compressed;
+use super::extensions::is_encrypted_pbo_path;
+use super::signature::{self, BisignVersion};
+use super::constants::{
+    COPY_BUFFER_SIZE, DEFAULT_MAX_DECOMPRESSED_SIZE, LZSS_MAX_RATIO, LZSS_WINDOW_SIZE, MAX_ENTRY_PREALLOCATION,
+    PACKING_METHOD_COMPRESSED, PACKING_METHOD_ENCRYPTED, PACKING_METHOD_UNCOMPRESSED, PACKING_METHOD_VERSION,
+};
+
+/// A file inside a PBO along with whatever metadata the source provided.
+///
+/// Entries parsed from the native header always carry size, timestamp and
+/// packing method; entries parsed from extractpbo output only have size and
+/// timestamp in detailed mode, and never the packing method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PboEntry {
+    /// Path of the file inside the PBO
+    pub path: String,
+    /// Uncompressed size in bytes
+    pub size: Option<u64>,
+    /// Modification time as seconds since the Unix epoch
+    pub timestamp: Option<u64>,
+    /// Packing method from the header (0 for uncompressed, "Cprs" for LZSS)
+    pub packing_method: Option<u32>,
+}
+
+impl PboEntry {
+    /// Whether the entry is stored LZSS compressed. `false` when the packing
+    /// method is unknown.
+    pub fn is_compressed(&self) -> bool {
+        self.packing_method == Some(PACKING_METHOD_COMPRESSED)
+    }
+
+    /// Short name of the packing method, e.g. for a listing column
+    pub fn packing_label(&self) -> &'static str {
+        match self.packing_method {
+            Some(PACKING_METHOD_UNCOMPRESSED) => "stored",
+            Some(PACKING_METHOD_COMPRESSED) => "compressed",
+            Some(PACKING_METHOD_ENCRYPTED) => "encrypted",
+            Some(_) => "unknown",
+            None => "-",
+        }
+    }
+}
+
+/// A raw header record from a PBO.
+///
+/// Each header record is a null-terminated filename followed by five
+/// little-endian `u32` fields (20 bytes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderEntry {
+    /// Path of the file inside the PBO, as stored (usually backslash separated)
+    pub path: String,
+    /// Packing method (0 for uncompressed, "Cprs" for LZSS)
+    pub packing_method: u32,
+    /// Uncompressed size of the file
+    pub original_size: u32,
+    /// Reserved field, normally zero
+    pub reserved: u32,
+    /// Modification time as seconds since the Unix epoch
+    pub timestamp: u32,
+    /// Size of the file data stored in the PBO
+    pub data_size: u32,
+}
+
+impl HeaderEntry {
+    /// Size of the file once unpacked. Uncompressed entries often leave
+    /// `original_size` at zero, in which case the stored size is used.
+    pub fn uncompressed_size(&self) -> u64 {
+        if self.original_size != 0 {
+            u64::from(self.original_size)
+        } else {
+            u64::from(self.data_size)
+        }
+    }
+}
+
+/// Path of an entry stored as `entry_path` relative to the directory it is
+/// extracted to, or `None` when it is empty or contains anything but plain
+/// names, such as `..` or a drive, and could therefore leave that directory
+pub(crate) fn entry_relative_path(entry_path: &str) -> Option<PathBuf> {
+    let relative: PathBuf = entry_path.split(['\\', '/']).filter(|part| !part.is_empty()).collect();
+    let contained = relative.components().all(|c| matches!(c, std::path::Component::Normal(_)));
+    (contained && !relative.as_os_str().is_empty()).then_some(relative)
+}
+
+/// Pure-Rust PBO reader that parses the header without invoking extractpbo.
+///
+/// Readers are usually opened from a file, but [`NativePboReader::from_reader`]
+/// accepts any `Read + Seek` source, such as a PBO held in memory.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pbo_tools::core::NativePboReader;
+/// use std::path::Path;
+///
+/// let reader = NativePboReader::open(Path::new("mission.pbo")).unwrap();
+/// println!("Prefix: {:?}", reader.prefix());
+/// for entry in reader.entries() {
+///     println!("{} ({:?} bytes)", entry.path, entry.size);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NativePboReader<R = BufReader<File>> {
+    path: Option<PathBuf>,
+    source: R,
+    entries: Vec<HeaderEntry>,
+    properties: Vec<(String, String)>,
+    data_offset: u64,
+    /// Length of the source, measured on first use
+    file_len: Option<u64>,
+}
+
+impl NativePboReader {
+    /// Open a PBO file and parse its header. A PBO compressed with gzip or zstd
+    /// (e.g. `mod.pbo.gz`, recognized by its magic bytes) is decompressed to a
+    /// temp file first, which requires the `compressed` feature, and may not
+    /// decompress to more than `DEFAULT_MAX_DECOMPRESSED_SIZE` bytes.
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::open_with_limit(path, DEFAULT_MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// Like [`open`](Self::open), but a compressed PBO may decompress to at
+    /// most `max_decompressed_size` bytes.
+    pub fn open_with_limit(path: &Path, max_decompressed_size: u64) -> Result<Self> {
+        debug!("Opening PBO with native reader: {:?}", path);
+        let mut file = File::open(path).map_err(|e| {
+            PboError::FileSystem(FileSystemError::ReadFile {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+        })?;
+        let wrapper = compressed::sniff_wrapper(&mut file).map_err(|e| read_error(Some(path), e))?;
+        if let Some(wrapper) = wrapper {
+            file = compressed::unwrap_to_tempfile(file, wrapper, path, max_decompressed_size)?;
+        }
+
+        Self::parse(BufReader::new(file), Some(path.to_path_buf()))
+    }
+
+    /// Parse the header of an already opened PBO file, which must be positioned
+    /// at its start. The header is parsed once, so `prefix`, `entries` and
+    /// `total_size` can all be queried from the same reader.
+    pub fn from_file(file: File) -> Result<Self> {
+        debug!("Opening PBO from file handle");
+        Self::parse(BufReader::new(file), None)
+    }
+}
+
+impl<R: Read + Seek> NativePboReader<R> {
+    /// Parse the header of a PBO read from `reader`, which must be positioned at
+    /// the start of the PBO. Entry data is read from it on demand.
+    pub fn from_reader(reader: R) -> Result<Self> {
+        debug!("Opening PBO from reader");
+        Self::parse(reader, None)
+    }
+
+    fn parse(mut source: R, path: Option<PathBuf>) -> Result<Self> {
+        let Header { entries, properties } = parse_header(&mut source)?;
+        let mut reader = Self {
+            path,
+            source,
+            entries,
+            properties,
+            data_offset: 0,
+            file_len: None,
+        };
+        reader.data_offset = reader.source.stream_position().map_err(|e| reader.read_error(e))?;
+
+        debug!("Parsed {} entries, data starts at offset {}", reader.entries.len(), reader.data_offset);
+        Ok(reader)
+    }
+
+    /// Path of the PBO this reader was opened from, or `None` for readers
+    /// created with [`NativePboReader::from_reader`] or [`NativePboReader::from_file`]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// File entries listed in the PBO header, in stored order
+    pub fn entries(&self) -> Vec<PboEntry> {
+        self.entries
+            .iter()
+            .map(|entry| PboEntry {
+                path: entry.path.clone(),
+                size: Some(entry.uncompressed_size()),
+                timestamp: Some(u64::from(entry.timestamp)),
+                packing_method: Some(entry.packing_method),
+            })
+            .collect()
+    }
+
+    /// Raw header records, in stored order
+    pub fn header_entries(&self) -> &[HeaderEntry] {
+        &self.entries
+    }
+
+    /// Whether this is an encrypted PBO: one opened from a `.ebo` file or with
+    /// entries stored with the "Encr" packing method. The header can still be
+    /// listed, but reading any entry fails.
+    pub fn is_encrypted(&self) -> bool {
+        self.path.as_deref().is_some_and(is_encrypted_pbo_path)
+            || self.entries.iter().any(|entry| entry.packing_method == PACKING_METHOD_ENCRYPTED)
+    }
+
+    /// Key/value pairs from the properties block following the "Vers" entry
+    pub fn properties(&self) -> &[(String, String)] {
+        &self.properties
+    }
+
+    /// The header extension properties (`prefix`, `hash`, `version` and any keys
+    /// a mod adds) by name. When a key is stored more than once the first value
+    /// is kept, matching `property`.
+    pub fn extension_properties(&self) -> HashMap<String, String> {
+        let mut properties = HashMap::with_capacity(self.properties.len());
+        for (key, value) in &self.properties {
+            properties.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        properties
+    }
+
+    /// Value of the property named `key`, compared case-insensitively
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Value of the `prefix` property, if present and non-empty
+    pub fn prefix(&self) -> Option<String> {
+        self.property("prefix")
+            .map(|value| value.trim().to_string())
+            .filter(|prefix| !prefix.is_empty())
+    }
+
+    /// Whether the PBO ships binarized content, judged from the header alone.
+    ///
+    /// A `config.cpp` means the config is text and a `config.bin` without one
+    /// means it is binarized. With no config at all, a `version` property
+    /// (written by binarizing packers) plus other `.bin` entries counts as
+    /// binarized. Anything else is indeterminate and reported as `false`.
+    pub fn is_binarized(&self) -> bool {
+        let file_names: Vec<String> = self.entries
+            .iter()
+            .map(|e| e.path.rsplit(['\\', '/']).next().unwrap_or(&e.path).to_lowercase())
+            .collect();
+
+        if file_names.iter().any(|name| name == "config.cpp") {
+            return false;
+        }
+        if file_names.iter().any(|name| name == "config.bin") {
+            return true;
+        }
+
+        let has_version = self.property("version").is_some_and(|v| !v.trim().is_empty());
+        has_version && file_names.iter().any(|name| name.ends_with(".bin"))
+    }
+
+    /// Offset of the first byte of entry data, directly after the header
+    pub fn data_offset(&self) -> u64 {
+        self.data_offset
+    }
+
+    /// Offset just past the last entry's data, where the checksum trailer starts
+    pub fn data_end(&self) -> u64 {
+        self.data_offset + self.entries.iter().map(|e| u64::from(e.data_size)).sum::<u64>()
+    }
+
+    /// Read the contents of the entry stored as `entry_path`, decompressing it if
+    /// needed. Paths are compared case-insensitively and may use either separator.
+    ///
+    /// Entries of an encrypted PBO fail with `PboError::InvalidPbo`, entries that
+    /// are neither stored uncompressed nor LZSS compressed with
+    /// `ExtractError::InvalidFile`.
+    pub fn read_entry(&mut self, entry_path: &str) -> Result<Vec<u8>> {
+        let (index, offset) = self.find_entry(entry_path)?;
+        self.read_entry_at(index, offset)
+    }
+
+    /// Write the contents of the entry stored as `entry_path` to `out`,
+    /// decompressing it on the fly, and return the number of bytes written.
+    /// Paths are matched like in `read_entry`.
+    ///
+    /// Only a small buffer is held in memory, so large entries can be piped into
+    /// a hasher or socket. The checksum of a compressed entry is verified after
+    /// all of its contents were written, so on a mismatch `out` already holds them.
+    pub fn extract_entry_to<W: Write>(&mut self, entry_path: &str, out: W) -> Result<u64> {
+        let (index, offset) = self.find_entry(entry_path)?;
+        self.copy_entry_at(index, offset, out)
+    }
+
+    /// Like `extract_entry_to`, for the entry at `index` in `header_entries`.
+    /// Fails with `ExtractError::NoFiles` when `index` is out of range.
+    pub fn extract_index_to<W: Write>(&mut self, index: usize, out: W) -> Result<u64> {
+        if index >= self.entries.len() {
+            return Err(PboError::Extraction(ExtractError::NoFiles));
+        }
+        let offset = self.entry_offset(index);
+        self.copy_entry_at(index, offset, out)
+    }
+
+    /// Index and data offset of the entry stored as `entry_path`
+    fn find_entry(&self, entry_path: &str) -> Result<(usize, u64)> {
+        let wanted = entry_path.replace('/', "\\");
+        let index = self.entries
+            .iter()
+            .position(|e| e.path.replace('/', "\\").eq_ignore_ascii_case(&wanted))
+            .ok_or(PboError::Extraction(ExtractError::NoFiles))?;
+        Ok((index, self.entry_offset(index)))
+    }
+
+    /// Offset of the data of the entry at `index`. Entry data is stored back to
+    /// back in header order.
+    fn entry_offset(&self, index: usize) -> u64 {
+        self.data_offset + self.entries[..index]
+            .iter()
+            .map(|e| u64::from(e.data_size))
+            .sum::<u64>()
+    }
+
+    /// Turn the reader into an iterator yielding each entry's path and contents
+    /// in stored order. Only one entry's data is held in memory at a time.
+    pub fn into_contents(self) -> EntryContents<R> {
+        let offset = self.data_offset;
+        EntryContents { reader: self, index: 0, offset }
+    }
+
+    fn read_entry_at(&mut self, index: usize, offset: u64) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(preallocation(&self.entries[index]));
+        self.copy_entry_at(index, offset, &mut data)?;
+        Ok(data)
+    }
+
+    /// Write the unpacked contents of the entry at `index`, whose data starts at
+    /// `offset`, to `out`
+    fn copy_entry_at<W: Write>(&mut self, index: usize, offset: u64, mut out: W) -> Result<u64> {
+        if self.is_encrypted() {
+            return Err(encrypted_pbo());
+        }
+        let entry = &self.entries[index];
+        if entry.packing_method != PACKING_METHOD_UNCOMPRESSED && entry.packing_method != PACKING_METHOD_COMPRESSED {
+            return Err(PboError::Extraction(ExtractError::InvalidFile(format!(
+                "{} uses unsupported packing method {:#010x}", entry.path, entry.packing_method
+            ))));
+        }
+        trace!("Reading {} ({} bytes at offset {})", entry.path, entry.data_size, offset);
+
+        let data_size = u64::from(entry.data_size);
+        let compressed = entry.packing_method == PACKING_METHOD_COMPRESSED;
+        let original_size = entry.original_size as usize;
+        let entry_path = PathBuf::from(&entry.path);
+        if offset + data_size > self.file_len()? {
+            return Err(PboError::InvalidPbo("Truncated PBO data".to_string()));
+        }
+
+        let pbo_path = self.path.clone();
+        let read_error = |e: io::Error| read_error(pbo_path.as_deref(), e);
+        let write_error = |e: io::Error| PboError::FileSystem(FileSystemError::Write {
+            path: entry_path.clone(),
+            reason: e.to_string(),
+        });
+
+        self.source.seek(SeekFrom::Start(offset)).map_err(read_error)?;
+        let mut data = (&mut self.source).take(data_size);
+        if compressed {
+            let input = BufReader::new(data).bytes().map(|byte| byte.map_err(read_error));
+            return decompress_lzss_to(input, original_size, |chunk| out.write_all(chunk).map_err(write_error));
+        }
+
+        let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+        let mut written = 0;
+        loop {
+            let read = data.read(&mut buffer).map_err(read_error)?;
+            if read == 0 {
+                return Ok(written);
+            }
+            out.write_all(&buffer[..read]).map_err(write_error)?;
+            written += read as u64;
+        }
+    }
+
+    /// Total size of all entries once unpacked, excluding the header and checksum.
+    ///
+    /// Fails with `InvalidPbo` when the header describes more data than the file
+    /// holds, since the sizes it lists cannot be trusted then.
+    pub fn total_size(&mut self) -> Result<u64> {
+        self.checked_file_len()?;
+        Ok(self.entries.iter().map(HeaderEntry::uncompressed_size).sum())
+    }
+
+    /// The SHA1 stored after the data block, or `None` if the PBO has no trailer
+    pub fn stored_checksum(&mut self) -> Result<Option<[u8; 20]>> {
+        let data_end = self.data_end();
+        let file_len = self.checked_file_len()?;
+
+        if file_len == data_end {
+            debug!("PBO has no checksum trailer");
+            return Ok(None);
+        }
+        if file_len - data_end < 21 {
+            return Err(PboError::InvalidPbo("Malformed checksum trailer".to_string()));
+        }
+
+        let mut trailer = [0u8; 21];
+        self.source.seek(SeekFrom::Start(data_end)).map_err(|e| self.read_error(e))?;
+        self.source.read_exact(&mut trailer).map_err(|e| self.read_error(e))?;
+        if trailer[0] != 0 {
+            return Err(PboError::InvalidPbo("Malformed checksum trailer".to_string()));
+        }
+
+        let mut checksum = [0u8; 20];
+        checksum.copy_from_slice(&trailer[1..]);
+        Ok(Some(checksum))
+    }
+
+    /// SHA1 computed over the header and data block, i.e. everything the trailer covers
+    pub fn compute_checksum(&mut self) -> Result<[u8; 20]> {
+        let data_end = self.data_end();
+        self.source.seek(SeekFrom::Start(0)).map_err(|e| self.read_error(e))?;
+
+        let mut hasher = Sha1::new();
+        let copied = io::copy(&mut (&mut self.source).take(data_end), &mut hasher)
+            .map_err(|e| self.read_error(e))?;
+        if copied != data_end {
+            return Err(PboError::InvalidPbo("Truncated PBO data".to_string()));
+        }
+
+        Ok(hasher.finalize().into())
+    }
+
+    /// Hash of the entry names used by BI's `.bisign` signatures: the lowercased
+    /// paths of all entries holding data, sorted and hashed together
+    pub fn name_hash(&self) -> [u8; 20] {
+        signature::name_hash(&self.entries)
+    }
+
+    /// Hash of the file contents used by BI's `.bisign` signatures, covering the
+    /// unpacked contents of the entries `version` selects in stored order
+    pub fn file_hash(&mut self, version: BisignVersion) -> Result<[u8; 20]> {
+        let mut hashed = Vec::new();
+        let mut offset = self.data_offset;
+        for (index, entry) in self.entries.iter().enumerate() {
+            if version.hashes_file(&entry.path) {
+                hashed.push((index, offset));
+            }
+            offset += u64::from(entry.data_size);
+        }
+        trace!("Hashing {} of {} entries for {:?}", hashed.len(), self.entries.len(), version);
+
+        let contents = hashed.into_iter().map(|(index, offset)| self.read_entry_at(index, offset));
+        signature::file_hash(contents, version)
+    }
+
+    /// Verify the stored SHA1 against the file contents.
+    ///
+    /// Returns `Ok(true)` when the checksum matches, `Ok(false)` when the PBO
+    /// carries no checksum, and `ExtractError::ChecksumFailed` on a mismatch.
+    pub fn verify_checksum(&mut self) -> Result<bool> {
+        let Some(stored) = self.stored_checksum()? else {
+            return Ok(false);
+        };
+
+        if self.compute_checksum()? != stored {
+            debug!("Checksum mismatch for {:?}", self.path);
+            return Err(PboError::Extraction(ExtractError::ChecksumFailed(self.path.clone().unwrap_or_default())));
+        }
+        Ok(true)
+    }
+
+    /// Length of the PBO file, checked to hold all the data the header lists
+    fn checked_file_len(&mut self) -> Result<u64> {
+        let data_end = self.data_end();
+        let file_len = self.file_len()?;
+        if file_len < data_end {
+            return Err(PboError::InvalidPbo(format!(
+                "Data block ends at {} but file is only {} bytes", data_end, file_len
+            )));
+        }
+        Ok(file_len)
+    }
+
+    /// Length of the PBO file, looked up once
+    fn file_len(&mut self) -> Result<u64> {
+        if let Some(file_len) = self.file_len {
+            return Ok(file_len);
+        }
+        let file_len = self.source.seek(SeekFrom::End(0)).map_err(|e| self.read_error(e))?;
+        self.file_len = Some(file_len);
+        Ok(file_len)
+    }
+
+    fn read_error(&self, e: io::Error) -> PboError {
+        read_error(self.path.as_deref(), e)
+    }
+}
+
+/// Error for a failed read from the PBO at `path`
+fn read_error(path: Option<&Path>, e: io::Error) -> PboError {
+    PboError::FileSystem(FileSystemError::ReadFile {
+        path: path.map(Path::to_path_buf).unwrap_or_default(),
+        reason: e.to_string(),
+    })
+}
+
+/// Iterator over the contents of a PBO's entries, created by
+/// [`NativePboReader::into_contents`].
+///
+/// An entry that cannot be read (e.g. an encrypted one) yields an error and
+/// iteration continues with the next entry.
+#[derive(Debug)]
+pub struct EntryContents<R = BufReader<File>> {
+    reader: NativePboReader<R>,
+    index: usize,
+    offset: u64,
+}
+
+impl<R: Read + Seek> Iterator for EntryContents<R> {
+    type Item = Result<(String, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.reader.entries.get(self.index)?;
+        let path = entry.path.clone();
+        let offset = self.offset;
+        self.offset += u64::from(entry.data_size);
+
+        let data = self.reader.read_entry_at(self.index, offset);
+        self.index += 1;
+        Some(data.map(|data| (path, data)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.reader.entries.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Whether two PBOs have the same content, e.g. for deduplication.
+///
+/// The stored SHA1 trailers are compared first, so PBOs with different hashes
+/// are never read in full. Matching hashes are confirmed by hashing both files
+/// when their sizes match. Returns `InvalidPbo` if either PBO has no trailer.
+pub fn pbos_equal(a: &Path, b: &Path) -> Result<bool> {
+    let mut reader_a = NativePboReader::open(a)?;
+    let mut reader_b = NativePboReader::open(b)?;
+
+    let stored_a = stored_checksum_required(&mut reader_a, a)?;
+    let stored_b = stored_checksum_required(&mut reader_b, b)?;
+    if stored_a != stored_b {
+        debug!("Stored checksums of {:?} and {:?} differ", a, b);
+        return Ok(false);
+    }
+
+    if reader_a.checked_file_len()? != reader_b.checked_file_len()? {
+        debug!("{:?} and {:?} have matching checksums but different sizes", a, b);
+        return Ok(false);
+    }
+
+    Ok(reader_a.compute_checksum()? == reader_b.compute_checksum()?)
+}
+
+pub(crate) fn stored_checksum_required(reader: &mut NativePboReader, path: &Path) -> Result<[u8; 20]> {
+    reader.stored_checksum()?.ok_or_else(|| {
+        PboError::InvalidPbo(format!("{} has no checksum trailer", path.display()))
+    })
+}
+
+struct Header {
+    entries: Vec<HeaderEntry>,
+    properties: Vec<(String, String)>,
+}
+
+fn parse_header<R: Read>(reader: &mut R) -> Result<Header> {
+    let mut entries = Vec::new();
+    let mut properties = Vec::new();
+
+    for index in 0usize.. {
+        let path = match read_string(reader) {
+            // Without a terminated name the file cannot start with a header record
+            Err(_) if index == 0 => return Err(bad_magic()),
+            path => path?,
+        };
+        let packing_method = read_u32(reader)?;
+        let original_size = read_u32(reader)?;
+        let reserved = read_u32(reader)?;
+        let timestamp = read_u32(reader)?;
+        let data_size = read_u32(reader)?;
+
+        if index == 0 {
+            check_signature(&path, packing_method, [original_size, reserved, timestamp, data_size])?;
+        }
+
+        if path.is_empty() {
+            if packing_method == PACKING_METHOD_VERSION {
+                trace!("Reading properties block");
+                properties.extend(read_properties(reader)?);
+                continue;
+            }
+            // An empty name with any other packing method terminates the header
+            break;
+        }
+
+        trace!("Header entry: {} ({} bytes)", path, data_size);
+        entries.push(HeaderEntry {
+            path,
+            packing_method,
+            original_size,
+            reserved,
+            timestamp,
+            data_size,
+        });
+    }
+
+    Ok(Header { entries, properties })
+}
+
+/// Check that the first header record matches a known PBO layout: the "Vers"
+/// product entry of Arma-era PBOs, the zeroed terminator of an empty PBO, or a
+/// file entry as in older PBOs without properties.
+fn check_signature(path: &str, packing_method: u32, fields: [u32; 4]) -> Result<()> {
+    let valid = if path.is_empty() {
+        packing_method == PACKING_METHOD_VERSION
+            || (packing_method == PACKING_METHOD_UNCOMPRESSED && fields.iter().all(|&f| f == 0))
+    } else {
+        !path.contains(|c: char| c.is_control())
+            && [PACKING_METHOD_UNCOMPRESSED, PACKING_METHOD_COMPRESSED, PACKING_METHOD_ENCRYPTED]
+                .contains(&packing_method)
+    };
+
+    if !valid {
+        debug!("Unrecognized first header record {:?} with packing method {:#x}", path, packing_method);
+        return Err(bad_magic());
+    }
+    Ok(())
+}
+
+/// Bytes to reserve for the unpacked contents of `entry`. The header sizes come
+/// from the file, so the reservation is bounded by what the stored data can
+/// expand to and by `MAX_ENTRY_PREALLOCATION`.
+fn preallocation(entry: &HeaderEntry) -> usize {
+    let expandable = u64::from(entry.data_size).saturating_mul(LZSS_MAX_RATIO);
+    entry.uncompressed_size().min(expandable).min(MAX_ENTRY_PREALLOCATION) as usize
+}
+
+fn bad_magic() -> PboError {
+    PboError::InvalidPbo("not a PBO (bad magic)".to_string())
+}
+
+pub(crate) fn encrypted_pbo() -> PboError {
+    PboError::InvalidPbo("encrypted PBO (.ebo) cannot be extracted".to_string())
+}
+
+fn read_properties<R: Read>(reader: &mut R) -> Result<Vec<(String, String)>> {
+    let mut properties = Vec::new();
+    loop {
+        let key = read_string(reader)?;
+        if key.is_empty() {
+            return Ok(properties);
+        }
+        let value = read_string(reader)?;
+        trace!("Property: {}={}", key, value);
+        properties.push((key, value));
+    }
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    let mut bytes = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte).map_err(|_| truncated())?;
+        if byte[0] == 0 {
+            break;
+        }
+        bytes.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf).map_err(|_| truncated())?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Decompress an LZSS-packed ("Cprs") entry into `expected` bytes, passing
+/// the output to `emit` in chunks as it is decoded. Returns the bytes produced.
+///
+/// Each flag byte covers eight items, least significant bit first: a set bit is
+/// a literal byte, a clear bit a two byte back-reference holding a 12 bit
+/// distance and a 4 bit length (plus 3). References before the start of the
+/// output yield spaces. The packed data ends with a u32 sum of the output bytes.
+fn decompress_lzss_to(
+    input: impl Iterator<Item = Result<u8>>,
+    expected: usize,
+    emit: impl FnMut(&[u8]) -> Result<()>,
+) -> Result<u64> {
+    let corrupt = || PboError::InvalidPbo("Corrupt compressed entry".to_string());
+    let mut input = input.fuse();
+    let mut next = || input.next().unwrap_or_else(|| Err(corrupt()));
+    let mut out = LzssOutput::new(emit);
+
+    while out.written < expected {
+        let flags = next()?;
+        for bit in 0..8 {
+            if out.written >= expected {
+                break;
+            }
+            if flags & (1 << bit) != 0 {
+                out.push(next()?)?;
+                continue;
+            }
+
+            let low = next()?;
+            let high = next()?;
+            let distance = usize::from(low) | (usize::from(high & 0xF0) << 4);
+            let length = usize::from(high & 0x0F) + 3;
+            if distance == 0 {
+                return Err(corrupt());
+            }
+            for _ in 0..length.min(expected - out.written) {
+                out.push(out.back_reference(distance))?;
+            }
+        }
+    }
+    let (written, sum) = out.finish()?;
+
+    let mut stored = [0u8; 4];
+    for byte in &mut stored {
+        *byte = next()?;
+    }
+    if u32::from_le_bytes(stored) != sum {
+        return Err(PboError::InvalidPbo("Checksum mismatch in compressed entry".to_string()));
+    }
+    Ok(written)
+}
+
+/// Output of `decompress_lzss_to`: the window back-references read from and
+/// the chunk waiting to be emitted
+struct LzssOutput<F> {
+    window: Vec<u8>,
+    chunk: Vec<u8>,
+    written: usize,
+    sum: u32,
+    emit: F,
+}
+
+impl<F: FnMut(&[u8]) -> Result<()>> LzssOutput<F> {
+    fn new(emit: F) -> Self {
+        Self {
+            window: vec![0; LZSS_WINDOW_SIZE],
+            chunk: Vec::with_capacity(COPY_BUFFER_SIZE),
+            written: 0,
+            sum: 0,
+            emit,
+        }
+    }
+
+    fn push(&mut self, byte: u8) -> Result<()> {
+        self.window[self.written % LZSS_WINDOW_SIZE] = byte;
+        self.sum = self.sum.wrapping_add(u32::from(byte));
+        self.written += 1;
+        self.chunk.push(byte);
+        if self.chunk.len() == COPY_BUFFER_SIZE {
+            (self.emit)(&self.chunk)?;
+            self.chunk.clear();
+        }
+        Ok(())
+    }
+
+    /// The byte `distance` bytes before the end of the output, or a space
+    /// before its start
+    fn back_reference(&self, distance: usize) -> u8 {
+        match self.written.checked_sub(distance) {
+            Some(position) => self.window[position % LZSS_WINDOW_SIZE],
+            None => b' ',
+        }
+    }
+
+    /// Emit the remaining output, returning the byte count and sum
+    fn finish(mut self) -> Result<(u64, u32)> {
+        if !self.chunk.is_empty() {
+            (self.emit)(&self.chunk)?;
+        }
+        Ok((self.written as u64, self.sum))
+    }
+}
+
+fn truncated() -> PboError {
+    PboError::InvalidPbo("Truncated PBO header".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+    use crate::test_utils;
+
+    #[test]
+    fn test_read_test_pbo_header() {
+        let reader = NativePboReader::open(&test_utils::get_test_pbo_path()).unwrap();
+        assert_eq!(reader.prefix(), Some("tc\\mirrorform".to_string()));
+
+        let headers = reader.header_entries();
+        assert!(!headers.is_empty());
+        let config = headers.iter().find(|e| e.path == "config.bin").unwrap();
+        assert_eq!(config.packing_method, 0);
+        assert_eq!(config.original_size, config.data_size);
+        assert!(headers.iter().any(|e| e.path == "uniform\\mirror.p3d"));
+        assert!(reader.data_offset() > 0);
+
+        let entries = reader.entries();
+        assert_eq!(entries.len(), headers.len());
+        let config_entry = entries.iter().find(|e| e.path == "config.bin").unwrap();
+        assert_eq!(config_entry.size, Some(config.uncompressed_size()));
+        assert_eq!(config_entry.timestamp, Some(u64::from(config.timestamp)));
+        assert_eq!(config_entry.packing_method, Some(PACKING_METHOD_UNCOMPRESSED));
+        assert!(!config_entry.is_compressed());
+        assert_eq!(config_entry.packing_label(), "stored");
+    }
+
+    #[test]
+    fn test_extension_properties() {
+        let temp = tempdir().unwrap();
+        let reader = open_built(temp.path(), &[
+            ("prefix", "tc\\mirrorform"),
+            ("hash", "0123ABCD"),
+            ("Mirrorform_Build", "42"),
+            ("hash", "FFFF"),
+        ], &[("config.cpp", b"class CfgPatches {};")]);
+
+        let properties = reader.extension_properties();
+        assert_eq!(properties.len(), 3);
+        assert_eq!(properties["prefix"], "tc\\mirrorform");
+        assert_eq!(properties["hash"], "0123ABCD");
+        assert_eq!(properties["Mirrorform_Build"], "42");
+
+        drop(reader);
+        let plain = open_built(temp.path(), &[], &[("config.cpp", b"")]);
+        assert!(plain.extension_properties().is_empty());
+    }
+
+    #[test]
+    fn test_packing_label() {
+        let entry = |packing_method| PboEntry {
+            path: "config.bin".to_string(),
+            size: None,
+            timestamp: None,
+            packing_method,
+        };
+        assert!(entry(Some(PACKING_METHOD_COMPRESSED)).is_compressed());
+        assert_eq!(entry(Some(PACKING_METHOD_COMPRESSED)).packing_label(), "compressed");
+        assert_eq!(entry(Some(PACKING_METHOD_ENCRYPTED)).packing_label(), "encrypted");
+        assert_eq!(entry(Some(1)).packing_label(), "unknown");
+        assert!(!entry(None).is_compressed());
+        assert_eq!(entry(None).packing_label(), "-");
+    }
+
+    fn open_built(dir: &Path, properties: &[(&str, &str)], files: &[(&str, &[u8])]) -> NativePboReader {
+        let path = dir.join("built.pbo");
+        fs::write(&path, test_utils::build_pbo(properties, files)).unwrap();
+        NativePboReader::open(&path).unwrap()
+    }
+
+    #[test]
+    fn test_is_binarized() {
+        let reader = NativePboReader::open(&test_utils::get_test_pbo_path()).unwrap();
+        assert!(reader.is_binarized());
+
+        let temp = tempdir().unwrap();
+        let text = open_built(temp.path(), &[("prefix", "tc\\text")], &[("config.cpp", b"class CfgPatches {};")]);
+        assert!(!text.is_binarized());
+
+        let both = open_built(temp.path(), &[], &[("config.bin", b"\0raP"), ("Config.cpp", b"")]);
+        assert!(!both.is_binarized());
+
+        let no_config = open_built(temp.path(), &[("version", "25011610")], &[("texHeaders.bin", b"0DHT")]);
+        assert!(no_config.is_binarized());
+
+        let indeterminate = open_built(temp.path(), &[], &[("data\\logo.paa", b"paa")]);
+        assert!(!indeterminate.is_binarized());
+        assert_eq!(indeterminate.property("PREFIX"), None);
+    }
+
+    #[test]
+    fn test_truncated_header() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("truncated.pbo");
+        fs::write(&path, b"\0sreV\0\0\0\0").unwrap();
+
+        match NativePboReader::open(&path) {
+            Err(PboError::InvalidPbo(msg)) => assert!(msg.contains("Truncated")),
+            other => panic!("Expected InvalidPbo error, got {:?}", other),
+        }
+    }
+
+    /// A header record with the given name and packing method and zeroed sizes
+    fn header_record(name: &[u8], packing_method: u32) -> Vec<u8> {
+        let mut record = name.to_vec();
+        record.push(0);
+        record.extend(packing_method.to_le_bytes());
+        record.extend([0u8; 16]);
+        record
+    }
+
+    #[test]
+    fn test_bad_magic() {
+        let temp = tempdir().unwrap();
+        let not_a_pbo = [
+            b"invalid data".to_vec(),
+            // "Vers" stored big-endian instead of as the little-endian u32
+            header_record(b"", u32::from_be_bytes(*b"sreV")),
+            header_record(b"config.cpp", 0x0403_0201),
+            header_record(b"\x89PNG\r\n\x1a\n", 0),
+        ];
+
+        for (i, data) in not_a_pbo.iter().enumerate() {
+            let path = temp.path().join(format!("bad{}.pbo", i));
+            fs::write(&path, data).unwrap();
+            match NativePboReader::open(&path) {
+                Err(PboError::InvalidPbo(msg)) => assert_eq!(msg, "not a PBO (bad magic)", "case {}", i),
+                other => panic!("Expected bad magic for case {}, got {:?}", i, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_legacy_and_empty_signatures() {
+        // Older PBOs start directly with a file entry, without a "Vers" record
+        let mut legacy = b"config.cpp\0".to_vec();
+        legacy.extend([0u8; 12]);
+        legacy.extend(4u32.to_le_bytes());
+        legacy.extend([0u8; 21]);
+        legacy.extend(b"test");
+        let reader = NativePboReader::from_reader(io::Cursor::new(legacy)).unwrap();
+        assert_eq!(reader.header_entries().len(), 1);
+
+        let empty = NativePboReader::from_reader(io::Cursor::new(vec![0u8; 21])).unwrap();
+        assert!(empty.header_entries().is_empty());
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let mut reader = NativePboReader::open(&test_utils::get_test_pbo_path()).unwrap();
+        assert!(reader.stored_checksum().unwrap().is_some());
+        assert!(reader.verify_checksum().unwrap());
+    }
+
+    #[test]
+    fn test_verify_checksum_mismatch() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("modified.pbo");
+        let mut data = fs::read(test_utils::get_test_pbo_path()).unwrap();
+        let last_data_byte = data.len() - 22;
+        data[last_data_byte] ^= 0xFF;
+        fs::write(&path, &data).unwrap();
+
+        let mut reader = NativePboReader::open(&path).unwrap();
+        match reader.verify_checksum() {
+            Err(PboError::Extraction(ExtractError::ChecksumFailed(failed))) => assert_eq!(failed, path),
+            other => panic!("Expected ChecksumFailed error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_verify_checksum_without_trailer() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("no_sha.pbo");
+        let data = fs::read(test_utils::get_test_pbo_path()).unwrap();
+        fs::write(&path, &data[..data.len() - 21]).unwrap();
+
+        let mut reader = NativePboReader::open(&path).unwrap();
+        assert_eq!(reader.stored_checksum().unwrap(), None);
+        assert!(!reader.verify_checksum().unwrap());
+    }
+
+    #[test]
+    fn test_pbos_equal() {
+        let temp = tempdir().unwrap();
+        let original = test_utils::get_test_pbo_path();
+        let data = fs::read(&original).unwrap();
+
+        let copy = temp.path().join("copy.pbo");
+        fs::write(&copy, &data).unwrap();
+        assert!(pbos_equal(&original, &copy).unwrap());
+        assert!(!pbos_equal(&original, Path::new("tests/data/headgear_pumpkin.pbo")).unwrap());
+
+        // Same stored hash, different content
+        let tampered = temp.path().join("tampered.pbo");
+        let mut tampered_data = data.clone();
+        let last_data_byte = tampered_data.len() - 22;
+        tampered_data[last_data_byte] ^= 0xFF;
+        fs::write(&tampered, &tampered_data).unwrap();
+        assert!(!pbos_equal(&original, &tampered).unwrap());
+
+        let no_sha = temp.path().join("no_sha.pbo");
+        fs::write(&no_sha, &data[..data.len() - 21]).unwrap();
+        assert!(matches!(pbos_equal(&original, &no_sha), Err(PboError::InvalidPbo(_))));
+    }
+
+    #[test]
+    fn test_total_size() {
+        let temp = tempdir().unwrap();
+        let mut reader = open_built(temp.path(), &[("prefix", "tc\\size")], &[
+            ("config.cpp", b"class CfgPatches {};"),
+            ("data\\logo.paa", &[0u8; 100]),
+        ]);
+        assert_eq!(reader.total_size().unwrap(), 120);
+
+        let mut reader = NativePboReader::open(&test_utils::get_test_pbo_path()).unwrap();
+        let expected: u64 = reader.header_entries().iter().map(HeaderEntry::uncompressed_size).sum();
+        assert!(expected > 0);
+        assert_eq!(reader.total_size().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_entry_size_without_original_size() {
+        // Uncompressed entries may leave the original size at zero
+        let mut data = test_utils::build_pbo(&[], &[("config.cpp", b"class CfgPatches {};")]);
+        let size_offset = 22 + "config.cpp".len() + 1 + 4;
+        data[size_offset..size_offset + 4].copy_from_slice(&0u32.to_le_bytes());
+        let reader = NativePboReader::from_reader(io::Cursor::new(data)).unwrap();
+        assert_eq!(reader.header_entries()[0].original_size, 0);
+        assert_eq!(reader.entries()[0].size, Some(20));
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = test_utils::get_test_pbo_path();
+        let opened = NativePboReader::open(&path).unwrap();
+        let mut reader = NativePboReader::from_file(File::open(&path).unwrap()).unwrap();
+
+        assert_eq!(reader.path(), None);
+        assert_eq!(reader.prefix(), opened.prefix());
+        assert_eq!(reader.entries(), opened.entries());
+        let total_size = reader.total_size().unwrap();
+        assert_eq!(reader.total_size().unwrap(), total_size);
+        assert!(reader.verify_checksum().unwrap());
+    }
+
+    #[test]
+    fn test_total_size_truncated_data() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("truncated_data.pbo");
+        let data = test_utils::build_pbo(&[], &[("config.cpp", &[b'x'; 64])]);
+        fs::write(&path, &data[..data.len() - 32]).unwrap();
+
+        let mut reader = NativePboReader::open(&path).unwrap();
+        assert!(matches!(reader.total_size(), Err(PboError::InvalidPbo(_))));
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let data = fs::read(test_utils::get_test_pbo_path()).unwrap();
+        let mut from_memory = NativePboReader::from_reader(io::Cursor::new(data)).unwrap();
+        let mut from_file = NativePboReader::open(&test_utils::get_test_pbo_path()).unwrap();
+
+        assert_eq!(from_memory.path(), None);
+        assert_eq!(from_memory.header_entries(), from_file.header_entries());
+        assert_eq!(from_memory.prefix(), from_file.prefix());
+        assert!(from_memory.verify_checksum().unwrap());
+
+        let config = from_memory.read_entry("config.bin").unwrap();
+        assert!(config.starts_with(b"\0raP"));
+        assert_eq!(config, from_file.read_entry("CONFIG.BIN").unwrap());
+    }
+
+    #[test]
+    fn test_read_entry() {
+        let temp = tempdir().unwrap();
+        let mut reader = open_built(temp.path(), &[], &[
+            ("config.cpp", b"class CfgPatches {};"),
+            ("data\\logo.paa", b"paa"),
+        ]);
+
+        assert_eq!(reader.read_entry("data/logo.paa").unwrap(), b"paa");
+        assert_eq!(reader.read_entry("config.cpp").unwrap(), b"class CfgPatches {};");
+        assert!(matches!(
+            reader.read_entry("missing.sqf"),
+            Err(PboError::Extraction(ExtractError::NoFiles))
+        ));
+    }
+
+    #[test]
+    fn test_into_contents() {
+        let temp = tempdir().unwrap();
+        let reader = open_built(temp.path(), &[], &[
+            ("config.cpp", b"class CfgPatches {};"),
+            ("data\\logo.paa", b"paa"),
+        ]);
+
+        let contents: Vec<(String, Vec<u8>)> = reader.into_contents().collect::<Result<_>>().unwrap();
+        assert_eq!(contents, vec![
+            ("config.cpp".to_string(), b"class CfgPatches {};".to_vec()),
+            ("data\\logo.paa".to_string(), b"paa".to_vec()),
+        ]);
+    }
+
+    #[test]
+    fn test_read_entry_untrusted_size() {
+        let entry = |original_size, data_size| HeaderEntry {
+            path: "config.cpp".to_string(),
+            packing_method: PACKING_METHOD_COMPRESSED,
+            original_size,
+            reserved: 0,
+            timestamp: 0,
+            data_size,
+        };
+        assert_eq!(preallocation(&entry(100, 20)), 100);
+        assert_eq!(preallocation(&entry(u32::MAX, 4)), 36);
+        assert_eq!(preallocation(&entry(u32::MAX, u32::MAX)), MAX_ENTRY_PREALLOCATION as usize);
+
+        // A few bytes claiming to unpack to 4 GiB fail instead of allocating it
+        let mut data = test_utils::build_pbo(&[], &[("secret.sqf", b"data")]);
+        let method_offset = 22 + "secret.sqf".len() + 1;
+        data[method_offset..method_offset + 4].copy_from_slice(&PACKING_METHOD_COMPRESSED.to_le_bytes());
+        data[method_offset + 4..method_offset + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut reader = NativePboReader::from_reader(io::Cursor::new(data)).unwrap();
+        assert!(reader.read_entry("secret.sqf").is_err());
+    }
+
+    #[test]
+    fn test_read_entry_encrypted() {
+        let temp = tempdir().unwrap();
+        let mut data = test_utils::build_pbo(&[], &[("secret.sqf", b"data")]);
+        // Packing method of the only entry, right after its name
+        let method_offset = 22 + "secret.sqf".len() + 1;
+        data[method_offset..method_offset + 4].copy_from_slice(&PACKING_METHOD_ENCRYPTED.to_le_bytes());
+        let path = temp.path().join("encrypted.pbo");
+        fs::write(&path, data).unwrap();
+
+        let mut reader = NativePboReader::open(&path).unwrap();
+        assert!(reader.is_encrypted());
+        assert_eq!(reader.entries().len(), 1);
+        assert!(matches!(reader.read_entry("secret.sqf"), Err(PboError::InvalidPbo(_))));
+
+        // Renamed to .ebo, an otherwise plain PBO is treated as encrypted too
+        let ebo = temp.path().join("plain.ebo");
+        fs::write(&ebo, test_utils::build_pbo(&[], &[("config.cpp", b"class CfgPatches {};")])).unwrap();
+        let mut reader = NativePboReader::open(&ebo).unwrap();
+        assert!(reader.is_encrypted());
+        assert!(matches!(reader.read_entry("config.cpp"), Err(PboError::InvalidPbo(_))));
+    }
+
+    #[test]
+    fn test_extract_entry_to() {
+        let temp = tempdir().unwrap();
+        let mut reader = open_built(temp.path(), &[], &[
+            ("config.cpp", b"class CfgPatches {};"),
+            ("data\\logo.paa", b"paa"),
+        ]);
+
+        let mut out = Vec::new();
+        assert_eq!(reader.extract_entry_to("config.cpp", &mut out).unwrap(), 20);
+        assert_eq!(out, b"class CfgPatches {};");
+        assert!(matches!(
+            reader.extract_entry_to("missing.sqf", &mut out),
+            Err(PboError::Extraction(ExtractError::NoFiles))
+        ));
+
+        let mut out = Vec::new();
+        assert_eq!(reader.extract_index_to(1, &mut out).unwrap(), 3);
+        assert_eq!(out, b"paa");
+        assert!(matches!(
+            reader.extract_index_to(2, &mut out),
+            Err(PboError::Extraction(ExtractError::NoFiles))
+        ));
+    }
+
+    fn decompress_lzss(packed: &[u8], expected: usize) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let written = decompress_lzss_to(packed.iter().map(|&b| Ok(b)), expected, |chunk| {
+            out.extend_from_slice(chunk);
+            Ok(())
+        })?;
+        assert_eq!(written, out.len() as u64);
+        Ok(out)
+    }
+
+    #[test]
+    fn test_decompress_lzss() {
+        // Three literals, then a back-reference 3 bytes back with length 9
+        let mut packed = vec![0b0000_0111, b'a', b'b', b'c', 0x03, 0x06];
+        let expected = b"abcabcabcabc";
+        let sum: u32 = expected.iter().map(|&b| u32::from(b)).sum();
+        packed.extend(sum.to_le_bytes());
+        assert_eq!(decompress_lzss(&packed, expected.len()).unwrap(), expected);
+
+        // A reference before the start of the output produces spaces
+        let mut packed = vec![0b0000_0010, 0x02, 0x00, b'x'];
+        packed.extend((3 * u32::from(b' ') + u32::from(b'x')).to_le_bytes());
+        assert_eq!(decompress_lzss(&packed, 4).unwrap(), b"   x");
+
+        let mut corrupt = vec![0b0000_0111, b'a', b'b', b'c', 0x03, 0x06];
+        corrupt.extend(0u32.to_le_bytes());
+        assert!(matches!(decompress_lzss(&corrupt, 12), Err(PboError::InvalidPbo(_))));
+        assert!(matches!(decompress_lzss(&[0xFF, b'a'], 4), Err(PboError::InvalidPbo(_))));
+        assert!(matches!(decompress_lzss(&[0b0000_0001, b'a', 0x00, 0x00], 4), Err(PboError::InvalidPbo(_))));
+
+        // Output longer than a chunk is emitted in several pieces
+        let length = COPY_BUFFER_SIZE + 10;
+        let mut packed = Vec::new();
+        // One literal, then back-references of 18 bytes one byte back
+        let items = 1 + (length - 1).div_ceil(18);
+        for item in 0..items {
+            if item % 8 == 0 {
+                packed.push(if item == 0 { 0b0000_0001 } else { 0 });
+            }
+            packed.extend(if item == 0 { vec![b'z'] } else { vec![0x01, 0x0F] });
+        }
+        packed.extend((length as u32 * u32::from(b'z')).to_le_bytes());
+        assert_eq!(decompress_lzss(&packed, length).unwrap(), vec![b'z'; length]);
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let result = NativePboReader::open(Path::new("nonexistent.pbo"));
+        assert!(matches!(result, Err(PboError::FileSystem(FileSystemError::ReadFile { .. }))));
+    }
+}
@@ -0,0 +1,108 @@
+use sha1::{Digest, Sha1};
+use crate::error::types::Result;
+use super::constants::{SIGNATURE_V2_UNHASHED_EXTENSIONS, SIGNATURE_V3_HASHED_EXTENSIONS};
+use super::reader::HeaderEntry;
+
+/// Version of BI's `.bisign` scheme, deciding which files the file hash covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BisignVersion {
+    /// Hashes every file except textures, models and other binary assets
+    V2,
+    /// Hashes scripts and configs only, the default since Arma 3 1.50
+    #[default]
+    V3,
+}
+
+impl BisignVersion {
+    /// Whether the file hash covers the entry stored as `path`
+    pub fn hashes_file(&self, path: &str) -> bool {
+        let extension = path.rsplit_once('.').map_or("", |(_, ext)| ext).to_lowercase();
+        match self {
+            Self::V2 => !SIGNATURE_V2_UNHASHED_EXTENSIONS.contains(&extension.as_str()),
+            Self::V3 => SIGNATURE_V3_HASHED_EXTENSIONS.contains(&extension.as_str()),
+        }
+    }
+
+    /// Bytes hashed in place of file contents when no file is covered
+    fn empty_marker(&self) -> &'static [u8] {
+        match self {
+            Self::V2 => b"nothing",
+            Self::V3 => b"gnihton",
+        }
+    }
+}
+
+/// SHA1 over the lowercased paths of all entries holding data, sorted
+pub(crate) fn name_hash(entries: &[HeaderEntry]) -> [u8; 20] {
+    let mut names: Vec<String> = entries.iter()
+        .filter(|entry| entry.data_size > 0)
+        .map(|entry| entry.path.to_lowercase())
+        .collect();
+    names.sort();
+
+    let mut hasher = Sha1::new();
+    for name in &names {
+        hasher.update(name.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// SHA1 over the `contents` of the files `version` covers
+pub(crate) fn file_hash(
+    contents: impl Iterator<Item = Result<Vec<u8>>>,
+    version: BisignVersion,
+) -> Result<[u8; 20]> {
+    let mut hasher = Sha1::new();
+    let mut hashed_any = false;
+    for data in contents {
+        hasher.update(data?);
+        hashed_any = true;
+    }
+
+    if !hashed_any {
+        hasher.update(version.empty_marker());
+    }
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::core::reader::NativePboReader;
+    use crate::test_utils::build_pbo;
+
+    #[test]
+    fn test_hashes_file() {
+        assert!(BisignVersion::V2.hashes_file("config.cpp"));
+        assert!(!BisignVersion::V2.hashes_file("data\\logo.PAA"));
+        assert!(BisignVersion::V3.hashes_file("scripts\\fn_init.sqf"));
+        assert!(!BisignVersion::V3.hashes_file("config.cpp"));
+        assert!(!BisignVersion::V3.hashes_file("readme"));
+    }
+
+    #[test]
+    fn test_name_and_file_hash() {
+        let pbo = build_pbo(&[("prefix", "tc\\mirrorform")], &[
+            ("Scripts\\fn_init.sqf", b"hint 'init';"),
+            ("config.cpp", b"class CfgPatches {};"),
+            ("empty.hpp", b""),
+            ("logo.paa", b"paa"),
+        ]);
+        let mut reader = NativePboReader::from_reader(Cursor::new(pbo)).unwrap();
+
+        let names: [u8; 20] = Sha1::digest(b"config.cpplogo.paascripts\\fn_init.sqf").into();
+        assert_eq!(reader.name_hash(), names);
+
+        let v2: [u8; 20] = Sha1::digest(b"hint 'init';class CfgPatches {};").into();
+        assert_eq!(reader.file_hash(BisignVersion::V2).unwrap(), v2);
+        let v3: [u8; 20] = Sha1::digest(b"hint 'init';").into();
+        assert_eq!(reader.file_hash(BisignVersion::V3).unwrap(), v3);
+
+        let assets = build_pbo(&[], &[("logo.paa", b"paa")]);
+        let mut reader = NativePboReader::from_reader(Cursor::new(assets)).unwrap();
+        let nothing: [u8; 20] = Sha1::digest(b"gnihton").into();
+        assert_eq!(reader.file_hash(BisignVersion::V3).unwrap(), nothing);
+    }
+}
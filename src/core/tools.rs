@@ -0,0 +1,112 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use log::debug;
+
+/// Which of the external Mikero tools can be run, as reported by
+/// [`PboApiGeneric::check_tools`](super::api::PboApiGeneric::check_tools)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ToolAvailability {
+    pub extractpbo: bool,
+    pub makepbo: bool,
+}
+
+impl ToolAvailability {
+    /// Whether every tool was found
+    pub fn all_available(&self) -> bool {
+        self.extractpbo && self.makepbo
+    }
+}
+
+/// Locate `program` the way the process launcher would: a path containing a
+/// directory is checked as is, a bare name is looked up in each PATH directory.
+/// On Windows the executable extensions are tried as well.
+pub(crate) fn find_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    let found = if path.components().count() > 1 {
+        executable_candidates(path).into_iter().find(|candidate| candidate.is_file())
+    } else {
+        env::var_os("PATH").and_then(|paths| {
+            env::split_paths(&paths)
+                .flat_map(|dir| executable_candidates(&dir.join(program)))
+                .find(|candidate| candidate.is_file())
+        })
+    };
+    debug!("Looked up {}: {:?}", program, found);
+    found
+}
+
+/// Version from the "ExtractPbo Version x.yy, ..." banner Mikero's tools print
+pub(crate) fn parse_tool_version(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let position = words.iter().position(|word| word.eq_ignore_ascii_case("version"))?;
+        if position == 0 || !words[position - 1].eq_ignore_ascii_case("extractpbo") {
+            return None;
+        }
+        let version = words.get(position + 1)?.trim_end_matches(|c: char| !c.is_ascii_digit());
+        version.starts_with(|c: char| c.is_ascii_digit()).then(|| version.to_string())
+    })
+}
+
+/// Whether `found` is `required` or newer, comparing dot separated numbers
+pub(crate) fn version_at_least(found: &str, required: &str) -> bool {
+    let parse = |version: &str| -> Vec<u32> {
+        let mut parts: Vec<u32> = version.split('.').map(|part| part.parse().unwrap_or(0)).collect();
+        while parts.last() == Some(&0) {
+            parts.pop();
+        }
+        parts
+    };
+    parse(found) >= parse(required)
+}
+
+/// `path` itself, followed on Windows by `path` with each executable extension
+fn executable_candidates(path: &Path) -> Vec<PathBuf> {
+    let extensions: &[&str] = if cfg!(windows) { &["exe", "cmd", "bat"] } else { &[] };
+    std::iter::once(path.to_path_buf())
+        .chain(extensions.iter().map(|ext| path.with_extension(ext)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_program_by_path() {
+        let temp = TempDir::new().unwrap();
+        let tool = temp.path().join("extractpbo");
+        assert_eq!(find_program(&tool.display().to_string()), None);
+
+        std::fs::write(&tool, "").unwrap();
+        assert_eq!(find_program(&tool.display().to_string()), Some(tool));
+    }
+
+    #[test]
+    fn test_parse_tool_version() {
+        let banner = "\nExtractPbo Version 3.31, Dll 8.80 \"mirrorform.pbo\"\nUsage: extractpbo ...";
+        assert_eq!(parse_tool_version(banner), Some("3.31".to_string()));
+        assert_eq!(parse_tool_version("extractpbo version 1.97"), Some("1.97".to_string()));
+        assert_eq!(parse_tool_version("MakePbo Version 2.44, Dll 8.80"), None);
+        assert_eq!(parse_tool_version("Usage: extractpbo [-options...] PboName"), None);
+    }
+
+    #[test]
+    fn test_version_at_least() {
+        assert!(version_at_least("3.31", "2.00"));
+        assert!(version_at_least("2.00", "2.00"));
+        assert!(version_at_least("2.10", "2.9"));
+        assert!(!version_at_least("1.97", "2.00"));
+        assert!(version_at_least("2", "2.00"));
+        assert!(!version_at_least("2", "2.01"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_program_on_path() {
+        assert!(find_program("sh").is_some());
+        assert_eq!(find_program("pbo_tools_missing_tool"), None);
+    }
+}
@@ -35,6 +35,16 @@ macro_rules! fs_err {
             reason: $reason.to_string(),
         }))
     };
+
+    // io::Result expression mapped to a path error: fs_err!(fs::rename(a, b) => WriteFile, b)
+    ($result:expr => $op:ident, $path:expr) => {
+        $result.map_err(|e| $crate::error::types::PboError::FileSystem(
+            $crate::error::types::FileSystemError::$op {
+                path: $path.to_path_buf(),
+                reason: e.to_string(),
+            }
+        ))
+    };
 }
 
 pub use types::*;
\ No newline at end of file
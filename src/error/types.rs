@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, PboError>;
@@ -17,15 +18,43 @@ pub enum PboError {
     #[error("Command not found: {0}")]
     CommandNotFound(String),
     
-    #[error("Operation timed out after {0} seconds")]
-    Timeout(u32),
+    #[error("Operation timed out after {0:?}")]
+    Timeout(Duration),
 
     #[error("Invalid PBO format: {0}")]
     InvalidFormat(String),
 
+    #[error("Invalid PBO: {0}")]
+    InvalidPbo(String),
+
     #[error("PBO validation failed: {0}")]
     ValidationFailed(String),
 
+    /// Wine could not be started, could not translate a path, or failed to
+    /// run extractpbo
+    #[error("Wine error: {0}")]
+    Wine(String),
+
+    /// The installed tool is too old for the switches the crate passes
+    #[error("Incompatible tool: version {required} or newer is required, found {found}")]
+    IncompatibleTool {
+        required: String,
+        found: String,
+    },
+
+    /// A configuration file could not be parsed. `line` is 1-based, and `None`
+    /// when the parser did not say where the problem is.
+    #[error(
+        "Invalid config file {}{}: {reason}",
+        .path.display(),
+        .line.map(|line| format!(", line {}", line)).unwrap_or_default()
+    )]
+    InvalidConfig {
+        path: PathBuf,
+        line: Option<usize>,
+        reason: String,
+    },
+
     #[error("Encoding error: {context} for {}", .path.display())]
     Encoding {
         context: String,
@@ -49,6 +78,12 @@ pub enum ExtractError {
 
     #[error("Invalid file filter: {0}")]
     InvalidFilter(String),
+
+    #[error("Checksum verification failed for {}", .0.display())]
+    ChecksumFailed(PathBuf),
+
+    #[error("Cannot read file from PBO: {0}")]
+    InvalidFile(String),
 }
 
 #[derive(Error, Debug)]
@@ -100,4 +135,17 @@ pub enum FileSystemError {
         path: PathBuf,
         reason: String,
     },
+
+    #[error("Files already exist in {}: {}", .path.display(), .conflicts.join(", "))]
+    AlreadyExists {
+        path: PathBuf,
+        conflicts: Vec<String>,
+    },
+
+    #[error("Not enough free space in {}: {required} bytes needed, {available} available", .path.display())]
+    InsufficientSpace {
+        path: PathBuf,
+        required: u64,
+        available: u64,
+    },
 }
@@ -1,10 +1,29 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::process::Command;
-use log::{debug, trace};
+use std::fs::File;
+use std::io;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use log::{debug, log, trace, warn, Level};
 use crate::error::types::{Result, PboError, ExtractError, FileSystemError};
-use crate::core::constants::{COMMON_PBO_EXTENSIONS, BAD_PBO_INDICATORS};
-use super::result::ExtractResult;
+use crate::core::config::PboConfig;
+use crate::core::reader::{encrypted_pbo, entry_relative_path, HeaderEntry, NativePboReader};
+use crate::core::constants::{
+    DEFAULT_MAX_NESTED_DEPTH, DEFAULT_WINE_PROGRAM, INVALID_FILTER_CHARS, LOCKED_PBO_INDICATORS, MAX_FILE_FILTER_LENGTH,
+    RETRY_BACKOFF_MS, VERSION_PROBE_TIMEOUT_SECS, WINE_FAILURE_INDICATORS,
+};
+use crate::core::extensions::{is_encrypted_pbo_path, is_pbo_path};
+use crate::core::tools::{find_program, parse_tool_version, version_at_least};
+use crate::fs::FileOperation;
+use crate::fs_err;
+use super::layout::{self, OutputLayout};
+use super::manifest::{ExtractManifest, ManifestEntry};
+use super::nested::{self, NestedPbo, NestedPbos};
+use super::process::{self, ProcessOutcome};
+use super::result::{ExtractResult, OperationMetrics};
 
 /// ExtractPBO Command Line Interface Documentation
 /// 
@@ -18,13 +37,16 @@ use super::result::ExtractResult;
 /// Arguments (in order):
 /// - Options: All options must start with - or +
 ///   - `-P`: Don't pause execution
-///   - `-W`: Treat warnings as errors
+///   - `-W`: Treat warnings as errors. Also makes a bad SHA fatal, so it is left
+///     out when `skip_checksum` is set and the other warnings are failed here.
 ///   - `-F=filelist[,...]`: Extract specific file(s). Files are extracted to their correct position 
 ///     in the output folder tree. Supports basic wildcards (*.ext for all files with extension).
 ///     Multiple files can be separated by commas.
 ///   - `-L`: List contents only (do not extract)
 ///   - `-LB`: Brief directory-style output listing
 ///   - `-N`: Noisy (verbose) output
+///   - `-D`: Decompress only, leaving binarized files such as config.bin as they are
+///   - `-H`: Dump the PBO header instead of extracting files
 /// - PBO Path: Path to the source PBO file
 /// - Destination Path: Optional output directory path. Must include drive letter.
 ///
@@ -46,38 +68,396 @@ use super::result::ExtractResult;
 /// - Error codes and output messages are used to determine operation success
 
 // Combining the traits into a single trait to avoid trait object limitations
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct ExtractOptions {
     /// Don't pause execution (-P)
     pub no_pause: bool,
-    /// Treat warnings as errors (-W)
+    /// Treat warnings as errors (-W). Still applies with `skip_checksum`, where
+    /// every warning except the checksum one fails the operation.
     pub warnings_as_errors: bool,
     /// Extract specific file(s) (-F=filelist[,...])
     pub file_filter: Option<String>,
+    /// Skip files matching this filter, e.g. `*.paa,*.p3d` for everything except
+    /// textures and models. Uses `filter_syntax` and applies after `file_filter`;
+    /// the remaining entries are passed to extractpbo as an explicit file list.
+    pub exclude_filter: Option<String>,
+    /// Syntax of `file_filter` and `exclude_filter`
+    pub filter_syntax: FilterSyntax,
     /// Noisy (verbose) output (-N)
     pub verbose: bool,
     /// Brief directory-style output listing (-LB)
     pub brief_listing: bool,
+    /// Decompress without de-binarizing, e.g. keep config.bin as is (-D)
+    pub decompress_only: bool,
+    /// Dump the raw PBO header (-H)
+    pub dump_headers: bool,
+    /// Where extracted files are placed inside the output directory
+    pub layout: OutputLayout,
+    /// What to do with files that already exist in the output directory
+    pub overwrite: OverwritePolicy,
+    /// Move extracted files out of their prefix folders into the output directory.
+    /// Only valid with the `AsIs` and `PrefixFolders` layouts.
+    pub strip_prefix: bool,
+    /// Move extracted files from their prefix folders into folders built from
+    /// this prefix instead. Only valid with the `AsIs` and `PrefixFolders` layouts.
+    pub rewrite_prefix: Option<String>,
+    /// Extract into a temporary directory and move the files into the output
+    /// directory only once extraction succeeded, so a failed extraction leaves
+    /// nothing behind. Applied by `PboApi`; requires `OverwritePolicy::Overwrite`.
+    pub atomic: bool,
+    /// Refuse to extract when the selected files total more than this many bytes
+    /// once unpacked, judged from the PBO header before anything is written
+    pub max_total_size: Option<u64>,
+    /// Refuse to extract when more than this many files are selected
+    pub max_file_count: Option<usize>,
+    /// Set each extracted file's modification time to the timestamp stored in
+    /// the PBO header. Entries with a zero timestamp are left alone.
+    pub preserve_timestamps: bool,
+    /// Write extractpbo's raw stdout and stderr to this file once it exits,
+    /// replacing any previous log
+    pub capture_log: Option<PathBuf>,
+    /// Create the output directory when it does not exist. When false a missing
+    /// output directory fails with `PboError::InvalidPath`, catching mistyped paths.
+    pub create_output_dir: bool,
+    /// Extract PBOs found among the extracted files into folders named after
+    /// them next to each one, following nested PBOs down to `max_depth` levels
+    pub recurse_nested: bool,
+    /// Levels of nested PBOs followed by `recurse_nested`
+    pub max_depth: usize,
+    /// Extract PBOs whose stored SHA no longer matches, e.g. ones modified after
+    /// packing. Runs extractpbo without -W so the bad checksum stays a warning,
+    /// and stops "Bad Sha" output from failing the operation. Other warnings
+    /// still fail it when `warnings_as_errors` is set.
+    pub skip_checksum: bool,
+    /// After a successful extraction, write a JSON manifest to this path listing
+    /// the source PBO, its prefix and each file written, relative to the output
+    /// folder, with its size on disk.
+    /// Requires the `serde` feature.
+    pub write_manifest: Option<PathBuf>,
+    /// Only extract entries whose header timestamp is later than this time, e.g.
+    /// the last sync. Entries with a zero timestamp are always extracted, since
+    /// their age is unknown. The entries are passed to extractpbo as an explicit
+    /// file list, split over several runs when it gets long.
+    pub only_newer_than: Option<SystemTime>,
+    /// Kill extractpbo after this long instead of the extractor's configured
+    /// timeout, for this call only. Not scaled with the PBO size.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            no_pause: false,
+            warnings_as_errors: false,
+            file_filter: None,
+            exclude_filter: None,
+            filter_syntax: FilterSyntax::default(),
+            verbose: false,
+            brief_listing: false,
+            decompress_only: false,
+            dump_headers: false,
+            layout: OutputLayout::default(),
+            overwrite: OverwritePolicy::default(),
+            strip_prefix: false,
+            rewrite_prefix: None,
+            atomic: false,
+            max_total_size: None,
+            max_file_count: None,
+            preserve_timestamps: false,
+            capture_log: None,
+            create_output_dir: true,
+            recurse_nested: false,
+            max_depth: DEFAULT_MAX_NESTED_DEPTH,
+            skip_checksum: false,
+            write_manifest: None,
+            only_newer_than: None,
+            timeout: None,
+        }
+    }
+}
+
+/// How `ExtractOptions::file_filter` is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterSyntax {
+    /// Passed straight to extractpbo as `-F=filter`, using its wildcard semantics
+    #[default]
+    Glob,
+    /// Rust regex matched against each entry path (with `/` separators); the
+    /// matching entries are passed to extractpbo as an explicit file list. A
+    /// match whose name that list cannot tell apart from an unmatched entry,
+    /// such as `a,b.sqf` next to `a_b.sqf`, fails with `ExtractError::InvalidFilter`.
+    Regex,
+}
+
+/// How an extraction treats files already present in the output directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// Replace existing files, as extractpbo does on its own
+    #[default]
+    Overwrite,
+    /// Leave existing files alone and extract only the missing ones
+    Skip,
+    /// Fail with `FileSystemError::AlreadyExists` before extracting anything
+    FailIfExists,
+}
+
+/// Header of the PBO an extraction runs on, shared by every stage of the
+/// extraction. The native reader opens it when a stage first needs it, so a
+/// header only extractpbo can parse fails only the options that read it.
+#[derive(Debug)]
+pub(crate) struct PboHeader<'a> {
+    pbo_path: &'a Path,
+    reader: OnceLock<NativePboReader>,
+}
+
+impl<'a> PboHeader<'a> {
+    pub(crate) fn new(pbo_path: &'a Path) -> Self {
+        Self { pbo_path, reader: OnceLock::new() }
+    }
+
+    pub(crate) fn path(&self) -> &'a Path {
+        self.pbo_path
+    }
+
+    /// The parsed header, read on first use
+    pub(crate) fn reader(&self) -> Result<&NativePboReader> {
+        if let Some(reader) = self.reader.get() {
+            return Ok(reader);
+        }
+        let reader = NativePboReader::open(self.pbo_path)?;
+        Ok(self.reader.get_or_init(|| reader))
+    }
+
+    /// The PBO's prefix, or `None` when it has none or the header cannot be read
+    pub(crate) fn prefix(&self) -> Option<String> {
+        self.reader().ok().and_then(NativePboReader::prefix)
+    }
+}
+
+/// Timeout for processing `pbo_path`: `base` plus `per_gb` for every whole
+/// gigabyte of the file. Falls back to `base` when the size cannot be read.
+pub(crate) fn scaled_timeout(base: Duration, per_gb: Option<Duration>, pbo_path: &Path) -> Duration {
+    const BYTES_PER_GB: u64 = 1024 * 1024 * 1024;
+
+    let Some(per_gb) = per_gb else {
+        return base;
+    };
+    let gigabytes = std::fs::metadata(pbo_path).map_or(0, |m| m.len() / BYTES_PER_GB);
+    let timeout = base + per_gb * u32::try_from(gigabytes).unwrap_or(u32::MAX);
+    debug!("Timeout for {:?} ({} GB): {:?}", pbo_path, gigabytes, timeout);
+    timeout
+}
+
+/// Regex equivalent of an extractpbo `-F=` filter: comma separated patterns with
+/// `*` and `?` wildcards, matched case-insensitively against the full entry path
+pub(crate) fn glob_filter_regex(filter: &str) -> Result<regex::Regex> {
+    let alternatives: Vec<String> = filter
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| {
+            pattern.chars().map(|c| match c {
+                '*' => ".*".to_string(),
+                '?' => ".".to_string(),
+                '\\' | '/' => r"[\\/]".to_string(),
+                c => regex::escape(&c.to_string()),
+            }).collect()
+        })
+        .collect();
+
+    regex::Regex::new(&format!("(?i)^(?:{})$", alternatives.join("|")))
+        .map_err(|e| PboError::Extraction(ExtractError::InvalidFilter(e.to_string())))
+}
+
+/// Explicit `-F=` list selecting exactly the `selected` paths among `entries`.
+///
+/// extractpbo splits the list on `,` and has no escape for it, so a `,` in a
+/// name is written as the `?` wildcard. Fails when that, or a `*` or `?` in a
+/// name, would also match an entry that was not selected; such entries can
+/// still be written with `PboApi::extract_entry_by_index`.
+pub(crate) fn file_list_filter(selected: &[&str], entries: &[HeaderEntry]) -> Result<String> {
+    let mut patterns = Vec::with_capacity(selected.len());
+    for path in selected {
+        let pattern = path.replace(',', "?");
+        if pattern.contains(['*', '?']) {
+            let regex = glob_filter_regex(&pattern)?;
+            if let Some(other) = entries.iter().find(|entry| regex.is_match(&entry.path) && !selected.contains(&entry.path.as_str())) {
+                return Err(PboError::Extraction(ExtractError::InvalidFilter(format!(
+                    "{} cannot be selected without also selecting {}", path, other.path
+                ))));
+            }
+        }
+        patterns.push(pattern);
+    }
+    Ok(patterns.join(","))
+}
+
+/// Split a `-F=` filter into filters of at most `MAX_FILE_FILTER_LENGTH`
+/// characters, one per extractpbo run. `None` stays a single unfiltered run.
+fn split_file_filter(filter: Option<&str>) -> Vec<Option<String>> {
+    let Some(filter) = filter.filter(|filter| filter.len() > MAX_FILE_FILTER_LENGTH) else {
+        return vec![filter.map(String::from)];
+    };
+
+    let mut parts: Vec<String> = Vec::new();
+    for pattern in filter.split(',') {
+        match parts.last_mut() {
+            Some(part) if part.len() + 1 + pattern.len() <= MAX_FILE_FILTER_LENGTH => {
+                part.push(',');
+                part.push_str(pattern);
+            }
+            _ => parts.push(pattern.to_string()),
+        }
+    }
+    debug!("Split a {} character file filter over {} runs", filter.len(), parts.len());
+    parts.into_iter().map(Some).collect()
+}
+
+/// Add the result of one run of a split extraction to the results so far.
+/// Returns whether to go on with the next run.
+fn merge_part(merged: &mut Option<Result<ExtractResult>>, part: Result<ExtractResult>) -> bool {
+    let succeeded = part.as_ref().is_ok_and(ExtractResult::is_success);
+    *merged = Some(match merged.take() {
+        Some(previous) => previous.and_then(|previous| part.map(|part| previous.merge(part))),
+        None => part,
+    });
+    succeeded
+}
+
+/// A problem found by [`ExtractOptions::validate_detailed`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// Name of the offending `ExtractOptions` field
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self { field, message: message.into() }
+    }
 }
 
 impl ExtractOptions {
+    /// Check the options for conflicts, failing with all problems joined into
+    /// one `ValidationFailed` message
     pub fn validate(&self) -> Result<()> {
+        self.validate_with(&PboConfig::default())
+    }
+
+    /// Like `validate`, accepting the special characters `config` allows in filters
+    pub fn validate_with(&self, config: &PboConfig) -> Result<()> {
+        self.validate_detailed_with(config).map_err(|issues| {
+            let messages: Vec<String> = issues.into_iter().map(|issue| issue.message).collect();
+            PboError::ValidationFailed(messages.join("; "))
+        })
+    }
+
+    /// Check the options for conflicts, reporting every problem along with the
+    /// field it concerns
+    pub fn validate_detailed(&self) -> std::result::Result<(), Vec<ValidationIssue>> {
+        self.validate_detailed_with(&PboConfig::default())
+    }
+
+    /// Like `validate_detailed`, accepting the special characters `config`
+    /// allows in filters
+    pub fn validate_detailed_with(&self, config: &PboConfig) -> std::result::Result<(), Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+
         // Can't use brief_listing with extraction operations
-        if self.brief_listing && (self.file_filter.is_some()) {
-            return Err(PboError::ValidationFailed(
-                "Brief listing option cannot be used with extraction options".to_string()
+        if self.brief_listing && (self.file_filter.is_some() || self.exclude_filter.is_some()) {
+            issues.push(ValidationIssue::new(
+                "brief_listing", "Brief listing option cannot be used with extraction options"
+            ));
+        }
+
+        // A header dump covers the whole PBO and extracts nothing
+        if self.dump_headers && (self.file_filter.is_some() || self.exclude_filter.is_some()) {
+            issues.push(ValidationIssue::new(
+                "dump_headers", "Header dump cannot be combined with a file filter"
+            ));
+        }
+        if self.dump_headers && self.decompress_only {
+            issues.push(ValidationIssue::new(
+                "dump_headers", "Header dump cannot be combined with decompress-only extraction"
+            ));
+        }
+
+        if self.strip_prefix && self.rewrite_prefix.is_some() {
+            issues.push(ValidationIssue::new(
+                "rewrite_prefix", "Prefix cannot be both stripped and rewritten"
+            ));
+        }
+        if (self.strip_prefix || self.rewrite_prefix.is_some())
+            && !matches!(self.layout, OutputLayout::AsIs | OutputLayout::PrefixFolders)
+        {
+            issues.push(ValidationIssue::new(
+                "layout", format!("Prefix cannot be stripped or rewritten with the {:?} layout", self.layout)
+            ));
+        }
+
+        // The overwrite policy would be checked against the empty temp directory
+        if self.atomic && self.overwrite != OverwritePolicy::Overwrite {
+            issues.push(ValidationIssue::new(
+                "overwrite", format!("Atomic extraction cannot be combined with the {:?} overwrite policy", self.overwrite)
+            ));
+        }
+
+        if self.only_newer_than.is_some() && (self.brief_listing || self.dump_headers) {
+            issues.push(ValidationIssue::new(
+                "only_newer_than", "Timestamp filter cannot be used with a listing"
+            ));
+        }
+
+        if self.timeout == Some(Duration::ZERO) {
+            issues.push(ValidationIssue::new("timeout", "Timeout must be greater than zero"));
+        }
+
+        if self.recurse_nested && self.max_depth == 0 {
+            issues.push(ValidationIssue::new(
+                "max_depth", "Nested extraction needs a max_depth of at least 1"
+            ));
+        }
+
+        #[cfg(not(feature = "serde"))]
+        if self.write_manifest.is_some() {
+            issues.push(ValidationIssue::new(
+                "write_manifest", "Writing a manifest requires the serde feature"
             ));
         }
 
         // Validate file filter format if present
-        if let Some(filter) = &self.file_filter {
-            if filter.contains(['<', '>', '|', '"', '\'']) {
-                return Err(PboError::ValidationFailed(
-                    "File filter contains invalid characters".to_string()
+        let filters = [("file_filter", &self.file_filter), ("exclude_filter", &self.exclude_filter)];
+        for (field, filter) in filters {
+            let Some(filter) = filter else {
+                continue;
+            };
+            if self.filter_syntax == FilterSyntax::Regex {
+                if regex::Regex::new(filter).is_err() {
+                    issues.push(ValidationIssue::new(
+                        field, format!("Invalid file filter pattern: {}", filter)
+                    ));
+                }
+            } else if filter.chars().any(|c| config.rejects_char(c, INVALID_FILTER_CHARS)) {
+                issues.push(ValidationIssue::new(
+                    field, "File filter contains invalid characters"
                 ));
             }
         }
 
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// Fail with `PboError::InvalidPath` if `output_dir` is missing and may not
+    /// be created
+    pub(crate) fn check_output_dir(&self, output_dir: &Path) -> Result<()> {
+        if !self.create_output_dir && !output_dir.is_dir() {
+            return Err(PboError::InvalidPath(output_dir.to_path_buf()));
+        }
         Ok(())
     }
 
@@ -144,33 +524,328 @@ impl Clone for Box<dyn ExtractorClone> {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct DefaultExtractor;
+impl ExtractorClone for Box<dyn ExtractorClone> {
+    fn extract_with_options(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        (**self).extract_with_options(pbo_path, output_dir, options)
+    }
+
+    fn list_with_options(&self, pbo_path: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        (**self).list_with_options(pbo_path, options)
+    }
+
+    fn clone_box(&self) -> Box<dyn ExtractorClone> {
+        (**self).clone_box()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DefaultExtractor {
+    config: Arc<PboConfig>,
+    extractpbo_path: Option<PathBuf>,
+    timeout: Option<Duration>,
+    /// Extra time allowed per gigabyte of PBO on top of `timeout`
+    timeout_per_gb: Option<Duration>,
+    /// Environment variables set for the extractpbo process only
+    env: HashMap<String, String>,
+    /// Wine binary extractpbo runs under, `None` to run it directly
+    wine: Option<PathBuf>,
+    /// Never pass -W, so extractpbo warnings cannot fail an operation
+    lenient: bool,
+    /// Level extractpbo's stdout and stderr are logged at, `None` for trace
+    output_log_level: Option<Level>,
+    /// Oldest extractpbo version a failed run is checked against, `None` to never probe
+    min_tool_version: Option<String>,
+    /// Version probed by `tool_version`, shared by clones until the binary changes
+    probed_version: Arc<OnceLock<String>>,
+}
 
 impl DefaultExtractor {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_config(config: Arc<PboConfig>) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Run the extractpbo binary at `path` instead of looking it up on PATH
+    pub fn with_extractpbo_path(mut self, path: PathBuf) -> Self {
+        self.extractpbo_path = Some(path);
+        self.probed_version = Arc::default();
+        self
+    }
+
+    /// Kill extractpbo if it runs longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Extend the timeout by `per_gb` for every gigabyte of the PBO being processed
+    pub fn with_timeout_per_gb(mut self, per_gb: Duration) -> Self {
+        self.timeout_per_gb = Some(per_gb);
+        self
+    }
+
+    /// Set an environment variable for the extractpbo process, e.g. `WINEPREFIX`
+    /// when running it under Wine. The current process environment is not changed.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Run extractpbo under Wine, using the binary at `wine_path` or `wine` on PATH.
+    /// The PBO and output paths are translated to Windows paths with `winepath`
+    /// from the same folder. Has no effect on Windows.
+    pub fn with_wine(mut self, wine_path: Option<PathBuf>) -> Self {
+        if cfg!(windows) {
+            debug!("Ignoring Wine on Windows");
+            return self;
+        }
+        self.wine = Some(wine_path.unwrap_or_else(|| PathBuf::from(DEFAULT_WINE_PROGRAM)));
+        self.probed_version = Arc::default();
+        self
+    }
+
+    /// Never run extractpbo with -W, whatever `ExtractOptions::warnings_as_errors`
+    /// says, so warnings cannot fail an operation
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Log extractpbo's stdout and stderr at `level` instead of trace. Output of
+    /// a failed run is logged at warn or above regardless.
+    pub fn with_output_log_level(mut self, level: Level) -> Self {
+        self.output_log_level = Some(level);
+        self
+    }
+
+    /// When a run fails, probe the extractpbo version once and report
+    /// `PboError::IncompatibleTool` if it is older than `version`, as the old
+    /// binary most likely rejected a switch it does not know. Off by default.
+    pub fn with_min_tool_version(mut self, version: impl Into<String>) -> Self {
+        self.min_tool_version = Some(version.into());
+        self
+    }
+
+    /// Level the output of a run that exited with `return_code` is logged at
+    fn output_log_level(&self, return_code: i32) -> Level {
+        let level = self.output_log_level.unwrap_or(Level::Trace);
+        if return_code != 0 {
+            return level.min(Level::Warn);
+        }
+        level
+    }
+
+    /// Configuration used to classify extractpbo output
+    pub(crate) fn config(&self) -> &Arc<PboConfig> {
+        &self.config
+    }
+
+    /// Whether the extractpbo binary exists, at the overridden path or on PATH.
+    /// Under Wine the Wine binary must exist, and extractpbo is only checked
+    /// when its path was overridden.
+    pub fn is_available(&self) -> bool {
+        match &self.wine {
+            Some(wine) => {
+                find_program(&wine.display().to_string()).is_some()
+                    && self.extractpbo_path.as_ref().is_none_or(|path| path.is_file())
+            }
+            None => find_program(&self.program_name()).is_some(),
+        }
+    }
+
+    /// Version of the extractpbo binary, read from the "ExtractPbo Version" line
+    /// it prints when run without a PBO. The binary is only run the first time,
+    /// later calls return the cached version.
+    pub fn tool_version(&self) -> Result<String> {
+        if let Some(version) = self.probed_version.get() {
+            return Ok(version.clone());
+        }
+        let version = self.probe_tool_version()?;
+        Ok(self.probed_version.get_or_init(|| version).clone())
+    }
+
+    fn probe_tool_version(&self) -> Result<String> {
+        self.check_executable()?;
+        let launcher = self.launcher();
+        let child = Command::new(&launcher[0])
+            .args(&launcher[1..])
+            .arg("-P")
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| self.spawn_error(e))?;
+
+        let timeout = Duration::from_secs(VERSION_PROBE_TIMEOUT_SECS);
+        let output = match process::wait_with_timeout(child, Some(timeout), &mut |_| {}) {
+            Ok(ProcessOutcome::Exited(output)) => output,
+            Ok(ProcessOutcome::TimedOut) => return Err(PboError::Timeout(timeout)),
+            Err(e) => return Err(self.spawn_error(e)),
+        };
+        let version = parse_tool_version(&output.stdout).or_else(|| parse_tool_version(&output.stderr));
+        debug!("Detected extractpbo version {:?}", version);
+        version.ok_or_else(|| PboError::Extraction(ExtractError::CommandFailed {
+            cmd: self.program_name(),
+            reason: "no version in its output".to_string(),
+        }))
+    }
+
+    /// Check that extractpbo is at least the version set with
+    /// `with_min_tool_version`, returning its version, or
+    /// `PboError::IncompatibleTool` when it is older. Any version passes when
+    /// no minimum is set.
+    pub fn check_tool_version(&self) -> Result<String> {
+        let found = self.tool_version()?;
+        if let Some(required) = &self.min_tool_version {
+            if !version_at_least(&found, required) {
+                return Err(PboError::IncompatibleTool {
+                    required: required.clone(),
+                    found,
+                });
+            }
+        }
+        Ok(found)
+    }
+
+    /// Replace the outcome of a failed run with `PboError::IncompatibleTool`
+    /// when a minimum version is set and extractpbo is older. Successful runs,
+    /// and every run without a minimum, are returned without probing.
+    fn explain_failure(&self, result: Result<ExtractResult>) -> Result<ExtractResult> {
+        if self.min_tool_version.is_none() {
+            return result;
+        }
+        let failed = match &result {
+            Ok(result) => result.return_code != 0,
+            Err(PboError::ValidationFailed(_)) => true,
+            Err(_) => false,
+        };
+        if !failed {
+            return result;
+        }
+
+        match self.check_tool_version() {
+            Err(error @ PboError::IncompatibleTool { .. }) => {
+                warn!("extractpbo failed and is too old: {}", error);
+                Err(error)
+            }
+            _ => result,
+        }
+    }
+
+    /// Name used for the extractpbo command in commands and error messages
+    fn program_name(&self) -> String {
+        self.extractpbo_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "extractpbo".to_string())
+    }
+
+    /// Program and leading arguments that start extractpbo: the Wine binary
+    /// followed by extractpbo when running under Wine, otherwise extractpbo alone
+    fn launcher(&self) -> Vec<String> {
+        match &self.wine {
+            Some(wine) => vec![wine.display().to_string(), self.program_name()],
+            None => vec![self.program_name()],
+        }
+    }
+
+    /// `path` as extractpbo should receive it: unchanged when it runs natively,
+    /// translated to a Windows path by `winepath` under Wine. `winepath` is
+    /// killed after `timeout`.
+    fn tool_path(&self, path: &str, timeout: Option<Duration>) -> Result<String> {
+        let Some(wine) = &self.wine else {
+            return Ok(path.to_string());
+        };
+
+        // winepath ships next to wine, fall back to PATH for a bare `wine`
+        let winepath = wine.parent()
+            .map(|dir| dir.join("winepath"))
+            .filter(|winepath| winepath.is_file())
+            .unwrap_or_else(|| PathBuf::from("winepath"));
+        let absolute = std::path::absolute(path).map_err(|_| PboError::InvalidPath(PathBuf::from(path)))?;
+
+        let run_error = |e: io::Error| PboError::Wine(format!("Failed to run {}: {}", winepath.display(), e));
+        let child = Command::new(&winepath)
+            .arg("-w")
+            .arg(&absolute)
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(run_error)?;
+        let output = match process::wait_with_timeout(child, timeout, &mut |_| {}) {
+            Ok(ProcessOutcome::Exited(output)) => output,
+            Ok(ProcessOutcome::TimedOut) => return Err(PboError::Timeout(timeout.unwrap_or_default())),
+            Err(e) => return Err(run_error(e)),
+        };
+        let translated = output.stdout.trim().to_string();
+        if !output.status.success() || translated.is_empty() {
+            return Err(PboError::Wine(format!(
+                "winepath could not translate {}: {}",
+                absolute.display(),
+                output.stderr.trim()
+            )));
+        }
+        trace!("Translated {:?} to {}", absolute, translated);
+        Ok(translated)
+    }
+
+    /// Spawn errors worth retrying: the tool is briefly busy or unavailable.
+    /// Permission errors are retried too, since a tool on a network share can
+    /// be denied while the share is still reconnecting.
+    fn is_transient_error(error: &io::Error) -> bool {
+        matches!(
+            error.kind(),
+            io::ErrorKind::ResourceBusy
+                | io::ErrorKind::PermissionDenied
+                | io::ErrorKind::WouldBlock
+                | io::ErrorKind::Interrupted
+                | io::ErrorKind::TimedOut
+        )
+    }
+
+    /// Whether warnings fail the operation, through -W or, when the checksum is
+    /// skipped, through the result's own error check
+    fn warnings_as_errors(&self, options: &ExtractOptions) -> bool {
+        options.warnings_as_errors && !self.lenient
     }
 
-    /// Execute the extractpbo command following the strict argument order:
-    /// 1. Core options (-PW)
+    /// Build the extractpbo argument list following the strict argument order:
+    /// 1. Core options (-P, plus -W when warnings are errors and the checksum
+    ///    is not skipped)
     /// 2. Operation-specific options (-F=pattern, -L, etc)
     /// 3. PBO path
     /// 4. Destination path (if any)
-    fn run_extractpbo_command(&self, args: Vec<&str>, pbo_path: &Path) -> Result<ExtractResult> {
-        debug!("Running extractpbo command with args: {:?}", args);
-        debug!("PBO path: {:?}", pbo_path);
-        
-        let mut command = Command::new("extractpbo");
-        
+    ///
+    /// Under Wine the paths are translated with `winepath`, which gets the
+    /// same timeout as extractpbo, unscaled.
+    pub(crate) fn build_args(&self, args: &[&str], pbo_path: &Path, options: &ExtractOptions) -> Result<Vec<String>> {
+        let timeout = options.timeout.or(self.timeout);
+        self.assemble_args(args, pbo_path, options, &|path| self.tool_path(path, timeout))
+    }
+
+    /// `build_args` with the paths passed through `tool_path`
+    fn assemble_args(
+        &self,
+        args: &[&str],
+        pbo_path: &Path,
+        options: &ExtractOptions,
+        tool_path: &dyn Fn(&str) -> Result<String>,
+    ) -> Result<Vec<String>> {
         // Validate PBO path exists and is accessible
         if !pbo_path.exists() {
             return Err(PboError::InvalidPath(pbo_path.to_path_buf()));
         }
 
-        if !pbo_path.extension().map_or(false, |ext| {
-            COMMON_PBO_EXTENSIONS.contains(&ext.to_str().unwrap_or(""))
-        }) {
+        if !is_pbo_path(pbo_path) {
             return Err(PboError::InvalidFormat(format!(
                 "File {} does not have a valid PBO extension", 
                 pbo_path.display()
@@ -178,15 +853,21 @@ impl DefaultExtractor {
         }
         
         // 1. Core options first (always used)
-        command.arg("-PW");  // Combined: Don't pause (-P) and treat warnings as errors (-W)
+        // Don't pause (-P), and treat warnings as errors (-W) unless that would
+        // make the skipped checksum fatal
+        let mut core = String::from("-P");
+        if self.warnings_as_errors(options) && !options.skip_checksum {
+            core.push('W');
+        }
+        let mut argv = vec![core];
         
         // 2. Operation-specific options (like -F=pattern or -L)
         let mut has_options = false;
-        for arg in &args {
+        for arg in args {
             if arg.starts_with('-') {
                 // Special validation for -F option which can contain wildcards
                 if arg.starts_with("-F=") {
-                    command.arg(arg);
+                    argv.push(arg.to_string());
                     has_options = true;
                     continue;
                 }
@@ -197,7 +878,7 @@ impl DefaultExtractor {
                         format!("Invalid option format: {}", arg)
                     ));
                 }
-                command.arg(arg);
+                argv.push(arg.to_string());
                 has_options = true;
             }
         }
@@ -207,135 +888,917 @@ impl DefaultExtractor {
 
         // 3. PBO path (required)
         if let Some(pbo_str) = pbo_path.to_str() {
-            command.arg(pbo_str.replace("\\\\?\\", ""));
+            argv.push(tool_path(&pbo_str.replace("\\\\?\\", ""))?);
         } else {
             return Err(PboError::InvalidPath(pbo_path.to_path_buf()));
         }
 
         // 4. Destination path (if any non-flag args remain)
-        let mut _added_dest = false;
-        for arg in &args {
+        for arg in args {
             if !arg.starts_with('-') {
                 // Validate destination path
                 let dest_path = Path::new(arg);
-                if dest_path.to_str().map_or(true, |s| s.contains(['<', '>', '|', '"', '\''])) {
+                if dest_path.to_str().map_or(true, |s| s.chars().any(|c| self.config.rejects_char(c, INVALID_FILTER_CHARS))) {
                     return Err(PboError::ValidationFailed(
                         format!("Invalid destination path: {}", arg)
                     ));
                 }
-                command.arg(arg);
-                _added_dest = true;
+                argv.push(tool_path(arg)?);
                 debug!("Added destination path");
                 break; // Only add the first non-flag argument as destination
             }
         }
 
-        trace!("Full command: {:?}", command);
-        
-        // Execute command with proper error handling
-        match command.output() {
-            Ok(output) => {
-                trace!("Command completed with status: {:?}", output.status);
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                
-                trace!("Stdout: {}", stdout);
-                trace!("Stderr: {}", stderr);
+        Ok(argv)
+    }
 
-                // Check for specific error patterns in the output
-                if BAD_PBO_INDICATORS.iter().any(|&indicator| {
-                    stderr.contains(indicator) || stdout.contains(indicator)
-                }) {
-                    return Err(PboError::ValidationFailed(
-                        format!("PBO validation failed:\n{}", stderr)
-                    ));
-                }
+    /// Translate a regex file filter or an exclude filter into the explicit file
+    /// list extractpbo understands. Other glob filters are returned unchanged.
+    ///
+    /// Regex filters ignore case unless the configuration is case sensitive;
+    /// globs always do, as extractpbo's own `-F=` matching does.
+    fn resolve_file_filter(&self, header: &PboHeader<'_>, options: ExtractOptions) -> Result<ExtractOptions> {
+        let is_regex = options.filter_syntax == FilterSyntax::Regex;
+        if options.exclude_filter.is_none() && !(is_regex && options.file_filter.is_some()) {
+            return Ok(options);
+        }
+
+        let to_regex = |pattern: &str| if is_regex {
+            regex::RegexBuilder::new(pattern)
+                .case_insensitive(!self.config.is_case_sensitive())
+                .build()
+                .map_err(|e| PboError::Extraction(ExtractError::InvalidFilter(e.to_string())))
+        } else {
+            glob_filter_regex(pattern)
+        };
+        let include = options.file_filter.as_deref().map(to_regex).transpose()?;
+        let exclude = options.exclude_filter.as_deref().map(to_regex).transpose()?;
+
+        let reader = header.reader()?;
+        let matched: Vec<&str> = reader.header_entries()
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .filter(|path| {
+                let path = path.replace('\\', "/");
+                include.as_ref().is_none_or(|include| include.is_match(&path))
+                    && !exclude.as_ref().is_some_and(|exclude| exclude.is_match(&path))
+            })
+            .collect();
+        debug!(
+            "Filter {:?} excluding {:?} matched {} entries",
+            options.file_filter, options.exclude_filter, matched.len()
+        );
+
+        // An empty -F= would extract everything, so treat no matches as an error
+        if matched.is_empty() {
+            return Err(PboError::Extraction(ExtractError::NoFiles));
+        }
+
+        Ok(ExtractOptions {
+            file_filter: Some(file_list_filter(&matched, reader.header_entries())?),
+            exclude_filter: None,
+            filter_syntax: FilterSyntax::Glob,
+            ..options
+        })
+    }
 
-                Ok(ExtractResult {
-                    return_code: output.status.code().unwrap_or(-1),
-                    stdout: stdout.to_string(),
-                    stderr: stderr.to_string(),
-                })
+    /// Whether extracting `entry_path` would replace a file in `content_dir`,
+    /// either under its own name or the name it is debinarized to
+    fn destination_exists(&self, content_dir: &Path, entry_path: &str) -> bool {
+        !self.existing_destinations(content_dir, entry_path).is_empty()
+    }
+
+    /// Files in `content_dir` that `entry_path` is extracted to, under its own
+    /// name or the name it is debinarized to
+    fn existing_destinations(&self, content_dir: &Path, entry_path: &str) -> Vec<PathBuf> {
+        let Some(relative) = entry_relative_path(entry_path) else {
+            return Vec::new();
+        };
+        let debinarized = relative.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| self.config.bin_target_name(name))
+            .map(|target| relative.with_file_name(target));
+
+        std::iter::once(relative.clone())
+            .chain(debinarized)
+            .map(|path| content_dir.join(path))
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    /// Fail with `ValidationFailed` when the entries selected by the file filter
+    /// exceed `max_total_size` or `max_file_count`
+    fn check_size_limits(header: &PboHeader<'_>, options: &ExtractOptions) -> Result<()> {
+        if options.max_total_size.is_none() && options.max_file_count.is_none() {
+            return Ok(());
+        }
+
+        let reader = header.reader()?;
+        let selected = Self::selected_entries(reader, options)?;
+        let file_count = selected.len();
+        let total_size: u64 = selected.iter().map(|entry| entry.uncompressed_size()).sum();
+        debug!("Extraction would write {} files totalling {} bytes", file_count, total_size);
+
+        if let Some(max_file_count) = options.max_file_count.filter(|&max| file_count > max) {
+            return Err(PboError::ValidationFailed(format!(
+                "Extraction would write {} files, exceeding the limit of {}", file_count, max_file_count
+            )));
+        }
+        if let Some(max_total_size) = options.max_total_size.filter(|&max| total_size > max) {
+            return Err(PboError::ValidationFailed(format!(
+                "Extraction would write {} bytes, exceeding the limit of {}", total_size, max_total_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// Header entries of `reader` selected by the resolved file filter of `options`
+    fn selected_entries<'a>(reader: &'a NativePboReader, options: &ExtractOptions) -> Result<Vec<&'a HeaderEntry>> {
+        let filter = options.file_filter.as_deref().map(glob_filter_regex).transpose()?;
+        Ok(reader.header_entries()
+            .iter()
+            .filter(|entry| filter.as_ref().is_none_or(|filter| filter.is_match(&entry.path)))
+            .collect())
+    }
+
+    /// Check the files an extraction would write against `options.overwrite`.
+    ///
+    /// `Skip` narrows the file filter to entries not yet on disk and returns `None`
+    /// when nothing is left to extract; `FailIfExists` reports every conflict.
+    fn apply_overwrite_policy(&self, header: &PboHeader<'_>, output_dir: &Path, options: ExtractOptions) -> Result<Option<ExtractOptions>> {
+        if options.overwrite == OverwritePolicy::Overwrite {
+            return Ok(Some(options));
+        }
+
+        let reader = header.reader()?;
+        let content_dir = options.layout.content_dir(output_dir, header.path(), reader.prefix().as_deref())?;
+        let filter = options.file_filter.as_deref().map(glob_filter_regex).transpose()?;
+
+        let mut remaining = Vec::new();
+        let mut conflicts = Vec::new();
+        for entry in reader.header_entries() {
+            if filter.as_ref().is_some_and(|filter| !filter.is_match(&entry.path)) {
+                continue;
             }
-            Err(e) => match e.kind() {
-                std::io::ErrorKind::NotFound => 
-                    Err(PboError::CommandNotFound("extractpbo".to_string())), 
-                std::io::ErrorKind::PermissionDenied =>
-                    Err(PboError::FileSystem(FileSystemError::PathValidation(
-                        "Permission denied".to_string()
-                    ))),
-                _ => Err(PboError::Extraction(ExtractError::CommandFailed {
-                    cmd: "extractpbo".to_string(),
-                    reason: e.to_string(),
-                }))
+            if self.destination_exists(&content_dir, &entry.path) {
+                conflicts.push(entry.path.clone());
+            } else {
+                remaining.push(entry.path.as_str());
             }
         }
+
+        if conflicts.is_empty() {
+            return Ok(Some(options));
+        }
+
+        if options.overwrite == OverwritePolicy::FailIfExists {
+            return Err(PboError::FileSystem(FileSystemError::AlreadyExists {
+                path: output_dir.to_path_buf(),
+                conflicts,
+            }));
+        }
+
+        debug!("Skipping {} existing files: {:?}", conflicts.len(), conflicts);
+        if remaining.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(ExtractOptions {
+            file_filter: Some(file_list_filter(&remaining, reader.header_entries())?),
+            filter_syntax: FilterSyntax::Glob,
+            ..options
+        }))
     }
-}
 
-impl ExtractorClone for DefaultExtractor {
-    fn extract_with_options(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
-        debug!("DefaultExtractor::extract_with_options called");
-        debug!("PBO path: {:?}", pbo_path);
-        debug!("Output dir: {:?}", output_dir);
-        debug!("Options: {:?}", options);
-        
-        options.validate()?;
+    /// Result of an extraction that had nothing left to extract
+    fn nothing_extracted(&self) -> ExtractResult {
+        ExtractResult {
+            return_code: 0,
+            config: self.config.clone(),
+            ..Default::default()
+        }
+    }
 
+    /// Operation arguments for an extraction, creating the output directory if needed
+    fn extraction_args(&self, output_dir: &Path, options: &ExtractOptions) -> Result<Vec<String>> {
         // Create output directory if it doesn't exist
-        if !output_dir.exists() {
-            std::fs::create_dir_all(output_dir).map_err(|_e| PboError::InvalidPath(output_dir.to_path_buf()))?;
-        }
+        output_dir.ensure_directory()?;
 
+        let mut args = Self::extraction_flags(options);
+        args.push(Self::output_dir_arg(output_dir)?);
+        Ok(args)
+    }
+
+    /// Option and filter arguments for an extraction, without the output directory
+    fn extraction_flags(options: &ExtractOptions) -> Vec<String> {
         let mut args = Vec::new();
         
-        // Build options string
+        // Build options string, -P and -W come from `build_args`
         let mut opts = String::new();
-        if options.no_pause { opts.push('P'); }
-        if options.warnings_as_errors { opts.push('W'); }
         if options.verbose { opts.push('N'); }
-        // Removed keep_pbo_name option as it's not supported
+        if options.decompress_only { opts.push('D'); }
+        if options.dump_headers { opts.push('H'); }
         if !opts.is_empty() { args.push(format!("-{}", opts)); }
         
         // Add file filter if present
-        if let Some(filter) = options.file_filter {
+        if let Some(filter) = &options.file_filter {
             args.push(format!("-F={}", filter));
         }
-        
-        // Add output directory
-        if let Some(out_str) = output_dir.canonicalize()
-            .ok()
-            .and_then(|p| p.to_str().map(|s| s.replace("\\\\?\\", "")))
-        {
-            args.push(out_str);
+
+        args
+    }
+
+    /// Absolute form of `output_dir` as passed to extractpbo, which needs a drive
+    /// letter on Windows. Directories that do not exist yet are not resolved further.
+    fn output_dir_arg(output_dir: &Path) -> Result<String> {
+        let absolute = if output_dir.exists() {
+            output_dir.canonicalize().ok()
         } else {
-            return Err(PboError::InvalidPath(output_dir.to_path_buf()));
-        }
-        
+            std::path::absolute(output_dir).ok()
+        };
+
+        absolute
+            .and_then(|p| p.to_str().map(|s| s.replace("\\\\?\\", "")))
+            .ok_or_else(|| PboError::InvalidPath(output_dir.to_path_buf()))
+    }
+
+    /// Command line an extraction would run, program first, without running it or
+    /// creating the output directory. Useful for reproducing an extraction by hand.
+    ///
+    /// Layouts other than `AsIs` extract into a staging folder inside `output_dir`,
+    /// which the preview shows as `output_dir` itself. Under Wine the paths are
+    /// shown as they are rather than run through `winepath`. Returns an empty
+    /// command when the overwrite policy leaves nothing to extract.
+    pub fn build_command_line(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<Vec<String>> {
+        let header = PboHeader::new(pbo_path);
+        let Some(options) = self.prepare_extraction(&header, output_dir, options)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut args = Self::extraction_flags(&options);
+        args.push(Self::output_dir_arg(output_dir)?);
         let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
-        self.run_extractpbo_command(args, pbo_path)
+
+        let mut argv = self.launcher();
+        argv.extend(self.assemble_args(&args, pbo_path, &options, &|path| Ok(path.to_string()))?);
+        Ok(argv)
     }
 
-    fn list_with_options(&self, pbo_path: &Path, options: ExtractOptions) -> Result<ExtractResult> {
-        trace!("DefaultExtractor::list_with_options called");
-        trace!("PBO path: {:?}", pbo_path);
-        trace!("Options: {:?}", options);
-        
-        options.validate()?;
-
-        let mut args = Vec::new();
-        
-        // Build options string
+    /// Operation arguments for a listing
+    fn listing_args(&self, options: &ExtractOptions) -> Vec<String> {
         let mut opts = String::new();
-        if options.no_pause { opts.push('P'); }
-        if options.warnings_as_errors { opts.push('W'); }
         if options.verbose { opts.push('N'); }
+        if options.decompress_only { opts.push('D'); }
+        if options.dump_headers { opts.push('H'); }
         opts.push('L');
         if options.brief_listing { opts.push('B'); }
-        args.push(format!("-{}", opts));
+        vec![format!("-{}", opts)]
+    }
+
+    /// Fail early for encrypted PBOs, which extractpbo only reports with a
+    /// generic failure. Headers that cannot be parsed are left for extractpbo.
+    fn check_not_encrypted(pbo_path: &Path) -> Result<()> {
+        let encrypted = is_encrypted_pbo_path(pbo_path)
+            || File::open(pbo_path)
+                .ok()
+                .and_then(|file| NativePboReader::from_file(file).ok())
+                .is_some_and(|reader| reader.is_encrypted());
+        if encrypted {
+            debug!("Refusing to run extractpbo on encrypted PBO {:?}", pbo_path);
+            return Err(encrypted_pbo());
+        }
+        Ok(())
+    }
+
+    fn check_executable(&self) -> Result<()> {
+        if let Some(path) = &self.extractpbo_path {
+            if !path.exists() {
+                return Err(PboError::CommandNotFound(path.display().to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    fn spawn_error(&self, e: io::Error) -> PboError {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => match &self.wine {
+                Some(wine) => PboError::Wine(format!("{} not found", wine.display())),
+                None => PboError::CommandNotFound(self.program_name()),
+            },
+            std::io::ErrorKind::PermissionDenied =>
+                PboError::FileSystem(FileSystemError::PathValidation(
+                    "Permission denied".to_string()
+                )),
+            _ => PboError::Extraction(ExtractError::CommandFailed {
+                cmd: self.program_name(),
+                reason: e.to_string(),
+            })
+        }
+    }
+
+    /// Turn the captured process output into an `ExtractResult`
+    fn process_output(&self, output: Output, command_duration: Duration, options: &ExtractOptions) -> Result<ExtractResult> {
+        let (stdout, stdout_redecoded) = process::decode_output(&output.stdout);
+        let (stderr, stderr_redecoded) = process::decode_output(&output.stderr);
+        self.build_result(output.status, &stdout, &stderr, stdout_redecoded || stderr_redecoded, command_duration, options)
+    }
+
+    fn build_result(
+        &self,
+        status: ExitStatus,
+        stdout: &str,
+        stderr: &str,
+        redecoded: bool,
+        command_duration: Duration,
+        options: &ExtractOptions,
+    ) -> Result<ExtractResult> {
+        let return_code = status.code().unwrap_or(-1);
+        let level = self.output_log_level(return_code);
+        trace!("Command completed with status {:?} in {:?}", status, command_duration);
+        log!(level, "Stdout: {}", stdout);
+        log!(level, "Stderr: {}", stderr);
+
+        let result = ExtractResult {
+            return_code,
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            config: self.config.clone(),
+            output_redecoded: redecoded,
+            checksum_skipped: options.skip_checksum,
+            warnings_as_errors: options.skip_checksum && self.warnings_as_errors(options),
+            metrics: Some(OperationMetrics {
+                command_duration,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        if let Some(line) = self.wine_failure(&result) {
+            return Err(PboError::Wine(format!("extractpbo failed under Wine: {}", line)));
+        }
+
+        // Check for the configured error patterns in the output
+        if stdout.lines().chain(stderr.lines()).any(|line| result.is_error_line(line)) {
+            return Err(PboError::ValidationFailed(
+                format!("PBO validation failed:\n{}", stderr)
+            ));
+        }
+        Ok(result)
+    }
+
+    /// First line of a failed run under Wine in which Wine reports the failure
+    fn wine_failure<'a>(&self, result: &'a ExtractResult) -> Option<&'a str> {
+        if self.wine.is_none() || result.return_code == 0 {
+            return None;
+        }
+        result.stderr.lines()
+            .chain(result.stdout.lines())
+            .map(str::trim)
+            .find(|line| WINE_FAILURE_INDICATORS.iter().any(|indicator| line.starts_with(indicator)))
+    }
+
+    /// Execute the extractpbo command with arguments ordered by `build_args`
+    fn run_extractpbo_command(&self, args: Vec<&str>, pbo_path: &Path, options: &ExtractOptions) -> Result<ExtractResult> {
+        self.run_extractpbo_streaming(args, pbo_path, options, &mut |_| {})
+    }
+
+    /// Write the raw output of an extractpbo run to `log_path`, headed by the
+    /// PBO path and the arguments it ran with
+    fn write_capture_log(
+        &self,
+        log_path: &Path,
+        pbo_path: &Path,
+        argv: &[String],
+        status: ExitStatus,
+        stdout: &str,
+        stderr: &str,
+    ) -> Result<()> {
+        log_path.ensure_parent_exists()?;
+        let contents = format!(
+            "PBO: {}\nCommand: {} {}\nExit status: {}\n\n--- stdout ---\n{}\n--- stderr ---\n{}\n",
+            pbo_path.display(),
+            self.launcher().join(" "),
+            argv.join(" "),
+            status,
+            stdout.trim_end(),
+            stderr.trim_end(),
+        );
+
+        debug!("Writing extractpbo output to {:?}", log_path);
+        std::fs::write(log_path, contents).map_err(|e| PboError::FileSystem(FileSystemError::WriteFile {
+            path: log_path.to_path_buf(),
+            reason: e.to_string(),
+        }))
+    }
+
+    /// Whether a failed run could not open the PBO although it exists, i.e.
+    /// another process probably holds it locked
+    fn is_locked_pbo(status: ExitStatus, stdout: &str, stderr: &str, pbo_path: &Path) -> bool {
+        !status.success()
+            && pbo_path.is_file()
+            && stdout.lines().chain(stderr.lines())
+                .any(|line| LOCKED_PBO_INDICATORS.iter().any(|indicator| line.contains(indicator)))
+    }
+
+    /// Count a failed attempt and return how long to wait before the next one,
+    /// or `None` once the configured retries are used up
+    fn next_attempt(&self, attempt: &mut u32, reason: &dyn std::fmt::Display) -> Option<Duration> {
+        let max_retries = self.config.max_retries();
+        if *attempt >= max_retries {
+            return None;
+        }
+        *attempt += 1;
+        warn!("extractpbo failed: {}, retrying ({}/{})", reason, attempt, max_retries);
+        Some(Duration::from_millis(RETRY_BACKOFF_MS * u64::from(*attempt)))
+    }
+
+    /// Like `run_extractpbo_command`, but passes each stdout line to `on_line` as
+    /// soon as extractpbo prints it. When a run is retried because the PBO was
+    /// locked, `on_line` also sees the output of the failed attempts.
+    ///
+    /// If the configured timeout elapses the child is killed, and `PboError::Timeout`
+    /// is only returned once it has exited and released the PBO and output directory.
+    fn run_extractpbo_streaming(
+        &self,
+        args: Vec<&str>,
+        pbo_path: &Path,
+        options: &ExtractOptions,
+        on_line: &mut dyn FnMut(&str),
+    ) -> Result<ExtractResult> {
+        debug!("Running extractpbo command with args: {:?}", args);
+        debug!("PBO path: {:?}", pbo_path);
+
+        Self::check_not_encrypted(pbo_path)?;
+        self.check_executable()?;
+        let argv = self.build_args(&args, pbo_path, options)?;
+        let launcher = self.launcher();
+        let mut command = Command::new(&launcher[0]);
+        command.args(&launcher[1..])
+            .args(&argv)
+            .envs(&self.env)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        trace!("Full command: {:?}", command);
+
+        let timeout = options.timeout
+            .or_else(|| self.timeout.map(|base| scaled_timeout(base, self.timeout_per_gb, pbo_path)));
+        let mut attempt = 0;
+        loop {
+            debug!("Running extractpbo (attempt {}/{})", attempt + 1, self.config.max_retries() + 1);
+            let child = match command.spawn() {
+                Ok(child) => child,
+                Err(e) if Self::is_transient_error(&e) => match self.next_attempt(&mut attempt, &e) {
+                    Some(delay) => {
+                        thread::sleep(delay);
+                        continue;
+                    }
+                    None => return Err(self.spawn_error(e)),
+                },
+                Err(e) => return Err(self.spawn_error(e)),
+            };
+
+            let started = Instant::now();
+            match process::wait_with_timeout(child, timeout, on_line) {
+                Ok(ProcessOutcome::Exited(output)) => {
+                    if let Some(log_path) = &options.capture_log {
+                        self.write_capture_log(log_path, pbo_path, &argv, output.status, &output.stdout, &output.stderr)?;
+                    }
+                    if Self::is_locked_pbo(output.status, &output.stdout, &output.stderr, pbo_path) {
+                        if let Some(delay) = self.next_attempt(&mut attempt, &"the PBO is locked") {
+                            thread::sleep(delay);
+                            continue;
+                        }
+                    }
+                    let result = self.build_result(
+                        output.status, &output.stdout, &output.stderr, output.redecoded, started.elapsed(), options,
+                    );
+                    return self.explain_failure(result);
+                }
+                Ok(ProcessOutcome::TimedOut) => {
+                    return Err(PboError::Timeout(timeout.unwrap_or_default()));
+                }
+                Err(e) => return Err(self.spawn_error(e)),
+            }
+        }
+    }
+
+    /// Validate `options` and resolve them into the options extractpbo runs with.
+    /// Returns `None` when the overwrite policy leaves nothing to extract.
+    fn prepare_extraction(&self, header: &PboHeader<'_>, output_dir: &Path, options: ExtractOptions) -> Result<Option<ExtractOptions>> {
+        options.validate_with(&self.config)?;
+        options.check_output_dir(output_dir)?;
+        let options = self.resolve_file_filter(header, options)?;
+        let Some(options) = Self::apply_newer_than(header, options)? else {
+            return Ok(None);
+        };
+        Self::check_size_limits(header, &options)?;
+        self.apply_overwrite_policy(header, output_dir, options)
+    }
+
+    /// Narrow the file filter to the selected entries newer than
+    /// `options.only_newer_than`. Returns `None` when no entry is newer.
+    fn apply_newer_than(header: &PboHeader<'_>, options: ExtractOptions) -> Result<Option<ExtractOptions>> {
+        let Some(threshold) = options.only_newer_than else {
+            return Ok(Some(options));
+        };
+
+        let reader = header.reader()?;
+        let newer: Vec<&str> = Self::selected_entries(reader, &options)?
+            .into_iter()
+            .filter(|entry| entry.timestamp == 0 || UNIX_EPOCH + Duration::from_secs(u64::from(entry.timestamp)) > threshold)
+            .map(|entry| entry.path.as_str())
+            .collect();
+        debug!("{} entries are newer than {:?}", newer.len(), threshold);
+        if newer.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(ExtractOptions {
+            file_filter: Some(file_list_filter(&newer, reader.header_entries())?),
+            filter_syntax: FilterSyntax::Glob,
+            ..options
+        }))
+    }
+
+    /// Run an extraction into the location required by `options.layout`
+    fn run_staged(
+        &self,
+        header: &PboHeader<'_>,
+        output_dir: &Path,
+        options: &ExtractOptions,
+        mut run: impl FnMut(Vec<&str>) -> Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        // Layouts other than AsIs extract into a staging folder first
+        let staging = options.layout.staging_dir(output_dir);
+        let target = staging.as_deref().unwrap_or(output_dir);
+        let mut result: Option<Result<ExtractResult>> = None;
+        for file_filter in split_file_filter(options.file_filter.as_deref()) {
+            let part = ExtractOptions { file_filter, ..options.clone() };
+            let part_result = self.extraction_args(target, &part)
+                .and_then(|args| run(args.iter().map(AsRef::as_ref).collect()));
+            if !merge_part(&mut result, part_result) {
+                break;
+            }
+        }
+        let result = result.expect("split_file_filter returns at least one filter");
+
+        let result = match staging {
+            Some(staging) => layout::finish_staged_extraction(options.layout, &staging, output_dir, header, result),
+            None => result,
+        };
+        let result = self.apply_timestamps(header, output_dir, options, result);
+        let result = self.extract_nested(header, output_dir, options, result);
+        let result = self.apply_prefix_options(header, output_dir, options, result);
+        let result = self.record_extracted(header, output_dir, options, result);
+        self.write_manifest(header, output_dir, options, result)
+    }
+
+    /// Apply the header timestamps to the files of a successful extraction when
+    /// `options.preserve_timestamps` is set. Runs before prefix folders are moved.
+    /// Only the modification time is set, without opening the files for
+    /// writing, so read-only files are handled too.
+    fn apply_timestamps(
+        &self,
+        header: &PboHeader<'_>,
+        output_dir: &Path,
+        options: &ExtractOptions,
+        result: Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        let result = result?;
+        if !result.is_success() || !options.preserve_timestamps {
+            return Ok(result);
+        }
+
+        let reader = header.reader()?;
+        let content_dir = options.layout.content_dir(output_dir, header.path(), reader.prefix().as_deref())?;
+        for entry in reader.header_entries().iter().filter(|entry| entry.timestamp != 0) {
+            let modified = UNIX_EPOCH + Duration::from_secs(u64::from(entry.timestamp));
+            for path in self.existing_destinations(&content_dir, &entry.path) {
+                trace!("Setting modification time of {:?} to {}", path, entry.timestamp);
+                let mtime = filetime::FileTime::from_system_time(modified);
+                fs_err!(filetime::set_file_mtime(&path, mtime) => WriteFile, path)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Extract the PBOs stored in a successful extraction when
+    /// `options.recurse_nested` is set. Runs before prefix folders are moved.
+    fn extract_nested(
+        &self,
+        header: &PboHeader<'_>,
+        output_dir: &Path,
+        options: &ExtractOptions,
+        result: Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        let result = result?;
+        if !result.is_success() || !options.recurse_nested {
+            return Ok(result);
+        }
+
+        let mut nested = self.nested_worklist(header, output_dir, options)?;
+        while let Some(pbo) = nested.next_pbo()? {
+            debug!("Extracting nested PBO {:?} into {:?}", pbo.path, pbo.target);
+            let nested_result = self.extract_with_options(&pbo.path, &pbo.target, nested::nested_options(options))?;
+            Self::check_nested_result(&pbo, &nested_result)?;
+            nested.extracted(&pbo)?;
+        }
+        Ok(result)
+    }
+
+    /// Nested PBOs stored in the entries `pbo_path` extracted into `output_dir`
+    fn nested_worklist(&self, header: &PboHeader<'_>, output_dir: &Path, options: &ExtractOptions) -> Result<NestedPbos> {
+        let reader = header.reader()?;
+        let content_dir = options.layout.content_dir(output_dir, header.path(), reader.prefix().as_deref())?;
+        NestedPbos::new(header.path(), reader.header_entries(), &content_dir, options.max_depth)
+    }
+
+    /// Fail the whole extraction when a nested PBO could not be extracted
+    fn check_nested_result(pbo: &NestedPbo, result: &ExtractResult) -> Result<()> {
+        if result.is_success() {
+            return Ok(());
+        }
+        Err(PboError::Extraction(ExtractError::CommandFailed {
+            cmd: "extractpbo".to_string(),
+            reason: format!(
+                "Failed to extract nested PBO {}: {}",
+                pbo.path.display(),
+                result.get_error_message().unwrap_or_else(|| "Unknown error".to_string()),
+            ),
+        }))
+    }
+
+    /// Files a successful extraction wrote into `output_dir` for the entries the
+    /// resolved file filter selected, with their size on disk. Entries are looked
+    /// up where the layout and prefix options put them, under their own name or
+    /// the name they are debinarized to; entries extractpbo skipped are left out.
+    fn written_files(
+        &self,
+        header: &PboHeader<'_>,
+        output_dir: &Path,
+        options: &ExtractOptions,
+        result: &ExtractResult,
+    ) -> Result<Vec<(PathBuf, u64)>> {
+        let reader = header.reader()?;
+        let mut content_dir = options.layout.content_dir(output_dir, header.path(), reader.prefix().as_deref())?;
+        if options.strip_prefix || options.rewrite_prefix.is_some() {
+            let prefix = result.get_prefix().or_else(|| reader.prefix());
+            content_dir = layout::prefix_moved_dir(output_dir, &content_dir, prefix.as_deref(), options.rewrite_prefix.as_deref())?;
+        }
+
+        let mut files = Vec::new();
+        for entry in Self::selected_entries(reader, options)? {
+            for path in self.existing_destinations(&content_dir, &entry.path) {
+                let size = fs_err!(path.metadata() => ReadFile, path)?.len();
+                files.push((path, size));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Fill in the file count and size of a successful extraction's metrics
+    /// from the files it wrote
+    fn record_extracted(
+        &self,
+        header: &PboHeader<'_>,
+        output_dir: &Path,
+        options: &ExtractOptions,
+        result: Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        let mut result = result?;
+        if !result.is_success() || result.metrics.is_none() {
+            return Ok(result);
+        }
+
+        let written = self.written_files(header, output_dir, options, &result)?;
+        if let Some(metrics) = result.metrics.as_mut() {
+            metrics.file_count = written.len();
+            metrics.bytes_extracted = written.iter().map(|(_, size)| size).sum();
+            debug!("Extraction metrics: {:?}", metrics);
+        }
+        Ok(result)
+    }
+
+    /// Write the manifest requested by `options.write_manifest` for a successful
+    /// extraction, listing the files it wrote relative to `output_dir`
+    fn write_manifest(
+        &self,
+        header: &PboHeader<'_>,
+        output_dir: &Path,
+        options: &ExtractOptions,
+        result: Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        let result = result?;
+        let Some(manifest_path) = options.write_manifest.as_deref().filter(|_| result.is_success()) else {
+            return Ok(result);
+        };
+
+        let files = self.written_files(header, output_dir, options, &result)?
+            .into_iter()
+            .map(|(path, size)| ManifestEntry {
+                path: path.strip_prefix(output_dir).unwrap_or(&path).to_string_lossy().into_owned(),
+                size,
+            })
+            .collect();
+        let prefix = result.get_prefix()
+            .or_else(|| header.prefix());
+        let manifest = ExtractManifest {
+            pbo: header.path().to_path_buf(),
+            prefix,
+            files,
+        };
+        manifest.write(manifest_path)?;
+        Ok(result)
+    }
+
+    /// Strip or rewrite the prefix folders of a successful extraction as
+    /// requested by `options`
+    fn apply_prefix_options(
+        &self,
+        header: &PboHeader<'_>,
+        output_dir: &Path,
+        options: &ExtractOptions,
+        result: Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        let result = result?;
+        if !result.is_success() || !(options.strip_prefix || options.rewrite_prefix.is_some()) {
+            return Ok(result);
+        }
+
+        let prefix = result.get_prefix()
+            .or_else(|| header.prefix());
+        layout::move_prefix_contents(output_dir, prefix.as_deref(), options.rewrite_prefix.as_deref())?;
+        Ok(result)
+    }
+
+    /// Extract files from a PBO, passing each line extractpbo prints (such as
+    /// `Extracting config.bin...`) to `on_line` as it arrives
+    pub fn extract_with_callback(
+        &self,
+        pbo_path: &Path,
+        output_dir: &Path,
+        options: ExtractOptions,
+        mut on_line: impl FnMut(&str),
+    ) -> Result<ExtractResult> {
+        let header = PboHeader::new(pbo_path);
+        let Some(options) = self.prepare_extraction(&header, output_dir, options)? else {
+            return Ok(self.nothing_extracted());
+        };
+        self.run_staged(&header, output_dir, &options, |args| {
+            self.run_extractpbo_streaming(args, pbo_path, &options, &mut on_line)
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl DefaultExtractor {
+    /// Async counterpart of `run_extractpbo_command` using `tokio::process`.
+    /// The child is killed if the returned future is dropped, e.g. on timeout.
+    async fn run_extractpbo_command_async(&self, args: Vec<&str>, pbo_path: &Path, options: &ExtractOptions) -> Result<ExtractResult> {
+        debug!("Running extractpbo command asynchronously with args: {:?}", args);
         
+        Self::check_not_encrypted(pbo_path)?;
+        self.check_executable()?;
+        let argv = self.build_args(&args, pbo_path, options)?;
+        let launcher = self.launcher();
+        let mut command = tokio::process::Command::new(&launcher[0]);
+        command.args(&launcher[1..]).args(&argv).envs(&self.env).kill_on_drop(true);
+
+        trace!("Full command: {:?}", command);
+
+        let mut attempt = 0;
+        loop {
+            debug!("Running extractpbo (attempt {}/{})", attempt + 1, self.config.max_retries() + 1);
+            let started = Instant::now();
+            let output = match command.output().await {
+                Ok(output) => output,
+                Err(e) if Self::is_transient_error(&e) => match self.next_attempt(&mut attempt, &e) {
+                    Some(delay) => {
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                    None => return Err(self.spawn_error(e)),
+                },
+                Err(e) => return Err(self.spawn_error(e)),
+            };
+            let command_duration = started.elapsed();
+
+            if let Some(log_path) = &options.capture_log {
+                self.write_capture_log(
+                    log_path,
+                    pbo_path,
+                    &argv,
+                    output.status,
+                    &process::decode_output(&output.stdout).0,
+                    &process::decode_output(&output.stderr).0,
+                )?;
+            }
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if Self::is_locked_pbo(output.status, &stdout, &stderr, pbo_path) {
+                if let Some(delay) = self.next_attempt(&mut attempt, &"the PBO is locked") {
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+            }
+            return self.explain_failure(self.process_output(output, command_duration, options));
+        }
+    }
+
+    /// Extract files from a PBO without blocking the async runtime
+    pub async fn extract_with_options_async(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        let header = PboHeader::new(pbo_path);
+        let Some(options) = self.prepare_extraction(&header, output_dir, options)? else {
+            return Ok(self.nothing_extracted());
+        };
+
+        // See `run_staged`
+        let staging = options.layout.staging_dir(output_dir);
+        let target = staging.as_deref().unwrap_or(output_dir);
+        let mut result: Option<Result<ExtractResult>> = None;
+        for file_filter in split_file_filter(options.file_filter.as_deref()) {
+            let part = ExtractOptions { file_filter, ..options.clone() };
+            let part_result = match self.extraction_args(target, &part) {
+                Ok(args) => self.run_extractpbo_command_async(args.iter().map(AsRef::as_ref).collect(), pbo_path, &part).await,
+                Err(e) => Err(e),
+            };
+            if !merge_part(&mut result, part_result) {
+                break;
+            }
+        }
+        let result = result.expect("split_file_filter returns at least one filter");
+
+        let result = match staging {
+            Some(staging) => layout::finish_staged_extraction(options.layout, &staging, output_dir, &header, result),
+            None => result,
+        };
+        let result = self.apply_timestamps(&header, output_dir, &options, result);
+        let result = self.extract_nested_async(&header, output_dir, &options, result).await;
+        let result = self.apply_prefix_options(&header, output_dir, &options, result);
+        let result = self.record_extracted(&header, output_dir, &options, result);
+        self.write_manifest(&header, output_dir, &options, result)
+    }
+
+    /// Async counterpart of `extract_nested`
+    async fn extract_nested_async(
+        &self,
+        header: &PboHeader<'_>,
+        output_dir: &Path,
+        options: &ExtractOptions,
+        result: Result<ExtractResult>,
+    ) -> Result<ExtractResult> {
+        let result = result?;
+        if !result.is_success() || !options.recurse_nested {
+            return Ok(result);
+        }
+
+        let mut nested = self.nested_worklist(header, output_dir, options)?;
+        while let Some(pbo) = nested.next_pbo()? {
+            debug!("Extracting nested PBO {:?} into {:?}", pbo.path, pbo.target);
+            let extraction = self.extract_with_options_async(&pbo.path, &pbo.target, nested::nested_options(options));
+            let nested_result = Box::pin(extraction).await?;
+            Self::check_nested_result(&pbo, &nested_result)?;
+            nested.extracted(&pbo)?;
+        }
+        Ok(result)
+    }
+
+    /// List contents of a PBO without blocking the async runtime
+    pub async fn list_with_options_async(&self, pbo_path: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        options.validate_with(&self.config)?;
+        let args = self.listing_args(&options);
         let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
-        self.run_extractpbo_command(args, pbo_path)
+        self.run_extractpbo_command_async(args, pbo_path, &options).await
+    }
+}
+
+impl ExtractorClone for DefaultExtractor {
+    fn extract_with_options(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        debug!("DefaultExtractor::extract_with_options called");
+        debug!("PBO path: {:?}", pbo_path);
+        debug!("Output dir: {:?}", output_dir);
+        debug!("Options: {:?}", options);
+        
+        let header = PboHeader::new(pbo_path);
+        let Some(options) = self.prepare_extraction(&header, output_dir, options)? else {
+            return Ok(self.nothing_extracted());
+        };
+        self.run_staged(&header, output_dir, &options, |args| {
+            self.run_extractpbo_command(args, pbo_path, &options)
+        })
+    }
+
+    fn list_with_options(&self, pbo_path: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        trace!("DefaultExtractor::list_with_options called");
+        trace!("PBO path: {:?}", pbo_path);
+        trace!("Options: {:?}", options);
+        
+        options.validate_with(&self.config)?;
+
+        let args = self.listing_args(&options);
+        let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+        self.run_extractpbo_command(args, pbo_path, &options)
     }
 
     fn extract(&self, pbo_path: &Path, output_dir: &Path, file_filter: Option<&str>) -> Result<ExtractResult> {
@@ -345,9 +1808,7 @@ impl ExtractorClone for DefaultExtractor {
         trace!("File filter: {:?}", file_filter);
         
         // Create output directory if it doesn't exist
-        if !output_dir.exists() {
-            std::fs::create_dir_all(output_dir).map_err(|_e| PboError::InvalidPath(output_dir.to_path_buf()))?;
-        }
+        output_dir.ensure_directory()?;
         
         let mut args = Vec::new();
         
@@ -369,7 +1830,7 @@ impl ExtractorClone for DefaultExtractor {
         // Convert args to string slices for command
         let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
         debug!("Calling run_extractpbo_command with args: {:?}", args);
-        self.run_extractpbo_command(args, pbo_path)
+        self.run_extractpbo_command(args, pbo_path, &ExtractOptions::for_extraction())
     }
 
     fn list_contents(&self, pbo_path: &Path, brief: bool) -> Result<ExtractResult> {
@@ -379,7 +1840,7 @@ impl ExtractorClone for DefaultExtractor {
         
         let args = if brief { vec!["-LB"] } else { vec!["-L"] };
         debug!("Calling run_extractpbo_command with args: {:?}", args);
-        self.run_extractpbo_command(args, pbo_path)
+        self.run_extractpbo_command(args, pbo_path, &ExtractOptions::for_listing())
     }
 
     fn clone_box(&self) -> Box<dyn ExtractorClone> {
@@ -417,6 +1878,101 @@ mod tests {
             ..Default::default()
         };
         assert!(options.validate().is_ok());
+
+        // Atomic extraction writes to an empty temp dir, so only Overwrite makes sense
+        let options = ExtractOptions {
+            atomic: true,
+            overwrite: OverwritePolicy::Skip,
+            ..Default::default()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+    }
+
+    #[test]
+    fn test_decompress_and_header_dump_flags() {
+        let extractor = DefaultExtractor::new();
+        let temp = tempfile::tempdir().unwrap();
+
+        let options = ExtractOptions {
+            decompress_only: true,
+            ..ExtractOptions::for_extraction()
+        };
+        assert!(options.validate().is_ok());
+        assert_eq!(extractor.extraction_args(temp.path(), &options).unwrap()[0], "-D");
+        let argv = extractor.build_args(&["-D"], &crate::test_utils::get_test_pbo_path(), &options).unwrap();
+        assert_eq!(argv[..2], ["-PW", "-D"]);
+
+        let options = ExtractOptions {
+            skip_checksum: true,
+            ..ExtractOptions::for_extraction()
+        };
+        assert_eq!(extractor.listing_args(&options), vec!["-L"]);
+        let argv = extractor.build_args(&["-L"], &crate::test_utils::get_test_pbo_path(), &options).unwrap();
+        assert_eq!(argv[..2], ["-P", "-L"]);
+
+        let options = ExtractOptions {
+            dump_headers: true,
+            ..ExtractOptions::for_listing()
+        };
+        assert_eq!(extractor.listing_args(&options), vec!["-HL"]);
+
+        let options = ExtractOptions {
+            dump_headers: true,
+            file_filter: Some("*.paa".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+
+        let options = ExtractOptions {
+            dump_headers: true,
+            decompress_only: true,
+            ..Default::default()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+    }
+
+    #[test]
+    fn test_prefix_option_validation() {
+        let options = ExtractOptions {
+            strip_prefix: true,
+            rewrite_prefix: Some("my_mod".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+
+        let options = ExtractOptions {
+            strip_prefix: true,
+            layout: OutputLayout::FlattenPrefix,
+            ..Default::default()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+
+        let options = ExtractOptions {
+            rewrite_prefix: Some("my_mod".to_string()),
+            layout: OutputLayout::PrefixFolders,
+            ..Default::default()
+        };
+        assert!(options.validate().is_ok());
+    }
+
+    #[test]
+    fn test_build_command_line() {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().join("out");
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        let extractor = DefaultExtractor::new().with_extractpbo_path(PathBuf::from("/opt/extractpbo"));
+
+        let options = ExtractOptions {
+            file_filter: Some("*.paa".to_string()),
+            ..ExtractOptions::for_extraction()
+        };
+        let argv = extractor.build_command_line(&pbo_path, &output_dir, options).unwrap();
+
+        assert_eq!(argv.first().map(String::as_str), Some("/opt/extractpbo"));
+        assert!(argv.contains(&"-F=*.paa".to_string()));
+        assert!(argv.contains(&pbo_path.display().to_string()));
+        assert_eq!(argv.last(), Some(&std::path::absolute(&output_dir).unwrap().display().to_string()));
+        assert!(!output_dir.exists(), "Previewing must not create the output directory");
     }
 
     #[test]
@@ -437,4 +1993,540 @@ mod tests {
         assert!(!extraction.brief_listing);
         assert!(extraction.validate().is_ok());
     }
+
+    #[test]
+    fn test_regex_filter_validation() {
+        let options = ExtractOptions {
+            file_filter: Some("^uniform/.*\\.(paa|p3d)$".to_string()),
+            filter_syntax: FilterSyntax::Regex,
+            ..Default::default()
+        };
+        assert!(options.validate().is_ok());
+
+        let options = ExtractOptions {
+            file_filter: Some("[[invalid".to_string()),
+            filter_syntax: FilterSyntax::Regex,
+            ..Default::default()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+    }
+
+    #[test]
+    fn test_resolve_regex_filter() {
+        let extractor = DefaultExtractor::new();
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        let options = ExtractOptions {
+            file_filter: Some("^uniform/.*\\.paa$".to_string()),
+            filter_syntax: FilterSyntax::Regex,
+            ..Default::default()
+        };
+
+        let resolved = extractor.resolve_file_filter(&PboHeader::new(&pbo_path), options).unwrap();
+        assert_eq!(resolved.filter_syntax, FilterSyntax::Glob);
+        assert_eq!(resolved.file_filter.as_deref(), Some("uniform\\black.paa"));
+
+        let options = ExtractOptions {
+            file_filter: Some("\\.sqf$".to_string()),
+            filter_syntax: FilterSyntax::Regex,
+            ..Default::default()
+        };
+        assert!(matches!(
+            extractor.resolve_file_filter(&PboHeader::new(&pbo_path), options),
+            Err(PboError::Extraction(ExtractError::NoFiles))
+        ));
+
+        let glob = ExtractOptions {
+            file_filter: Some("*.paa".to_string()),
+            ..Default::default()
+        };
+        let resolved = extractor.resolve_file_filter(&PboHeader::new(&pbo_path), glob).unwrap();
+        assert_eq!(resolved.file_filter.as_deref(), Some("*.paa"));
+    }
+
+    #[test]
+    fn test_regex_filter_special_names() {
+        let temp = tempfile::tempdir().unwrap();
+        let pbo_path = temp.path().join("names.pbo");
+        let files: [(&str, &[u8]); 3] = [("data\\a,b.sqf", b"1"), ("data\\a_b.sqf", b"2"), ("data\\c*.sqf", b"3")];
+        std::fs::write(&pbo_path, crate::test_utils::build_pbo(&[], &files)).unwrap();
+        let extractor = DefaultExtractor::new();
+        let regex = |pattern: &str| ExtractOptions {
+            file_filter: Some(pattern.to_string()),
+            filter_syntax: FilterSyntax::Regex,
+            ..Default::default()
+        };
+
+        let resolved = extractor.resolve_file_filter(&PboHeader::new(&pbo_path), regex("^data/(a|c)")).unwrap();
+        assert_eq!(resolved.file_filter.as_deref(), Some("data\\a?b.sqf,data\\a_b.sqf,data\\c*.sqf"));
+        let resolved = extractor.resolve_file_filter(&PboHeader::new(&pbo_path), regex("^data/c")).unwrap();
+        assert_eq!(resolved.file_filter.as_deref(), Some("data\\c*.sqf"));
+
+        // The list cannot select a,b.sqf without a_b.sqf
+        assert!(matches!(
+            extractor.resolve_file_filter(&PboHeader::new(&pbo_path), regex(",")),
+            Err(PboError::Extraction(ExtractError::InvalidFilter(_)))
+        ));
+    }
+
+    #[test]
+    fn test_regex_filter_case_sensitivity() {
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        let options = ExtractOptions {
+            file_filter: Some("^texheaders\\.bin$".to_string()),
+            filter_syntax: FilterSyntax::Regex,
+            ..Default::default()
+        };
+
+        let resolved = DefaultExtractor::new().resolve_file_filter(&PboHeader::new(&pbo_path), options.clone()).unwrap();
+        assert_eq!(resolved.file_filter.as_deref(), Some("texHeaders.bin"));
+
+        let config = PboConfig::builder().case_sensitive(true).build();
+        let extractor = DefaultExtractor::with_config(Arc::new(config));
+        assert!(matches!(
+            extractor.resolve_file_filter(&PboHeader::new(&pbo_path), options),
+            Err(PboError::Extraction(ExtractError::NoFiles))
+        ));
+    }
+
+    #[test]
+    fn test_validate_detailed() {
+        assert_eq!(ExtractOptions::for_extraction().validate_detailed(), Ok(()));
+
+        let options = ExtractOptions {
+            dump_headers: true,
+            decompress_only: true,
+            file_filter: Some("[[invalid".to_string()),
+            filter_syntax: FilterSyntax::Regex,
+            atomic: true,
+            overwrite: OverwritePolicy::Skip,
+            ..Default::default()
+        };
+        let issues = options.validate_detailed().unwrap_err();
+        let fields: Vec<&str> = issues.iter().map(|issue| issue.field).collect();
+        assert_eq!(fields, vec!["dump_headers", "dump_headers", "overwrite", "file_filter"]);
+
+        match options.validate() {
+            Err(PboError::ValidationFailed(msg)) => {
+                assert_eq!(msg.split("; ").count(), 4);
+                assert!(msg.contains("Invalid file filter pattern: [[invalid"));
+            }
+            other => panic!("Expected ValidationFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        let reader = NativePboReader::open(&pbo_path).unwrap();
+        let file_count = reader.header_entries().len();
+        let total_size: u64 = reader.header_entries().iter().map(HeaderEntry::uncompressed_size).sum();
+
+        let within = ExtractOptions {
+            max_total_size: Some(total_size),
+            max_file_count: Some(file_count),
+            ..Default::default()
+        };
+        assert!(DefaultExtractor::check_size_limits(&PboHeader::new(&pbo_path), &within).is_ok());
+
+        let too_many = ExtractOptions {
+            max_file_count: Some(file_count - 1),
+            ..Default::default()
+        };
+        match DefaultExtractor::check_size_limits(&PboHeader::new(&pbo_path), &too_many) {
+            Err(PboError::ValidationFailed(msg)) => assert!(msg.contains(&file_count.to_string())),
+            other => panic!("Expected ValidationFailed, got {:?}", other),
+        }
+
+        let too_large = ExtractOptions {
+            max_total_size: Some(total_size - 1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            DefaultExtractor::check_size_limits(&PboHeader::new(&pbo_path), &too_large),
+            Err(PboError::ValidationFailed(_))
+        ));
+
+        // Only the files selected by the filter count
+        let filtered = ExtractOptions {
+            file_filter: Some("logo.paa".to_string()),
+            max_file_count: Some(1),
+            ..Default::default()
+        };
+        assert!(DefaultExtractor::check_size_limits(&PboHeader::new(&pbo_path), &filtered).is_ok());
+    }
+
+    #[test]
+    fn test_missing_output_dir_not_created() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let output_dir = temp.path().join("typo");
+        let options = ExtractOptions {
+            create_output_dir: false,
+            ..ExtractOptions::for_extraction()
+        };
+        assert!(ExtractOptions::default().create_output_dir);
+
+        let extractor = DefaultExtractor::new();
+        match extractor.extract_with_options(&crate::test_utils::get_test_pbo_path(), &output_dir, options.clone()) {
+            Err(PboError::InvalidPath(path)) => assert_eq!(path, output_dir),
+            other => panic!("Expected InvalidPath, got {:?}", other),
+        }
+        assert!(!output_dir.exists());
+        assert!(options.check_output_dir(temp.path()).is_ok());
+    }
+
+    #[test]
+    fn test_exclude_filter_validation() {
+        let options = ExtractOptions {
+            exclude_filter: Some("*.paa".to_string()),
+            brief_listing: true,
+            ..Default::default()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+
+        let options = ExtractOptions {
+            exclude_filter: Some("[[invalid".to_string()),
+            filter_syntax: FilterSyntax::Regex,
+            ..Default::default()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+    }
+
+    #[test]
+    fn test_resolve_exclude_filter() {
+        let extractor = DefaultExtractor::new();
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        let entries: Vec<String> = NativePboReader::open(&pbo_path).unwrap()
+            .header_entries()
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect();
+
+        let options = ExtractOptions {
+            exclude_filter: Some("*.paa".to_string()),
+            ..Default::default()
+        };
+        let resolved = extractor.resolve_file_filter(&PboHeader::new(&pbo_path), options).unwrap();
+        let expected: Vec<&str> = entries.iter()
+            .map(String::as_str)
+            .filter(|path| !path.ends_with(".paa"))
+            .collect();
+        assert_eq!(resolved.file_filter, Some(expected.join(",")));
+        assert_eq!(resolved.exclude_filter, None);
+
+        // The include filter applies first
+        let options = ExtractOptions {
+            file_filter: Some("uniform\\*".to_string()),
+            exclude_filter: Some("*.p3d,*.rvmat".to_string()),
+            ..Default::default()
+        };
+        let resolved = extractor.resolve_file_filter(&PboHeader::new(&pbo_path), options).unwrap();
+        assert_eq!(resolved.file_filter.as_deref(), Some("uniform\\black.paa"));
+
+        let options = ExtractOptions {
+            exclude_filter: Some("*".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            extractor.resolve_file_filter(&PboHeader::new(&pbo_path), options),
+            Err(PboError::Extraction(ExtractError::NoFiles))
+        ));
+    }
+
+    #[test]
+    fn test_exclude_filter_special_names() {
+        let temp = tempfile::tempdir().unwrap();
+        let pbo_path = temp.path().join("names.pbo");
+        let files: [(&str, &[u8]); 3] = [("a,b.sqf", b"1"), ("a_b.sqf", b"2"), ("c.sqf", b"3")];
+        std::fs::write(&pbo_path, crate::test_utils::build_pbo(&[], &files)).unwrap();
+        let extractor = DefaultExtractor::new();
+        let exclude = |pattern: &str| ExtractOptions {
+            exclude_filter: Some(pattern.to_string()),
+            ..Default::default()
+        };
+
+        let resolved = extractor.resolve_file_filter(&PboHeader::new(&pbo_path), exclude("c.sqf")).unwrap();
+        assert_eq!(resolved.file_filter.as_deref(), Some("a?b.sqf,a_b.sqf"));
+
+        // a?b.sqf would bring back the excluded a_b.sqf
+        assert!(matches!(
+            extractor.resolve_file_filter(&PboHeader::new(&pbo_path), exclude("a_b.sqf")),
+            Err(PboError::Extraction(ExtractError::InvalidFilter(_)))
+        ));
+    }
+
+    #[test]
+    fn test_glob_filter_regex() {
+        let regex = glob_filter_regex("*.paa, config.bin").unwrap();
+        assert!(regex.is_match("logo.paa"));
+        assert!(regex.is_match("uniform\\black.paa"));
+        assert!(regex.is_match("CONFIG.BIN"));
+        assert!(!regex.is_match("uniform\\mirror.p3d"));
+
+        let regex = glob_filter_regex("uniform/?lack.PAA").unwrap();
+        assert!(regex.is_match("uniform\\black.paa"));
+        assert!(!regex.is_match("logo.paa"));
+    }
+
+    #[test]
+    fn test_scaled_timeout() {
+        let temp = tempfile::tempdir().unwrap();
+        let small = temp.path().join("small.pbo");
+        std::fs::write(&small, b"data").unwrap();
+        let large = temp.path().join("large.pbo");
+        std::fs::File::create(&large).unwrap().set_len(4 * 1024 * 1024 * 1024 + 1).unwrap();
+
+        let base = Duration::from_secs(30);
+        let per_gb = Some(Duration::from_secs(60));
+        assert_eq!(scaled_timeout(base, per_gb, &small), base);
+        assert_eq!(scaled_timeout(base, per_gb, &large), Duration::from_secs(30 + 4 * 60));
+        assert_eq!(scaled_timeout(base, None, &large), base);
+        assert_eq!(scaled_timeout(base, per_gb, &temp.path().join("missing.pbo")), base);
+    }
+
+    #[test]
+    fn test_only_newer_than() {
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        let reader = NativePboReader::open(&pbo_path).unwrap();
+        let mut timestamps: Vec<u32> = reader.header_entries().iter().map(|entry| entry.timestamp).collect();
+        timestamps.sort();
+        let newest = *timestamps.last().unwrap();
+        assert!(timestamps[0] > 0 && timestamps[0] < newest);
+
+        let options = |threshold: u32| ExtractOptions {
+            only_newer_than: Some(UNIX_EPOCH + Duration::from_secs(u64::from(threshold))),
+            ..ExtractOptions::for_extraction()
+        };
+        let expected: Vec<&str> = reader.header_entries()
+            .iter()
+            .filter(|entry| entry.timestamp == newest)
+            .map(|entry| entry.path.as_str())
+            .collect();
+
+        let resolved = DefaultExtractor::apply_newer_than(&PboHeader::new(&pbo_path), options(newest - 1)).unwrap().unwrap();
+        assert_eq!(resolved.file_filter, Some(expected.join(",")));
+        assert!(DefaultExtractor::apply_newer_than(&PboHeader::new(&pbo_path), options(newest)).unwrap().is_none());
+
+        let untouched = DefaultExtractor::apply_newer_than(&PboHeader::new(&pbo_path), ExtractOptions::for_extraction()).unwrap().unwrap();
+        assert_eq!(untouched.file_filter, None);
+
+        let listing = ExtractOptions { only_newer_than: Some(SystemTime::now()), ..ExtractOptions::for_brief_listing() };
+        assert!(listing.validate().is_err());
+    }
+
+    #[test]
+    fn test_only_newer_than_special_names() {
+        let temp = tempfile::tempdir().unwrap();
+        let pbo_path = temp.path().join("names.pbo");
+        let files: [(&str, &[u8]); 2] = [("data\\a,b.sqf", b"1"), ("data\\c.sqf", b"2")];
+        std::fs::write(&pbo_path, crate::test_utils::build_pbo(&[], &files)).unwrap();
+
+        // Entries without a timestamp always count as newer
+        let options = ExtractOptions { only_newer_than: Some(UNIX_EPOCH), ..ExtractOptions::for_extraction() };
+        let resolved = DefaultExtractor::apply_newer_than(&PboHeader::new(&pbo_path), options).unwrap().unwrap();
+        assert_eq!(resolved.file_filter.as_deref(), Some("data\\a?b.sqf,data\\c.sqf"));
+    }
+
+    #[test]
+    fn test_file_list_filter() {
+        let temp = tempfile::tempdir().unwrap();
+        let pbo_path = temp.path().join("names.pbo");
+        let files: [(&str, &[u8]); 3] = [("a,b.sqf", b"1"), ("a_b.sqf", b"2"), ("c.sqf", b"3")];
+        std::fs::write(&pbo_path, crate::test_utils::build_pbo(&[], &files)).unwrap();
+        let reader = NativePboReader::open(&pbo_path).unwrap();
+        let entries = reader.header_entries();
+
+        assert_eq!(file_list_filter(&["c.sqf"], entries).unwrap(), "c.sqf");
+        let filter = file_list_filter(&["a,b.sqf", "a_b.sqf"], entries).unwrap();
+        assert_eq!(filter, "a?b.sqf,a_b.sqf");
+        let regex = glob_filter_regex(&filter).unwrap();
+        assert!(regex.is_match("a,b.sqf") && !regex.is_match("c.sqf"));
+
+        // a?b.sqf would also extract a_b.sqf
+        assert!(matches!(
+            file_list_filter(&["a,b.sqf"], entries),
+            Err(PboError::Extraction(ExtractError::InvalidFilter(_)))
+        ));
+    }
+
+    #[test]
+    fn test_split_file_filter() {
+        assert_eq!(split_file_filter(None), vec![None]);
+        assert_eq!(split_file_filter(Some("*.paa")), vec![Some("*.paa".to_string())]);
+
+        let names: Vec<String> = (0..2000).map(|i| format!("addons\\data\\texture_{:04}.paa", i)).collect();
+        let filter = names.join(",");
+        let parts: Vec<String> = split_file_filter(Some(&filter)).into_iter().map(Option::unwrap).collect();
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|part| part.len() <= MAX_FILE_FILTER_LENGTH));
+        assert_eq!(parts.join(","), filter);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_long_file_list_split_over_runs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let names: Vec<String> = (0..1000).map(|i| format!("addons\\data\\texture_{:04}.paa", i)).collect();
+        let files: Vec<(&str, &[u8])> = names.iter().map(|name| (name.as_str(), &b"x"[..])).collect();
+        let pbo_path = temp.path().join("large.pbo");
+        std::fs::write(&pbo_path, crate::test_utils::build_pbo(&[], &files)).unwrap();
+
+        let runs = temp.path().join("runs");
+        let script = temp.path().join("extractpbo");
+        std::fs::write(&script, format!("#!/bin/sh\necho run >> '{}'\necho 'Extracting texture.paa...'\n", runs.display())).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let extractor = DefaultExtractor::new().with_extractpbo_path(script);
+        let options = ExtractOptions { only_newer_than: Some(UNIX_EPOCH), ..ExtractOptions::for_extraction() };
+        let result = extractor.extract_with_options(&pbo_path, &temp.path().join("out"), options).unwrap();
+        assert!(result.is_success());
+        assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 2);
+        assert_eq!(result.stdout.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_overwrite_policy() {
+        let temp = tempfile::tempdir().unwrap();
+        let extractor = DefaultExtractor::new();
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+        let content_dir = temp.path().join("tc/mirrorform");
+        std::fs::create_dir_all(content_dir.join("uniform")).unwrap();
+        std::fs::write(content_dir.join("logo.paa"), "local").unwrap();
+        // config.bin is written debinarized, so config.cpp counts as a conflict
+        std::fs::write(content_dir.join("config.cpp"), "local").unwrap();
+
+        let options = |overwrite| ExtractOptions {
+            file_filter: Some("*.paa,config.bin".to_string()),
+            overwrite,
+            ..ExtractOptions::for_extraction()
+        };
+
+        let resolved = extractor.apply_overwrite_policy(&PboHeader::new(&pbo_path), temp.path(), options(OverwritePolicy::Overwrite)).unwrap();
+        assert_eq!(resolved.unwrap().file_filter.as_deref(), Some("*.paa,config.bin"));
+
+        match extractor.apply_overwrite_policy(&PboHeader::new(&pbo_path), temp.path(), options(OverwritePolicy::FailIfExists)) {
+            Err(PboError::FileSystem(FileSystemError::AlreadyExists { mut conflicts, .. })) => {
+                conflicts.sort();
+                assert_eq!(conflicts, vec!["config.bin", "logo.paa"]);
+            }
+            other => panic!("Expected AlreadyExists, got {:?}", other),
+        }
+
+        let resolved = extractor.apply_overwrite_policy(&PboHeader::new(&pbo_path), temp.path(), options(OverwritePolicy::Skip)).unwrap();
+        assert_eq!(resolved.unwrap().file_filter.as_deref(), Some("logo_small.paa,uniform\\black.paa"));
+
+        std::fs::write(content_dir.join("logo_small.paa"), "local").unwrap();
+        std::fs::write(content_dir.join("uniform/black.paa"), "local").unwrap();
+        let resolved = extractor.apply_overwrite_policy(&PboHeader::new(&pbo_path), temp.path(), options(OverwritePolicy::Skip)).unwrap();
+        assert!(resolved.is_none());
+
+        // A flattened layout writes to a different place, so nothing conflicts
+        let flattened = ExtractOptions {
+            layout: OutputLayout::FlattenPrefix,
+            ..options(OverwritePolicy::FailIfExists)
+        };
+        assert!(extractor.apply_overwrite_policy(&PboHeader::new(&pbo_path), temp.path(), flattened).is_ok());
+    }
+
+    #[test]
+    fn test_overwrite_skip_special_names() {
+        let temp = tempfile::tempdir().unwrap();
+        let pbo_path = temp.path().join("names.pbo");
+        let files: [(&str, &[u8]); 3] = [("a,b.sqf", b"1"), ("a_b.sqf", b"2"), ("c.sqf", b"3")];
+        std::fs::write(&pbo_path, crate::test_utils::build_pbo(&[], &files)).unwrap();
+        let extractor = DefaultExtractor::new();
+        let skip = ExtractOptions {
+            overwrite: OverwritePolicy::Skip,
+            ..ExtractOptions::for_extraction()
+        };
+
+        std::fs::write(temp.path().join("c.sqf"), "local").unwrap();
+        let resolved = extractor.apply_overwrite_policy(&PboHeader::new(&pbo_path), temp.path(), skip.clone()).unwrap();
+        assert_eq!(resolved.unwrap().file_filter.as_deref(), Some("a?b.sqf,a_b.sqf"));
+
+        // a?b.sqf would overwrite the existing a_b.sqf
+        std::fs::write(temp.path().join("a_b.sqf"), "local").unwrap();
+        assert!(matches!(
+            extractor.apply_overwrite_policy(&PboHeader::new(&pbo_path), temp.path(), skip),
+            Err(PboError::Extraction(ExtractError::InvalidFilter(_)))
+        ));
+    }
+
+    #[test]
+    fn test_filter_allowed_special_chars() {
+        let options = ExtractOptions {
+            file_filter: Some("data\\o'brien.paa".to_string()),
+            ..ExtractOptions::for_extraction()
+        };
+        assert!(matches!(options.validate(), Err(PboError::ValidationFailed(_))));
+
+        let config = PboConfig::builder().allow_special_chars(['\'']).build();
+        assert!(options.validate_with(&config).is_ok());
+        assert!(options.validate_detailed_with(&config).is_ok());
+    }
+
+    #[test]
+    fn test_output_log_level() {
+        let extractor = DefaultExtractor::new();
+        assert_eq!(extractor.output_log_level(0), Level::Trace);
+        assert_eq!(extractor.output_log_level(1), Level::Warn);
+
+        let extractor = extractor.with_output_log_level(Level::Info);
+        assert_eq!(extractor.output_log_level(0), Level::Info);
+        assert_eq!(extractor.output_log_level(-1), Level::Warn);
+
+        let extractor = extractor.with_output_log_level(Level::Error);
+        assert_eq!(extractor.output_log_level(2), Level::Error);
+    }
+
+    #[test]
+    fn test_check_not_encrypted() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut data = crate::test_utils::build_pbo(&[], &[("secret.sqf", b"data")]);
+        let plain = temp.path().join("plain.pbo");
+        std::fs::write(&plain, &data).unwrap();
+        assert!(DefaultExtractor::check_not_encrypted(&plain).is_ok());
+
+        let method_offset = 22 + "secret.sqf".len() + 1;
+        data[method_offset..method_offset + 4].copy_from_slice(&crate::core::constants::PACKING_METHOD_ENCRYPTED.to_le_bytes());
+        let encrypted = temp.path().join("encrypted.pbo");
+        std::fs::write(&encrypted, &data).unwrap();
+        assert!(matches!(DefaultExtractor::check_not_encrypted(&encrypted), Err(PboError::InvalidPbo(_))));
+
+        // Unreadable headers are left for extractpbo to report
+        assert!(DefaultExtractor::check_not_encrypted(&temp.path().join("missing.pbo")).is_ok());
+        assert!(DefaultExtractor::check_not_encrypted(&temp.path().join("missing.ebo")).is_err());
+    }
+
+    #[test]
+    fn test_transient_error_detection() {
+        let transient = io::Error::new(io::ErrorKind::ResourceBusy, "locked");
+        assert!(DefaultExtractor::is_transient_error(&transient));
+
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "network share");
+        assert!(DefaultExtractor::is_transient_error(&denied));
+
+        let missing = io::Error::new(io::ErrorKind::NotFound, "no extractpbo");
+        assert!(!DefaultExtractor::is_transient_error(&missing));
+    }
+
+    #[test]
+    fn test_missing_extractpbo_override() {
+        let extractor = DefaultExtractor::new()
+            .with_extractpbo_path(PathBuf::from("/nonexistent/tools/extractpbo"));
+        let pbo_path = crate::test_utils::get_test_pbo_path();
+
+        match extractor.list_contents(&pbo_path, false) {
+            Err(PboError::CommandNotFound(msg)) => assert!(msg.contains("/nonexistent/tools/extractpbo")),
+            other => panic!("Expected CommandNotFound error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_extractor_uses_config() {
+        let config = PboConfig::builder().max_retries(7).build();
+        let extractor = DefaultExtractor::with_config(Arc::new(config));
+        assert_eq!(extractor.config.max_retries(), 7);
+    }
 }
@@ -0,0 +1,88 @@
+use std::path::Path;
+use log::debug;
+use crate::error::types::{Result, PboError};
+use super::extractor::{ExtractorClone, DefaultExtractor, ExtractOptions};
+use super::native::NativeExtractor;
+use super::result::ExtractResult;
+
+/// Extractor that lists with [`NativePboReader`](crate::core::NativePboReader)
+/// and falls back to extractpbo for PBOs the native reader cannot parse.
+///
+/// Extraction always runs extractpbo through the wrapped [`DefaultExtractor`].
+#[derive(Debug, Clone, Default)]
+pub struct HybridExtractor {
+    native: NativeExtractor,
+    fallback: DefaultExtractor,
+}
+
+impl HybridExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `fallback` for extraction and for listings the native reader rejects
+    pub fn with_fallback(fallback: DefaultExtractor) -> Self {
+        Self {
+            native: NativeExtractor::with_fallback(fallback.clone()),
+            fallback,
+        }
+    }
+}
+
+impl ExtractorClone for HybridExtractor {
+    fn extract_with_options(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        debug!("HybridExtractor extracting {:?} with extractpbo", pbo_path);
+        self.fallback.extract_with_options(pbo_path, output_dir, options)
+    }
+
+    fn list_with_options(&self, pbo_path: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        match self.native.list_with_options(pbo_path, options.clone()) {
+            Err(PboError::InvalidPbo(reason)) => {
+                debug!("Native reader rejected {:?} ({}), listing with extractpbo", pbo_path, reason);
+                self.fallback.list_with_options(pbo_path, options)
+            }
+            result => {
+                debug!("Listed {:?} with the native reader", pbo_path);
+                result
+            }
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn ExtractorClone> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+    use crate::test_utils;
+
+    #[test]
+    fn test_hybrid_lists_natively() {
+        // No extractpbo is needed for a PBO the native reader understands
+        let fallback = DefaultExtractor::new().with_extractpbo_path("missing-extractpbo".into());
+        let extractor = HybridExtractor::with_fallback(fallback);
+
+        let result = extractor.list_contents(&test_utils::get_test_pbo_path(), true).unwrap();
+        assert!(result.get_file_list().contains(&"config.bin".to_string()));
+    }
+
+    #[test]
+    fn test_hybrid_falls_back_on_invalid_pbo() {
+        let temp = tempdir().unwrap();
+        let bad_pbo = temp.path().join("bad.pbo");
+        fs::write(&bad_pbo, b"invalid data").unwrap();
+
+        let fallback = DefaultExtractor::new().with_extractpbo_path(temp.path().join("missing-extractpbo"));
+        let extractor = HybridExtractor::with_fallback(fallback);
+
+        // Reaching for the missing binary shows the fallback was taken
+        assert!(matches!(
+            extractor.list_contents(&bad_pbo, false),
+            Err(PboError::CommandNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,316 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use log::{debug, trace, warn};
+use uuid::Uuid;
+use walkdir::WalkDir;
+use crate::error::types::{Result, PboError, FileSystemError};
+use crate::fs::{prefix_path, reprefix_directory};
+use super::extractor::PboHeader;
+use super::result::{ExtractResult, normalize_prefix};
+
+/// Directory layout of extracted files inside the output directory.
+///
+/// extractpbo has no switches for this, so every layout other than `AsIs`
+/// extracts into a staging folder and moves the files into place afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputLayout {
+    /// Keep whatever structure extractpbo produces
+    #[default]
+    AsIs,
+    /// Strip the PBO prefix so files land directly in the output directory
+    FlattenPrefix,
+    /// Place files in a folder named after the PBO, without the prefix
+    PboNameFolder,
+    /// Place files under folders built from the PBO prefix
+    PrefixFolders,
+}
+
+impl OutputLayout {
+    /// Staging directory extractpbo should write to, or `None` to extract in place
+    pub(crate) fn staging_dir(self, output_dir: &Path) -> Option<PathBuf> {
+        match self {
+            OutputLayout::AsIs => None,
+            _ => Some(output_dir.join(format!(".staging_{}", Uuid::new_v4()))),
+        }
+    }
+
+    /// Directory holding the PBO's entries once extraction has finished. Without
+    /// a layout, extractpbo itself places the entries under their prefix folders.
+    /// Fails for prefixes that would lead outside `output_dir`.
+    pub(crate) fn content_dir(self, output_dir: &Path, pbo_path: &Path, prefix: Option<&str>) -> Result<PathBuf> {
+        let prefix = prefix.map(prefix_path).transpose()?.filter(|p| !p.as_os_str().is_empty());
+        Ok(match (self, prefix) {
+            (OutputLayout::AsIs, Some(prefix)) => output_dir.join(prefix),
+            (layout, prefix) => layout.target_dir(output_dir, pbo_path, prefix.as_deref()),
+        })
+    }
+
+    /// Directory that receives the PBO contents for this layout
+    fn target_dir(self, output_dir: &Path, pbo_path: &Path, prefix: Option<&Path>) -> PathBuf {
+        match self {
+            OutputLayout::AsIs | OutputLayout::FlattenPrefix => output_dir.to_path_buf(),
+            OutputLayout::PboNameFolder => match pbo_path.file_stem() {
+                Some(stem) => output_dir.join(stem),
+                None => output_dir.to_path_buf(),
+            },
+            OutputLayout::PrefixFolders => match prefix {
+                Some(prefix) => output_dir.join(prefix),
+                None => output_dir.to_path_buf(),
+            },
+        }
+    }
+}
+
+/// Move the files extractpbo wrote into `staging` to their place in `output_dir`
+/// and remove the staging directory. The staging directory is removed even when
+/// the extraction itself failed.
+pub(crate) fn finish_staged_extraction(
+    layout: OutputLayout,
+    staging: &Path,
+    output_dir: &Path,
+    header: &PboHeader<'_>,
+    result: Result<ExtractResult>,
+) -> Result<ExtractResult> {
+    let relocated = match &result {
+        Ok(extract_result) if extract_result.is_success() => {
+            let prefix = match header.reader() {
+                Ok(reader) => reader.prefix(),
+                Err(e) => {
+                    warn!("Could not read PBO prefix for layout: {}", e);
+                    None
+                }
+            };
+            relocate(layout, staging, output_dir, header.path(), prefix.as_deref())
+        }
+        _ => Ok(()),
+    };
+
+    if staging.exists() {
+        if let Err(e) = fs::remove_dir_all(staging) {
+            warn!("Failed to remove staging directory {:?}: {}", staging, e);
+        }
+    }
+
+    relocated?;
+    result
+}
+
+fn relocate(
+    layout: OutputLayout,
+    staging: &Path,
+    output_dir: &Path,
+    pbo_path: &Path,
+    prefix: Option<&str>,
+) -> Result<()> {
+    let prefix = prefix.map(prefix_path).transpose()?.filter(|p| !p.as_os_str().is_empty());
+    let target = layout.target_dir(output_dir, pbo_path, prefix.as_deref());
+
+    // Files below the prefix folders are re-rooted; anything else (such as
+    // $PBOPREFIX$.txt) keeps its position relative to the staging root.
+    let content_root = prefix
+        .as_ref()
+        .map(|p| staging.join(p))
+        .filter(|p| p.is_dir())
+        .unwrap_or_else(|| staging.to_path_buf());
+    debug!("Applying {:?} layout from {:?} to {:?}", layout, content_root, target);
+
+    for entry in WalkDir::new(staging).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let source = entry.path();
+        let relative = source.strip_prefix(&content_root)
+            .or_else(|_| source.strip_prefix(staging))
+            .map_err(|_| PboError::InvalidPath(source.to_path_buf()))?;
+        let destination = target.join(relative);
+        trace!("Moving {:?} -> {:?}", source, destination);
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|e| FileSystemError::CreateDir {
+                path: parent.to_path_buf(),
+                reason: e.to_string(),
+            })?;
+        }
+        fs::rename(source, &destination).map_err(|e| FileSystemError::WriteFile {
+            path: destination.clone(),
+            reason: e.to_string(),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Move the files extracted below the PBO prefix in `output_dir` up into
+/// `output_dir` itself, or below `new_prefix` when one is given, and remove the
+/// prefix folders left empty. Does nothing when the PBO has no prefix.
+pub(crate) fn move_prefix_contents(output_dir: &Path, prefix: Option<&str>, new_prefix: Option<&str>) -> Result<()> {
+    let Some(prefix) = prefix.filter(|p| !normalize_prefix(p).is_empty()) else {
+        debug!("No prefix to move in {:?}", output_dir);
+        return Ok(());
+    };
+    reprefix_directory(output_dir, prefix, new_prefix.unwrap_or("")).map(|_| ())
+}
+
+/// Where `dir`, a folder below `output_dir`, ends up once `move_prefix_contents`
+/// moved the PBO prefix to `new_prefix`
+pub(crate) fn prefix_moved_dir(output_dir: &Path, dir: &Path, prefix: Option<&str>, new_prefix: Option<&str>) -> Result<PathBuf> {
+    let Some(prefix) = prefix.filter(|p| !normalize_prefix(p).is_empty()) else {
+        return Ok(dir.to_path_buf());
+    };
+    let source_root = output_dir.join(prefix_path(prefix)?);
+    Ok(match dir.strip_prefix(&source_root) {
+        Ok(rest) => output_dir.join(prefix_path(new_prefix.unwrap_or(""))?).join(rest),
+        Err(_) => dir.to_path_buf(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn staged_tree(output_dir: &Path) -> PathBuf {
+        let staging = output_dir.join(".staging_test");
+        fs::create_dir_all(staging.join("tc/mirrorform/uniform")).unwrap();
+        fs::write(staging.join("$PBOPREFIX$.txt"), "tc\\mirrorform").unwrap();
+        fs::write(staging.join("tc/mirrorform/config.cpp"), "class CfgPatches {};").unwrap();
+        fs::write(staging.join("tc/mirrorform/uniform/mirror.p3d"), "p3d").unwrap();
+        staging
+    }
+
+    #[test]
+    fn test_prefix_moved_dir() {
+        let output_dir = Path::new("out");
+        let prefix = Some("tc\\mirrorform");
+        let moved = |dir: &str, new_prefix| prefix_moved_dir(output_dir, &output_dir.join(dir), prefix, new_prefix).unwrap();
+        assert_eq!(moved("tc/mirrorform", None), output_dir);
+        assert_eq!(moved("tc/mirrorform/data", Some("x\\y")), output_dir.join("x/y/data"));
+        assert_eq!(moved("mirrorform", None), output_dir.join("mirrorform"));
+        assert_eq!(prefix_moved_dir(output_dir, output_dir, None, None).unwrap(), output_dir);
+    }
+
+    #[test]
+    fn test_flatten_prefix() {
+        let temp = tempdir().unwrap();
+        let staging = staged_tree(temp.path());
+        let pbo_path = Path::new("mirrorform.pbo");
+
+        relocate(OutputLayout::FlattenPrefix, &staging, temp.path(), pbo_path, Some("tc\\mirrorform")).unwrap();
+
+        assert!(temp.path().join("config.cpp").exists());
+        assert!(temp.path().join("uniform/mirror.p3d").exists());
+        assert!(temp.path().join("$PBOPREFIX$.txt").exists());
+        assert!(!temp.path().join("tc").exists());
+    }
+
+    #[test]
+    fn test_pbo_name_folder() {
+        let temp = tempdir().unwrap();
+        let staging = staged_tree(temp.path());
+        let pbo_path = Path::new("tests/data/mirrorform.pbo");
+
+        relocate(OutputLayout::PboNameFolder, &staging, temp.path(), pbo_path, Some("tc\\mirrorform")).unwrap();
+
+        assert!(temp.path().join("mirrorform/config.cpp").exists());
+        assert!(temp.path().join("mirrorform/uniform/mirror.p3d").exists());
+    }
+
+    #[test]
+    fn test_prefix_folders() {
+        let temp = tempdir().unwrap();
+        let staging = temp.path().join(".staging_test");
+        fs::create_dir_all(staging.join("uniform")).unwrap();
+        fs::write(staging.join("config.cpp"), "").unwrap();
+        fs::write(staging.join("uniform/mirror.p3d"), "").unwrap();
+
+        relocate(OutputLayout::PrefixFolders, &staging, temp.path(), Path::new("mirrorform.pbo"), Some("tc\\mirrorform")).unwrap();
+
+        assert!(temp.path().join("tc/mirrorform/config.cpp").exists());
+        assert!(temp.path().join("tc/mirrorform/uniform/mirror.p3d").exists());
+    }
+
+    #[test]
+    fn test_failed_extraction_removes_staging() {
+        let temp = tempdir().unwrap();
+        let staging = staged_tree(temp.path());
+        let failed = ExtractResult {
+            return_code: 1,
+            stdout: String::new(),
+            stderr: "error".to_string(),
+            ..Default::default()
+        };
+
+        let result = finish_staged_extraction(
+            OutputLayout::FlattenPrefix, &staging, temp.path(), &PboHeader::new(Path::new("mirrorform.pbo")), Ok(failed),
+        ).unwrap();
+
+        assert!(!result.is_success());
+        assert!(!staging.exists());
+        assert!(!temp.path().join("config.cpp").exists());
+    }
+
+    #[test]
+    fn test_strip_prefix() {
+        let temp = tempdir().unwrap();
+        let output = temp.path();
+        fs::create_dir_all(output.join("tc/mirrorform/uniform")).unwrap();
+        fs::write(output.join("tc/mirrorform/config.cpp"), "").unwrap();
+        fs::write(output.join("tc/mirrorform/uniform/mirror.p3d"), "").unwrap();
+
+        move_prefix_contents(output, Some("tc\\mirrorform"), None).unwrap();
+
+        assert!(output.join("config.cpp").exists());
+        assert!(output.join("uniform/mirror.p3d").exists());
+        assert!(!output.join("tc").exists(), "Empty prefix folders should be removed");
+    }
+
+    #[test]
+    fn test_rewrite_prefix() {
+        let temp = tempdir().unwrap();
+        let output = temp.path();
+        fs::create_dir_all(output.join("tc/mirrorform/uniform")).unwrap();
+        fs::create_dir_all(output.join("tc/other")).unwrap();
+        fs::write(output.join("tc/mirrorform/uniform/mirror.p3d"), "").unwrap();
+        fs::write(output.join("tc/other/keep.txt"), "").unwrap();
+
+        move_prefix_contents(output, Some("tc\\mirrorform"), Some("my_mod\\addons")).unwrap();
+
+        assert!(output.join("my_mod/addons/uniform/mirror.p3d").exists());
+        assert!(!output.join("tc/mirrorform").exists());
+        assert!(output.join("tc/other/keep.txt").exists(), "Unrelated folders must be kept");
+
+        // Without a prefix there is nothing to move
+        move_prefix_contents(output, None, None).unwrap();
+        assert!(output.join("my_mod/addons/uniform/mirror.p3d").exists());
+    }
+
+    #[test]
+    fn test_prefix_outside_output_rejected() {
+        let temp = tempdir().unwrap();
+        let output = temp.path().join("out");
+        fs::create_dir_all(temp.path().join("home/x")).unwrap();
+        fs::write(temp.path().join("home/x/keep.txt"), "").unwrap();
+        fs::create_dir_all(&output).unwrap();
+
+        for prefix in ["..\\home\\x", "tc\\..\\..\\home\\x"] {
+            assert!(move_prefix_contents(&output, Some(prefix), None).is_err());
+        }
+        assert!(move_prefix_contents(&output, Some("tc\\mirrorform"), Some("..\\escaped")).is_err());
+        assert!(temp.path().join("home/x/keep.txt").exists(), "Files outside the output must not move");
+
+        let staging = output.join(".staging_test");
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("config.cpp"), "").unwrap();
+        let relocated = relocate(OutputLayout::PrefixFolders, &staging, &output, Path::new("a.pbo"), Some("..\\..\\x"));
+        assert!(relocated.is_err());
+        assert!(OutputLayout::AsIs.content_dir(&output, Path::new("a.pbo"), Some("..\\x")).is_err());
+    }
+
+    #[test]
+    fn test_staging_dir_only_for_relocating_layouts() {
+        let output = Path::new("out");
+        assert!(OutputLayout::AsIs.staging_dir(output).is_none());
+        assert!(OutputLayout::FlattenPrefix.staging_dir(output).unwrap().starts_with(output));
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+use log::debug;
+use crate::error::types::{Result, PboError};
+use crate::fs::FileOperation;
+use crate::fs_err;
+
+/// Record of what an extraction wrote, saved as JSON when
+/// `ExtractOptions::write_manifest` is set
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtractManifest {
+    /// PBO the files were extracted from
+    pub pbo: PathBuf,
+    pub prefix: Option<String>,
+    pub files: Vec<ManifestEntry>,
+}
+
+/// A file listed in an [`ExtractManifest`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ManifestEntry {
+    /// Path of the written file, relative to the output directory
+    pub path: String,
+    /// Size of the file on disk
+    pub size: u64,
+}
+
+impl ExtractManifest {
+    /// Write the manifest to `path` as pretty-printed JSON, replacing any
+    /// previous file. Requires the `serde` feature.
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        #[cfg(feature = "serde")]
+        {
+            debug!("Writing manifest of {} files to {:?}", self.files.len(), path);
+            let json = serde_json::to_string_pretty(self)
+                .map_err(|e| PboError::ValidationFailed(format!("Failed to serialize manifest: {}", e)))?;
+            path.ensure_parent_exists()?;
+            fs_err!(std::fs::write(path, json) => WriteFile, path)
+        }
+        #[cfg(not(feature = "serde"))]
+        {
+            Err(PboError::ValidationFailed(format!(
+                "Cannot write manifest {}: requires the serde feature", path.display()
+            )))
+        }
+    }
+
+    /// Replace the PBO recorded in the manifest written to `path`, e.g. with the
+    /// compressed file the caller passed when a decompressed copy was extracted
+    pub(crate) fn set_source(path: &Path, pbo: &Path) -> Result<()> {
+        #[cfg(feature = "serde")]
+        {
+            let json = fs_err!(std::fs::read_to_string(path) => ReadFile, path)?;
+            let mut manifest: serde_json::Value = serde_json::from_str(&json)
+                .map_err(|e| PboError::ValidationFailed(format!("Failed to parse manifest {}: {}", path.display(), e)))?;
+            manifest["pbo"] = serde_json::json!(pbo);
+            let json = serde_json::to_string_pretty(&manifest)
+                .map_err(|e| PboError::ValidationFailed(format!("Failed to serialize manifest: {}", e)))?;
+            fs_err!(std::fs::write(path, json) => WriteFile, path)
+        }
+        #[cfg(not(feature = "serde"))]
+        {
+            // Without serde no manifest is ever written
+            let _ = (path, pbo);
+            Ok(())
+        }
+    }
+}
@@ -1,5 +1,17 @@
 mod extractor;
+mod hybrid;
+mod layout;
+mod manifest;
+mod native;
+mod nested;
+pub(crate) mod process;
 mod result;
 
-pub use extractor::{ExtractorClone, DefaultExtractor, ExtractOptions};
-pub use result::ExtractResult;
+pub use extractor::{ExtractorClone, DefaultExtractor, ExtractOptions, FilterSyntax, OverwritePolicy, ValidationIssue};
+pub use hybrid::HybridExtractor;
+pub use layout::OutputLayout;
+pub use manifest::{ExtractManifest, ManifestEntry};
+pub use native::NativeExtractor;
+pub use result::{ExtractOutcome, ExtractResult, ExtractSummary, OperationMetrics, normalize_prefix};
+pub(crate) use result::parse_prefix;
+pub(crate) use extractor::scaled_timeout;
@@ -0,0 +1,137 @@
+use std::path::Path;
+use log::{debug, trace};
+use crate::error::types::Result;
+use crate::core::reader::{NativePboReader, PboEntry};
+use super::extractor::{ExtractorClone, DefaultExtractor, ExtractOptions};
+use super::result::{ExtractResult, HeaderListing};
+
+/// Extractor that answers listings from the PBO header using [`NativePboReader`].
+///
+/// Listing never spawns a subprocess. Extraction is still delegated to
+/// extractpbo through the wrapped [`DefaultExtractor`].
+#[derive(Debug, Clone, Default)]
+pub struct NativeExtractor {
+    fallback: DefaultExtractor,
+}
+
+impl NativeExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `fallback` for operations the native reader cannot perform
+    pub fn with_fallback(fallback: DefaultExtractor) -> Self {
+        Self { fallback }
+    }
+
+    /// Listing of the header entries, without size and timestamp for a brief
+    /// listing just as extractpbo prints them
+    fn header_listing(reader: &NativePboReader, options: &ExtractOptions) -> HeaderListing {
+        let entries = reader.entries()
+            .into_iter()
+            .map(|entry| if options.brief_listing {
+                PboEntry { size: None, timestamp: None, ..entry }
+            } else {
+                entry
+            })
+            .collect();
+        HeaderListing { prefix: reader.prefix(), entries }
+    }
+
+    /// Render a listing in the line format extractpbo uses, for callers that
+    /// display `stdout`. The result is built from the header, not this text.
+    fn render_listing(reader: &NativePboReader, options: &ExtractOptions) -> String {
+        let mut stdout = String::new();
+
+        if let Some(prefix) = reader.prefix() {
+            stdout.push_str(&format!("prefix={};\n", prefix));
+        }
+
+        for entry in reader.header_entries() {
+            if options.brief_listing {
+                stdout.push_str(&format!("{}\n", entry.path));
+            } else {
+                stdout.push_str(&format!("{}:{}: {} bytes\n", entry.path, entry.timestamp, entry.uncompressed_size()));
+            }
+        }
+
+        stdout
+    }
+}
+
+impl ExtractorClone for NativeExtractor {
+    fn extract_with_options(&self, pbo_path: &Path, output_dir: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        debug!("NativeExtractor delegating extraction to extractpbo");
+        self.fallback.extract_with_options(pbo_path, output_dir, options)
+    }
+
+    fn list_with_options(&self, pbo_path: &Path, options: ExtractOptions) -> Result<ExtractResult> {
+        trace!("NativeExtractor::list_with_options called");
+        trace!("PBO path: {:?}", pbo_path);
+        trace!("Options: {:?}", options);
+
+        options.validate()?;
+
+        let reader = NativePboReader::open(pbo_path)?;
+        Ok(ExtractResult {
+            return_code: 0,
+            stdout: Self::render_listing(&reader, &options),
+            stderr: String::new(),
+            config: self.fallback.config().clone(),
+            listing: Some(Self::header_listing(&reader, &options)),
+            ..Default::default()
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn ExtractorClone> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    #[test]
+    fn test_native_listing() {
+        let extractor = NativeExtractor::new();
+        let result = extractor.list_contents(&test_utils::get_test_pbo_path(), false).unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(result.get_prefix(), Some("tc\\mirrorform".to_string()));
+        let files = result.get_file_list();
+        assert!(files.contains(&"config.bin".to_string()));
+        assert!(files.contains(&"uniform/mirror.p3d".to_string()));
+    }
+
+    #[test]
+    fn test_native_brief_listing_matches_detailed() {
+        let extractor = NativeExtractor::new();
+        let pbo_path = test_utils::get_test_pbo_path();
+        let brief = extractor.list_contents(&pbo_path, true).unwrap();
+        let detailed = extractor.list_contents(&pbo_path, false).unwrap();
+
+        assert_eq!(brief.get_file_list(), detailed.get_file_list());
+    }
+
+    #[test]
+    fn test_listing_from_header() {
+        let pbo = test_utils::build_pbo(
+            &[("prefix", "tc\\test")],
+            &[("ErrorHandler.sqf", b"x"), ("Opening.sqf", b"y"), ("scripts\\Cannot open.sqf", b"z")],
+        );
+        let temp = tempfile::tempdir().unwrap();
+        let pbo_path = temp.path().join("test.pbo");
+        std::fs::write(&pbo_path, pbo).unwrap();
+
+        let result = NativeExtractor::new().list_contents(&pbo_path, false).unwrap();
+        assert!(result.is_success());
+        assert!(result.get_warnings().is_empty());
+        assert_eq!(result.get_prefix(), Some("tc\\test".to_string()));
+        assert_eq!(result.get_file_list(), vec!["ErrorHandler.sqf", "Opening.sqf", "scripts/Cannot open.sqf"]);
+        let entries = result.get_entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].size, Some(1));
+    }
+}
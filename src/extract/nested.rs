@@ -0,0 +1,226 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use log::{debug, warn};
+use sha1::{Digest, Sha1};
+use crate::error::types::Result;
+use crate::core::extensions::is_pbo_path;
+use crate::core::reader::{entry_relative_path, HeaderEntry, NativePboReader};
+use crate::fs_err;
+use super::extractor::ExtractOptions;
+use super::layout::OutputLayout;
+
+/// A PBO found among extracted files, waiting to be extracted itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NestedPbo {
+    pub path: PathBuf,
+    /// Sibling folder named after the PBO that receives its contents, e.g.
+    /// `data/inner` for `data/inner.pbo`, or `data/inner_xbo` when another
+    /// nested PBO already claimed `data/inner`
+    pub target: PathBuf,
+    /// Nesting level, 1 for PBOs stored directly in the extracted PBO
+    pub depth: usize,
+}
+
+/// Worklist of PBOs stored inside extracted PBOs, followed breadth first down
+/// to `max_depth` levels. PBOs whose contents were already seen are skipped,
+/// so a PBO that contains a copy of one of its ancestors cannot loop.
+#[derive(Debug)]
+pub(crate) struct NestedPbos {
+    pending: VecDeque<NestedPbo>,
+    seen: HashSet<Vec<u8>>,
+    /// Target folders already handed out
+    targets: HashSet<PathBuf>,
+    max_depth: usize,
+}
+
+impl NestedPbos {
+    /// Start from `pbo_path` with header `entries`, which were extracted into
+    /// `content_dir`
+    pub(crate) fn new(pbo_path: &Path, entries: &[HeaderEntry], content_dir: &Path, max_depth: usize) -> Result<Self> {
+        let mut nested = Self {
+            pending: VecDeque::new(),
+            seen: HashSet::new(),
+            targets: HashSet::new(),
+            max_depth,
+        };
+        nested.seen.insert(content_digest(pbo_path)?);
+        nested.queue_entries(entries, content_dir, 1);
+        Ok(nested)
+    }
+
+    /// Queue the PBOs found after extracting `pbo` into its target folder
+    pub(crate) fn extracted(&mut self, pbo: &NestedPbo) -> Result<()> {
+        self.queue_from(&pbo.path, &pbo.target, pbo.depth + 1)
+    }
+
+    /// Next PBO to extract, skipping those with contents already extracted
+    pub(crate) fn next_pbo(&mut self) -> Result<Option<NestedPbo>> {
+        while let Some(pbo) = self.pending.pop_front() {
+            if self.seen.insert(content_digest(&pbo.path)?) {
+                return Ok(Some(pbo));
+            }
+            warn!("Skipping nested PBO {:?}: its contents were already extracted", pbo.path);
+        }
+        Ok(None)
+    }
+
+    /// Queue the PBO entries of `pbo_path` that exist in `content_dir`
+    fn queue_from(&mut self, pbo_path: &Path, content_dir: &Path, depth: usize) -> Result<()> {
+        if depth > self.max_depth {
+            return Ok(());
+        }
+        let reader = NativePboReader::open(pbo_path)?;
+        self.queue_entries(reader.header_entries(), content_dir, depth);
+        Ok(())
+    }
+
+    /// Queue the PBOs among `entries` that exist in `content_dir`
+    fn queue_entries(&mut self, entries: &[HeaderEntry], content_dir: &Path, depth: usize) {
+        if depth > self.max_depth {
+            return;
+        }
+
+        for entry in entries {
+            let Some(relative) = entry_relative_path(&entry.path) else {
+                warn!("Skipping nested entry {:?}: it is not a path inside {:?}", entry.path, content_dir);
+                continue;
+            };
+            let path = content_dir.join(relative);
+            if is_pbo_path(&path) && path.is_file() {
+                debug!("Found nested PBO {:?} at depth {}", path, depth);
+                let target = self.claim_target(&path);
+                self.pending.push_back(NestedPbo { path, target, depth });
+            }
+        }
+    }
+
+    /// Folder `path` is extracted to: the path without its extension, or with
+    /// the extension appended when `a.pbo` and `a.xbo` would share that folder
+    fn claim_target(&mut self, path: &Path) -> PathBuf {
+        let mut target = path.with_extension("");
+        if self.targets.contains(&target) {
+            let mut name = target.file_name().unwrap_or_default().to_os_string();
+            name.push("_");
+            name.push(path.extension().unwrap_or_default());
+            target.set_file_name(name);
+        }
+        self.targets.insert(target.clone());
+        target
+    }
+}
+
+/// Options a nested PBO is extracted with: the parent's extraction switches,
+/// with the contents placed directly in the target folder
+pub(crate) fn nested_options(options: &ExtractOptions) -> ExtractOptions {
+    ExtractOptions {
+        no_pause: options.no_pause,
+        warnings_as_errors: options.warnings_as_errors,
+        verbose: options.verbose,
+        decompress_only: options.decompress_only,
+        overwrite: options.overwrite,
+        preserve_timestamps: options.preserve_timestamps,
+        timeout: options.timeout,
+        layout: OutputLayout::FlattenPrefix,
+        ..Default::default()
+    }
+}
+
+fn content_digest(path: &Path) -> Result<Vec<u8>> {
+    let data = fs_err!(fs::read(path) => ReadFile, path)?;
+    Ok(Sha1::digest(&data).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use crate::test_utils::build_pbo;
+
+    /// Write `files` below `dir` as extractpbo would
+    fn write_extracted(dir: &Path, files: &[(&str, &[u8])]) {
+        for (name, content) in files {
+            let path = dir.join(name.replace('\\', "/"));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_nested_worklist() {
+        let temp = TempDir::new().unwrap();
+        let deepest = build_pbo(&[], &[("readme.txt", b"deepest")]);
+        let deep = build_pbo(&[], &[("deepest.pbo", &deepest)]);
+        let inner = build_pbo(&[], &[("deep.pbo", &deep)]);
+        let outer_files: [(&str, &[u8]); 3] = [
+            ("data\\inner.pbo", &inner),
+            ("data\\copy.pbo", &inner),
+            ("config.cpp", b"class CfgPatches {};"),
+        ];
+        let outer = temp.path().join("outer.pbo");
+        fs::write(&outer, build_pbo(&[], &outer_files)).unwrap();
+
+        let out = temp.path().join("out");
+        write_extracted(&out, &outer_files);
+
+        let mut nested = NestedPbos::new(&outer, NativePboReader::open(&outer).unwrap().header_entries(), &out, 2).unwrap();
+        let first = nested.next_pbo().unwrap().unwrap();
+        assert_eq!(first.path, out.join("data/inner.pbo"));
+        assert_eq!(first.target, out.join("data/inner"));
+        assert_eq!(first.depth, 1);
+
+        write_extracted(&first.target, &[("deep.pbo", &deep)]);
+        nested.extracted(&first).unwrap();
+
+        // copy.pbo has the same contents as inner.pbo
+        let second = nested.next_pbo().unwrap().unwrap();
+        assert_eq!(second.path, out.join("data/inner/deep.pbo"));
+        assert_eq!(second.depth, 2);
+
+        // Depth 3 is beyond the limit
+        write_extracted(&second.target, &[("deepest.pbo", &deepest)]);
+        nested.extracted(&second).unwrap();
+        assert_eq!(nested.next_pbo().unwrap(), None);
+    }
+
+    #[test]
+    fn test_nested_paths_stay_inside() {
+        let temp = TempDir::new().unwrap();
+        let inner = build_pbo(&[], &[("readme.txt", b"inner")]);
+        let outer_files: [(&str, &[u8]); 3] = [
+            ("..\\escape.pbo", &inner),
+            ("data\\a.pbo", &inner),
+            ("data\\a.xbo", b"not the same PBO"),
+        ];
+        let outer = temp.path().join("outer.pbo");
+        fs::write(&outer, build_pbo(&[], &outer_files)).unwrap();
+
+        // `..\escape.pbo` would resolve to a PBO next to the output directory
+        let out = temp.path().join("out");
+        fs::write(temp.path().join("escape.pbo"), build_pbo(&[], &[("other.txt", b"x")])).unwrap();
+        write_extracted(&out, &outer_files[1..]);
+
+        let mut nested = NestedPbos::new(&outer, NativePboReader::open(&outer).unwrap().header_entries(), &out, 1).unwrap();
+        let first = nested.next_pbo().unwrap().unwrap();
+        assert_eq!(first.target, out.join("data/a"));
+        let second = nested.next_pbo().unwrap().unwrap();
+        assert_eq!(second.path, out.join("data/a.xbo"));
+        assert_eq!(second.target, out.join("data/a_xbo"));
+        assert_eq!(nested.next_pbo().unwrap(), None);
+    }
+
+    #[test]
+    fn test_nested_options() {
+        let options = ExtractOptions {
+            file_filter: Some("*.pbo".to_string()),
+            recurse_nested: true,
+            preserve_timestamps: true,
+            ..ExtractOptions::for_extraction()
+        };
+        let nested = nested_options(&options);
+        assert!(nested.no_pause && nested.warnings_as_errors && nested.preserve_timestamps);
+        assert_eq!(nested.file_filter, None);
+        assert!(!nested.recurse_nested);
+        assert_eq!(nested.layout, OutputLayout::FlattenPrefix);
+    }
+}
@@ -0,0 +1,254 @@
+use std::borrow::Cow;
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{Child, ExitStatus};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use log::{trace, warn};
+
+/// Interval at which a running child is polled for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How long output is still read after the child exited, for pipes a
+/// grandchild keeps open
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Captured output of a child process that ran to completion
+#[derive(Debug)]
+pub(crate) struct ProcessOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+    /// Some output was not valid UTF-8 and was decoded as Windows-1252
+    pub redecoded: bool,
+}
+
+#[derive(Debug)]
+pub(crate) enum ProcessOutcome {
+    Exited(ProcessOutput),
+    /// The child ran past its timeout; it has been killed and reaped
+    TimedOut,
+}
+
+/// Decode process output as UTF-8, falling back to Windows-1252 when it isn't
+/// valid UTF-8 (extractpbo prints in the console codepage). The flag is set
+/// when the fallback was used.
+pub(crate) fn decode_output(bytes: &[u8]) -> (Cow<'_, str>, bool) {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (Cow::Borrowed(text), false),
+        Err(_) => {
+            trace!("Output is not valid UTF-8, decoding as Windows-1252");
+            let (text, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(bytes);
+            (text, true)
+        }
+    }
+}
+
+/// Wait for a child spawned with piped stdout/stderr, passing each stdout line
+/// to `on_line` as it arrives.
+///
+/// The pipes are read on their own threads, so a grandchild that inherited
+/// them (such as `wineserver`) cannot keep this waiting past the child's exit
+/// or the timeout; output still arriving after `OUTPUT_DRAIN_TIMEOUT` is
+/// dropped. When `timeout` elapses the child is killed, and this only returns
+/// once the process has exited, so it no longer holds handles to the PBO or
+/// output dir.
+pub(crate) fn wait_with_timeout(
+    mut child: Child,
+    timeout: Option<Duration>,
+    on_line: &mut dyn FnMut(&str),
+) -> io::Result<ProcessOutcome> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let stdout_lines = child.stdout.take().map(|pipe| read_in_background(pipe, true));
+    let stderr_chunks = child.stderr.take().map(|pipe| read_in_background(pipe, false));
+
+    let mut stdout = String::new();
+    let mut redecoded = false;
+    let mut take_line = |bytes: Vec<u8>| {
+        let (line, line_redecoded) = decode_output(&bytes);
+        redecoded |= line_redecoded;
+        let line = line.trim_end_matches(['\r', '\n']);
+        on_line(line);
+        stdout.push_str(line);
+        stdout.push('\n');
+    };
+
+    let status = loop {
+        match stdout_lines.as_ref().map(|lines| lines.recv_timeout(POLL_INTERVAL)) {
+            Some(Ok(bytes)) => take_line(bytes),
+            Some(Err(mpsc::RecvTimeoutError::Timeout)) => {}
+            // Stdout is closed or was not piped
+            _ => thread::sleep(POLL_INTERVAL),
+        }
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if let (Some(timeout), Some(deadline)) = (timeout, deadline) {
+            if Instant::now() < deadline {
+                continue;
+            }
+            warn!("Process timed out after {} seconds, killing it", timeout.as_secs());
+            if let Err(e) = child.kill() {
+                warn!("Failed to kill timed out process: {}", e);
+            }
+            child.wait()?;
+            return Ok(ProcessOutcome::TimedOut);
+        }
+    };
+    trace!("Process exited with status: {:?}", status);
+
+    let drain_until = Instant::now() + OUTPUT_DRAIN_TIMEOUT;
+    if let Some(lines) = &stdout_lines {
+        drain(lines, drain_until, &mut take_line);
+    }
+    let mut stderr_bytes = Vec::new();
+    if let Some(chunks) = &stderr_chunks {
+        drain(chunks, drain_until, &mut |chunk| stderr_bytes.extend(chunk));
+    }
+    let (stderr, stderr_redecoded) = decode_output(&stderr_bytes);
+
+    Ok(ProcessOutcome::Exited(ProcessOutput {
+        status,
+        stdout,
+        stderr: stderr.into_owned(),
+        redecoded: redecoded || stderr_redecoded,
+    }))
+}
+
+/// Read `pipe` on a new thread, sending each line (or each chunk read when
+/// `by_line` is false) until end of file
+fn read_in_background(pipe: impl Read + Send + 'static, by_line: bool) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut chunk = [0; 8192];
+        loop {
+            let mut buf = Vec::new();
+            let read = if by_line {
+                reader.read_until(b'\n', &mut buf)
+            } else {
+                reader.read(&mut chunk).inspect(|&len| buf.extend_from_slice(&chunk[..len]))
+            };
+            match read {
+                Ok(0) => break,
+                Ok(_) => {
+                    if tx.send(buf).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to read process output: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+    rx
+}
+
+/// Pass what `pipe` still sends to `take` until it is closed or `until` passes
+fn drain(pipe: &mpsc::Receiver<Vec<u8>>, until: Instant, take: &mut dyn FnMut(Vec<u8>)) {
+    loop {
+        match pipe.recv_timeout(until.saturating_duration_since(Instant::now())) {
+            Ok(bytes) => take(bytes),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                warn!("Process output is still open after it exited, ignoring the rest");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    fn spawn_sh(script: &str) -> Child {
+        Command::new("sh")
+            .args(["-c", script])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_collects_output() {
+        let mut lines = Vec::new();
+        let outcome = wait_with_timeout(
+            spawn_sh("echo one; echo two; echo oops >&2"),
+            Some(Duration::from_secs(10)),
+            &mut |line| lines.push(line.to_string()),
+        ).unwrap();
+
+        match outcome {
+            ProcessOutcome::Exited(output) => {
+                assert!(output.status.success());
+                assert_eq!(output.stdout, "one\ntwo\n");
+                assert_eq!(output.stderr.trim(), "oops");
+                assert!(!output.redecoded);
+            }
+            other => panic!("Expected process to exit, got {:?}", other),
+        }
+        assert_eq!(lines, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn test_decodes_codepage_output() {
+        let mut lines = Vec::new();
+        let outcome = wait_with_timeout(
+            spawn_sh("printf 'Extracting caf\\351.paa\\n'"),
+            Some(Duration::from_secs(10)),
+            &mut |line| lines.push(line.to_string()),
+        ).unwrap();
+
+        match outcome {
+            ProcessOutcome::Exited(output) => {
+                assert_eq!(output.stdout, "Extracting café.paa\n");
+                assert!(output.redecoded);
+            }
+            other => panic!("Expected process to exit, got {:?}", other),
+        }
+        assert_eq!(lines, vec!["Extracting café.paa"]);
+    }
+
+    #[test]
+    fn test_kills_on_timeout() {
+        let start = Instant::now();
+        let outcome = wait_with_timeout(
+            spawn_sh("exec sleep 10"),
+            Some(Duration::from_millis(200)),
+            &mut |_| {},
+        ).unwrap();
+
+        assert!(matches!(outcome, ProcessOutcome::TimedOut));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_returns_while_grandchild_holds_output() {
+        let start = Instant::now();
+        let outcome = wait_with_timeout(
+            spawn_sh("sleep 10 & echo started"),
+            Some(Duration::from_secs(30)),
+            &mut |_| {},
+        ).unwrap();
+
+        match outcome {
+            ProcessOutcome::Exited(output) => assert_eq!(output.stdout, "started\n"),
+            other => panic!("Expected process to exit, got {:?}", other),
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let start = Instant::now();
+        let outcome = wait_with_timeout(
+            spawn_sh("sleep 10 & exec sleep 10"),
+            Some(Duration::from_millis(200)),
+            &mut |_| {},
+        ).unwrap();
+        assert!(matches!(outcome, ProcessOutcome::TimedOut));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
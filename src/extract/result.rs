@@ -1,15 +1,72 @@
 use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 use log::{debug, trace, warn};
 use crate::error::types::{Result, PboError, ExtractError};
+use crate::core::config::PboConfig;
+use crate::core::constants::{CHECKSUM_FAILURE_INDICATORS, RESIDUAL_BYTES_INDICATOR};
+use crate::core::reader::PboEntry;
+use super::extractor::glob_filter_regex;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ExtractResult {
     pub return_code: i32,
     pub stdout: String,
     pub stderr: String,
+    /// Requested files that exist in the PBO (set by `extract_file_list`)
+    pub(crate) found_files: Vec<String>,
+    /// Requested files that do not exist in the PBO (set by `extract_file_list`)
+    pub(crate) missing_files: Vec<String>,
+    /// Configuration deciding which output lines are warnings and which are errors
+    pub(crate) config: Arc<PboConfig>,
+    /// extractpbo printed output that was not valid UTF-8, so it was decoded
+    /// as Windows-1252 instead
+    pub output_redecoded: bool,
+    /// Extraction ran with `ExtractOptions::skip_checksum`, so checksum
+    /// failures are not treated as errors
+    pub(crate) checksum_skipped: bool,
+    /// Warnings count as errors although extractpbo ran without -W, which
+    /// `skip_checksum` leaves out
+    pub(crate) warnings_as_errors: bool,
+    /// Timing and size of the extractpbo run, `None` when it did not run
+    pub metrics: Option<OperationMetrics>,
+    /// Entries read from the PBO header instead of parsed from extractpbo
+    /// output, set by `NativeExtractor`
+    pub(crate) listing: Option<HeaderListing>,
+}
+
+/// Listing taken straight from the PBO header. `stdout` of such a result only
+/// renders it for display and is never parsed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HeaderListing {
+    pub prefix: Option<String>,
+    pub entries: Vec<PboEntry>,
 }
 
 impl ExtractResult {
+    /// Configuration the output was classified with
+    pub fn config(&self) -> &PboConfig {
+        &self.config
+    }
+
+    /// Paths requested from `extract_file_list` that exist in the PBO, empty
+    /// for other operations
+    pub fn found_files(&self) -> &[String] {
+        &self.found_files
+    }
+
+    /// Paths requested from `extract_file_list` that do not exist in the PBO,
+    /// empty for other operations
+    pub fn missing_files(&self) -> &[String] {
+        &self.missing_files
+    }
+
+    /// Whether the extraction ran with `ExtractOptions::skip_checksum`, so
+    /// checksum failures were not treated as errors
+    pub fn checksum_skipped(&self) -> bool {
+        self.checksum_skipped
+    }
+
     pub fn is_success(&self) -> bool {
         let return_code_ok = self.return_code == 0;
         let no_errors = !self.has_error_indicators();
@@ -17,38 +74,60 @@ impl ExtractResult {
         return_code_ok && no_errors
     }
 
-    fn has_error_indicators(&self) -> bool {
-        let known_warnings = [
-            "1st/last entry has non-zero real_size",
-            "reserved field non zero",
-            "no shakey on arma",
-            "arma pbo is missing a prefix",
-        ];
-
-        let error_indicators = [
-            "Error",
-            "Failed",
-            "Cannot open",
-            "Bad Sha",
-            "unknown header type",
-            "this warning is set as an error",
-            "residual bytes in file",
-        ];
+    /// Lines of stdout/stderr that match one of the configured warnings
+    /// (`KNOWN_WARNINGS` by default). Warnings do not affect `is_success`.
+    pub fn get_warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = Vec::new();
 
-        let mut is_error = false;
-        
-        // Check for warnings first
-        for warning in &known_warnings {
-            if self.stderr.contains(warning) || self.stdout.contains(warning) {
-                debug!("Found known warning: {}", warning);
-                // These are just warnings, don't fail the operation
+        for line in self.output_lines() {
+            let line = line.trim();
+            if self.config.is_warning(line) && !warnings.iter().any(|w| w == line) {
+                debug!("Found known warning: {}", line);
+                warnings.push(line.to_string());
             }
         }
 
-        // Then check for actual errors
-        for indicator in &error_indicators {
-            if self.stderr.contains(indicator) || self.stdout.contains(indicator) {
-                warn!("Found error indicator: {}", indicator);
+        warnings
+    }
+
+    /// Classify the result from its return code and output. The first error line
+    /// naming a known failure decides the variant; a nonzero return code or any
+    /// other error line gives `Unknown`. Agrees with [`ExtractResult::is_success`].
+    pub fn classify(&self) -> ExtractOutcome {
+        let error_line = self.output_lines().find(|line| self.is_error_line(line));
+
+        if let Some(line) = error_line {
+            let outcome = if line.contains("Bad Sha") {
+                ExtractOutcome::ChecksumFailed
+            } else if line.contains("unknown header type") {
+                ExtractOutcome::BadHeader
+            } else if line.contains("this warning is set as an error") || self.config.is_warning(line) {
+                ExtractOutcome::WarningAsError
+            } else if line.contains("Cannot open") {
+                ExtractOutcome::FileNotFound
+            } else {
+                return ExtractOutcome::Unknown(self.return_code);
+            };
+            debug!("Classified result as {:?} from line: {}", outcome, line.trim());
+            return outcome;
+        }
+
+        if self.return_code != 0 {
+            ExtractOutcome::Unknown(self.return_code)
+        } else if !self.get_warnings().is_empty() {
+            ExtractOutcome::WarningsPresent
+        } else {
+            ExtractOutcome::Success
+        }
+    }
+
+    fn has_error_indicators(&self) -> bool {
+        let mut is_error = false;
+
+        // Lines carrying a known warning are just warnings and don't fail the operation
+        for line in self.output_lines() {
+            if self.is_error_line(line) {
+                warn!("Found error indicator: {}", line.trim());
                 is_error = true;
                 break;
             }
@@ -59,8 +138,81 @@ impl ExtractResult {
         is_error
     }
 
+    /// Combine the result of an extraction that was split over several
+    /// extractpbo runs with the result of the next run. The output is
+    /// concatenated, and the first nonzero return code is kept.
+    pub(crate) fn merge(mut self, next: ExtractResult) -> ExtractResult {
+        if self.return_code == 0 {
+            self.return_code = next.return_code;
+        }
+        for (output, more) in [(&mut self.stdout, next.stdout), (&mut self.stderr, next.stderr)] {
+            if !output.is_empty() && !output.ends_with('\n') && !more.is_empty() {
+                output.push('\n');
+            }
+            output.push_str(&more);
+        }
+        self.output_redecoded |= next.output_redecoded;
+        if let (Some(metrics), Some(more)) = (self.metrics.as_mut(), next.metrics) {
+            metrics.command_duration += more.command_duration;
+        }
+        self
+    }
+
+    /// stdout as printed by a tool, empty for a listing read from the header,
+    /// whose stdout holds entry names that must not be mistaken for messages
+    fn tool_stdout(&self) -> &str {
+        if self.listing.is_some() { "" } else { &self.stdout }
+    }
+
+    /// Lines of tool output on stdout and stderr, see `tool_stdout`
+    fn output_lines(&self) -> impl Iterator<Item = &str> {
+        self.tool_stdout().lines().chain(self.stderr.lines())
+    }
+
+    /// Entry path with separators normalized as the configuration asks
+    fn normalized_path(&self, path: &str) -> String {
+        if self.config.normalize_separators() { path.replace('\\', "/") } else { path.to_string() }
+    }
+
+    /// Whether an output line signals failure, as judged by the configuration.
+    /// Checksum failures do not count when the checksum was skipped, and
+    /// warnings do when they are errors but extractpbo ran without -W.
+    pub(crate) fn is_error_line(&self, line: &str) -> bool {
+        let is_error = self.config.is_error_line(line) || (self.warnings_as_errors && self.config.is_warning(line));
+        is_error && !(self.checksum_skipped && CHECKSUM_FAILURE_INDICATORS.iter().any(|indicator| line.contains(indicator)))
+    }
+
+    /// Files listed or extracted by extractpbo, sorted lexicographically and
+    /// without duplicates, so the list does not depend on the order extractpbo
+    /// printed them in
     pub fn get_file_list(&self) -> Vec<String> {
+        self.get_file_list_diagnostics().0
+    }
+
+    /// Like `get_file_list`, with the normalized prefix in front of each file,
+    /// e.g. `tc/mirrorform/config.bin`: the path the game resolves at runtime.
+    /// Without a prefix this is the plain file list.
+    pub fn get_file_list_prefixed(&self) -> Vec<String> {
+        let files = self.get_file_list();
+        match self.get_prefix_normalized() {
+            Some(prefix) => files.into_iter().map(|file| format!("{}/{}", prefix, file)).collect(),
+            None => files,
+        }
+    }
+
+    /// Parse the file list like `get_file_list`, also returning the non-empty
+    /// stdout lines that did not yield a file, such as metadata lines skipped
+    /// by the listing heuristics, in the order they were printed
+    pub fn get_file_list_diagnostics(&self) -> (Vec<String>, Vec<String>) {
+        if let Some(listing) = &self.listing {
+            let mut files: Vec<String> = listing.entries.iter().map(|entry| self.normalized_path(&entry.path)).collect();
+            files.sort();
+            files.dedup();
+            return (files, Vec::new());
+        }
+
         let mut files = Vec::new();
+        let mut unrecognized = Vec::new();
         trace!("Processing stdout for file list, stdout length: {}", self.stdout.len());
         trace!("Stdout contents:\n{}", self.stdout);
         trace!("Stderr contents:\n{}", self.stderr);
@@ -76,12 +228,16 @@ impl ExtractResult {
             
             if self.should_skip_line(line) {
                 trace!("Skipping metadata line {}: '{}'", i, line);
+                unrecognized.push(line.to_string());
                 continue;
             }
 
-            if let Some(file) = self.extract_filename(line) {
-                trace!("Adding file from line {}: '{}'", i, file);
-                files.push(file);
+            match self.extract_filename(line) {
+                Some(file) => {
+                    trace!("Adding file from line {}: '{}'", i, file);
+                    files.push(file);
+                }
+                None => unrecognized.push(line.to_string()),
             }
         }
         
@@ -90,7 +246,102 @@ impl ExtractResult {
         files.dedup();
         
         trace!("Final file list ({} files): {:?}", files.len(), files);
-        files
+        (files, unrecognized)
+    }
+
+    /// Unique directories containing the listed files, including intermediate
+    /// ones, using the file list's separators and sorted. Files at the root add nothing.
+    pub fn get_directory_list(&self) -> Vec<String> {
+        let mut directories = Vec::new();
+        for file in self.get_file_list() {
+            let mut parent = file.as_str();
+            while let Some((dir, _)) = parent.rsplit_once(['/', '\\']) {
+                if !dir.is_empty() {
+                    directories.push(dir.to_string());
+                }
+                parent = dir;
+            }
+        }
+
+        directories.sort();
+        directories.dedup();
+        directories
+    }
+
+    /// Split `requested` filter entries into those matching a file extractpbo
+    /// reported as `Extracting ...` and those that matched nothing, e.g. to warn
+    /// about typos in a filter. Entries may be comma-separated lists and use the
+    /// same wildcards as `-F=`.
+    pub fn compare_to_requested(&self, requested: &[&str]) -> (Vec<String>, Vec<String>) {
+        let extracted: Vec<String> = self.tool_stdout()
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("Extracting "))
+            .filter_map(|line| self.extract_filename(line))
+            .collect();
+        trace!("Comparing {:?} against extracted files {:?}", requested, extracted);
+
+        requested
+            .iter()
+            .flat_map(|entry| entry.split(','))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(String::from)
+            .partition(|entry| match glob_filter_regex(entry) {
+                Ok(pattern) => extracted.iter().any(|file| pattern.is_match(file)),
+                Err(_) => false,
+            })
+    }
+
+    /// Parse the listing into entries, keeping size and timestamp when
+    /// extractpbo printed them in the detailed `filename:timestamp: size bytes` format.
+    pub fn get_entries(&self) -> Vec<PboEntry> {
+        if let Some(listing) = &self.listing {
+            return listing.entries
+                .iter()
+                .map(|entry| PboEntry { path: self.normalized_path(&entry.path), ..entry.clone() })
+                .collect();
+        }
+
+        let mut entries: Vec<PboEntry> = Vec::new();
+
+        for line in self.stdout.lines() {
+            let line = line.trim();
+            if line.is_empty() || self.should_skip_line(line) {
+                continue;
+            }
+
+            if let Some(entry) = self.extract_entry(line) {
+                if !entries.iter().any(|e| e.path == entry.path) {
+                    trace!("Adding entry: {:?}", entry);
+                    entries.push(entry);
+                }
+            }
+        }
+
+        entries
+    }
+
+    fn extract_entry(&self, line: &str) -> Option<PboEntry> {
+        let path = self.extract_filename(line)?;
+
+        // Only the detailed format carries metadata. The timestamp sits between the
+        // first and last colon so that a formatted time containing colons still parses.
+        let (size, timestamp) = match (line.find(':'), line.rfind(':')) {
+            (Some(first), Some(last)) if first < last && !line.starts_with("Extracting ") => {
+                let timestamp = line[first + 1..last].trim().parse::<u64>().ok();
+                let size = line[last + 1..]
+                    .trim()
+                    .trim_end_matches("bytes")
+                    .trim()
+                    .parse::<u64>()
+                    .ok();
+                (size, timestamp)
+            }
+            _ => (None, None),
+        };
+
+        Some(PboEntry { path, size, timestamp, packing_method: None })
     }
 
     fn should_skip_line(&self, line: &str) -> bool {
@@ -141,26 +392,56 @@ impl ExtractResult {
         };
 
         filename
-            .map(|s| s.replace('\\', "/"))
+            .map(|s| self.normalized_path(s))
             .filter(|s| !s.is_empty())
             .filter(|s| !s.contains("hemtt=") && !s.contains("git="))
     }
 
+    /// Prefix as extractpbo printed it, e.g. `tc\mirrorform`
     pub fn get_prefix(&self) -> Option<String> {
         debug!("Searching for prefix in stdout (length: {})", self.stdout.len());
         trace!("Full stdout content:\n{}", self.stdout);
-        
-        self.stdout
-            .lines()
-            .find(|line| line.starts_with("prefix="))
-            .and_then(|line| {
-                line.split('=')
-                    .nth(1)
-                    .map(|prefix| prefix.trim().trim_end_matches(';').to_string())
-            })
+        if let Some(listing) = &self.listing {
+            return listing.prefix.clone();
+        }
+
+        parse_prefix(&self.stdout)
+    }
+
+    /// Prefix in forward-slash form, e.g. `tc/mirrorform`. See [`normalize_prefix`].
+    pub fn get_prefix_normalized(&self) -> Option<String> {
+        self.get_prefix()
+            .map(|prefix| normalize_prefix(&prefix))
             .filter(|prefix| !prefix.is_empty())
     }
 
+    /// Segments of the normalized prefix, e.g. `["tc", "mirrorform"]` for
+    /// `tc\mirrorform`, ready to join onto a `PathBuf`. Empty without a prefix.
+    pub fn get_prefix_components(&self) -> Vec<String> {
+        self.get_prefix_normalized()
+            .map(|prefix| prefix.split('/').map(String::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// Number of residual bytes extractpbo found after the last entry, taken
+    /// from its "residual bytes in file" message: the number right before the
+    /// message, or right after it as in `residual bytes in file: 12`. `None`
+    /// when the message is absent or no count sits next to it, so digits
+    /// elsewhere on the line (e.g. in `a3_2035.pbo`) are never taken.
+    pub fn residual_bytes(&self) -> Option<u64> {
+        let (line, start) = self.output_lines().find_map(|line| {
+            line.to_ascii_lowercase().find(RESIDUAL_BYTES_INDICATOR).map(|start| (line, start))
+        })?;
+        let before = line[..start].split_whitespace().next_back().and_then(|word| word.parse().ok());
+        let count = before.or_else(|| {
+            let after = line[start + RESIDUAL_BYTES_INDICATOR.len()..].trim_start_matches([':', ' ', '\t']);
+            let digits = after.find(|c: char| !c.is_ascii_digit()).map_or(after, |end| &after[..end]);
+            digits.parse().ok()
+        });
+        debug!("Residual bytes reported: {:?}", count);
+        count
+    }
+
     pub fn get_error_message(&self) -> Option<String> {
         if !self.is_success() {
             let mut msg = String::new();
@@ -189,6 +470,90 @@ impl ExtractResult {
             None
         }
     }
+
+    /// Timing and size of the extractpbo run, if one took place
+    pub fn metrics(&self) -> Option<&OperationMetrics> {
+        self.metrics.as_ref()
+    }
+
+    /// Snapshot of the result's parsed fields, e.g. for returning as JSON
+    pub fn to_summary(&self) -> ExtractSummary {
+        ExtractSummary {
+            success: self.is_success(),
+            files: self.get_file_list(),
+            prefix: self.get_prefix(),
+            warnings: self.get_warnings(),
+            error: self.get_error_message(),
+            return_code: self.return_code,
+        }
+    }
+}
+
+/// Outcome of an extractpbo run, as determined by [`ExtractResult::classify`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ExtractOutcome {
+    /// Finished without errors or warnings
+    Success,
+    /// Finished, but reported known warnings
+    WarningsPresent,
+    /// The PBO's SHA did not match its contents
+    ChecksumFailed,
+    /// The PBO header is of an unknown type or corrupt
+    BadHeader,
+    /// A warning was promoted to an error by extractpbo
+    WarningAsError,
+    /// The PBO or a file it refers to could not be opened
+    FileNotFound,
+    /// Failed for another reason, with the process return code
+    Unknown(i32),
+}
+
+impl ExtractOutcome {
+    /// Whether the operation succeeded, with or without warnings
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success | Self::WarningsPresent)
+    }
+}
+
+/// Performance figures of an extractpbo run, see [`ExtractResult::metrics`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OperationMetrics {
+    /// Wall time of the extractpbo process
+    pub command_duration: Duration,
+    /// Files written by an extraction, zero for listings
+    pub file_count: usize,
+    /// Size of the written files on disk
+    pub bytes_extracted: u64,
+}
+
+/// Owned summary of an [`ExtractResult`], serializable with the `serde` feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtractSummary {
+    pub success: bool,
+    pub files: Vec<String>,
+    pub prefix: Option<String>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+    pub return_code: i32,
+}
+
+impl fmt::Display for ExtractSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(error) => write!(f, "Failed ({}): {}", self.return_code, error)?,
+            None => write!(f, "OK: {} files", self.files.len())?,
+        }
+        if let Some(prefix) = &self.prefix {
+            write!(f, ", prefix {}", prefix)?;
+        }
+        if !self.warnings.is_empty() {
+            write!(f, ", {} warnings", self.warnings.len())?;
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for ExtractResult {
@@ -235,6 +600,29 @@ impl fmt::Display for ExtractResult {
     }
 }
 
+/// Value of the `prefix=` line in extractpbo output, as written
+pub(crate) fn parse_prefix(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find(|line| line.starts_with("prefix="))
+        // Only the first `=` separates the key, the value may contain more
+        .and_then(|line| line.split_once('='))
+        .map(|(_, prefix)| prefix.trim().trim_end_matches(';').to_string())
+        .filter(|prefix| !prefix.is_empty())
+}
+
+/// Normalize a PBO prefix to forward slashes without leading, trailing or
+/// repeated separators, so `\tc\mirrorform;` becomes `tc/mirrorform`
+pub fn normalize_prefix(raw: &str) -> String {
+    raw.trim()
+        .trim_end_matches(';')
+        .split(['\\', '/'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 pub trait ResultProcessor {
     fn process_output(&self) -> Result<Vec<String>>;
     fn process_prefix(&self) -> Option<String>;
@@ -273,6 +661,7 @@ mod tests {
             return_code: 0,
             stdout: String::new(),
             stderr: "Bad Sha detected".to_string(),
+            ..Default::default()
         };
         assert!(!result.is_success());
 
@@ -280,8 +669,126 @@ mod tests {
             return_code: 0,
             stdout: String::new(),
             stderr: String::new(),
+            ..Default::default()
         };
         assert!(result.is_success());
+
+        let result = ExtractResult {
+            return_code: 0,
+            stderr: "Bad Sha detected".to_string(),
+            checksum_skipped: true,
+            ..Default::default()
+        };
+        assert!(result.is_success());
+        assert!(result.checksum_skipped());
+
+        // Without -W the result fails on the warnings itself
+        let result = ExtractResult {
+            return_code: 0,
+            stderr: "Bad Sha detected\nWarning: arma pbo is missing a prefix".to_string(),
+            checksum_skipped: true,
+            warnings_as_errors: true,
+            ..Default::default()
+        };
+        assert!(!result.is_success());
+        assert_eq!(result.classify(), ExtractOutcome::WarningAsError);
+    }
+
+    #[test]
+    fn test_warnings_are_not_errors() {
+        let result = ExtractResult {
+            return_code: 0,
+            stdout: "Opening pbo archive\nconfig.bin".to_string(),
+            stderr: "Warning: arma pbo is missing a prefix\nreserved field non zero".to_string(),
+            ..Default::default()
+        };
+        assert!(result.is_success());
+        assert_eq!(result.get_warnings(), vec![
+            "Warning: arma pbo is missing a prefix".to_string(),
+            "reserved field non zero".to_string(),
+        ]);
+
+        let result = ExtractResult {
+            return_code: 0,
+            stdout: String::new(),
+            stderr: "no shakey on arma\nBad Sha detected".to_string(),
+            ..Default::default()
+        };
+        assert!(!result.is_success());
+        assert_eq!(result.get_warnings(), vec!["no shakey on arma".to_string()]);
+    }
+
+    #[test]
+    fn test_classify() {
+        let classify = |return_code: i32, stderr: &str| ExtractResult {
+            return_code,
+            stderr: stderr.to_string(),
+            ..Default::default()
+        }.classify();
+
+        assert_eq!(classify(0, ""), ExtractOutcome::Success);
+        assert_eq!(classify(0, "no shakey on arma"), ExtractOutcome::WarningsPresent);
+        assert_eq!(classify(1, "no shakey on arma\nBad Sha detected"), ExtractOutcome::ChecksumFailed);
+        assert_eq!(classify(1, "DePbo:Pbo unknown header type"), ExtractOutcome::BadHeader);
+        assert_eq!(classify(1, "this warning is set as an error"), ExtractOutcome::WarningAsError);
+        assert_eq!(classify(1, "Cannot open missing.pbo"), ExtractOutcome::FileNotFound);
+        assert_eq!(classify(0, "Failed to write"), ExtractOutcome::Unknown(0));
+        assert_eq!(classify(3, ""), ExtractOutcome::Unknown(3));
+
+        for (return_code, stderr) in [(0, ""), (0, "no shakey on arma"), (0, "Bad Sha"), (2, "")] {
+            let result = ExtractResult { return_code, stderr: stderr.to_string(), ..Default::default() };
+            assert_eq!(result.classify().is_success(), result.is_success());
+        }
+    }
+
+    #[test]
+    fn test_residual_bytes() {
+        let config = Arc::new(PboConfig::builder().tolerate_residual_bytes().build());
+        let result = |stderr: &str| ExtractResult {
+            stderr: stderr.to_string(),
+            config: config.clone(),
+            ..Default::default()
+        };
+
+        let residual = result("Warning: 1536 residual bytes in file");
+        assert_eq!(residual.residual_bytes(), Some(1536));
+        assert!(residual.is_success());
+        assert_eq!(result("Residual bytes in file: 12").residual_bytes(), Some(12));
+        assert_eq!(result("residual bytes in file").residual_bytes(), None);
+        assert_eq!(result("Extracting config.bin...").residual_bytes(), None);
+        assert_eq!(result("a3_2035.pbo: 1536 residual bytes in file").residual_bytes(), Some(1536));
+        assert_eq!(result("a3_2035.pbo: residual bytes in file").residual_bytes(), None);
+        assert_eq!(result("a3_2035.pbo residual bytes in file: 7 (ignored)").residual_bytes(), Some(7));
+
+        let strict = ExtractResult {
+            stderr: "1536 residual bytes in file".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(strict.residual_bytes(), Some(1536));
+        assert!(!strict.is_success());
+    }
+
+    #[test]
+    fn test_configured_indicators() {
+        let config = PboConfig::builder()
+            .ignore_indicator("residual bytes in file")
+            .treat_warning_as_error("no shakey on arma")
+            .build();
+
+        let result = ExtractResult {
+            stderr: "residual bytes in file".to_string(),
+            config: Arc::new(config.clone()),
+            ..Default::default()
+        };
+        assert!(result.is_success());
+        assert_eq!(result.get_warnings(), vec!["residual bytes in file".to_string()]);
+
+        let result = ExtractResult {
+            stderr: "no shakey on arma".to_string(),
+            config: Arc::new(config),
+            ..Default::default()
+        };
+        assert!(!result.is_success());
     }
 
     #[test]
@@ -290,6 +797,7 @@ mod tests {
             return_code: 0,
             stdout: "config.bin\ndata/test.paa\nmodels/model.p3d".to_string(),
             stderr: String::new(),
+            ..Default::default()
         };
         
         let files = result.get_file_list();
@@ -298,4 +806,204 @@ mod tests {
         assert!(files.contains(&"data/test.paa".to_string()));
         assert!(files.contains(&"models/model.p3d".to_string()));
     }
+
+    #[test]
+    fn test_file_list_order_independent() {
+        let listing = |stdout: &str| ExtractResult {
+            stdout: stdout.to_string(),
+            ..Default::default()
+        };
+        let first = listing("uniform/mirror.p3d\nconfig.bin\nlogo.paa\nconfig.bin");
+        let second = listing("logo.paa\nconfig.bin\nuniform/mirror.p3d");
+
+        assert_eq!(first.get_file_list(), vec!["config.bin", "logo.paa", "uniform/mirror.p3d"]);
+        assert_eq!(first.get_file_list(), second.get_file_list());
+    }
+
+    #[test]
+    fn test_file_list_prefixed() {
+        let result = ExtractResult {
+            stdout: "prefix=\\tc\\mirrorform\\;\nconfig.bin\nuniform\\black.paa".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(result.get_file_list_prefixed(), vec!["tc/mirrorform/config.bin", "tc/mirrorform/uniform/black.paa"]);
+
+        let unprefixed = ExtractResult {
+            stdout: "config.bin\nlogo.paa".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(unprefixed.get_file_list_prefixed(), unprefixed.get_file_list());
+    }
+
+    #[test]
+    fn test_file_list_diagnostics() {
+        let result = ExtractResult {
+            stdout: "Active code page: 65001\nprefix=tc\\mirrorform;\n\nconfig.bin\n:12345\nlogo.paa\n".to_string(),
+            ..Default::default()
+        };
+
+        let (files, unrecognized) = result.get_file_list_diagnostics();
+        assert_eq!(files, vec!["config.bin", "logo.paa"]);
+        assert_eq!(unrecognized, vec!["Active code page: 65001", "prefix=tc\\mirrorform;", ":12345"]);
+        assert_eq!(result.get_file_list(), files);
+    }
+
+    #[test]
+    fn test_get_directory_list() {
+        let result = ExtractResult {
+            return_code: 0,
+            stdout: "prefix=tc\\mirrorform;\nconfig.bin\nuniform\\black.paa\nuniform\\mirror.p3d\ndata\\textures\\ca\\logo.paa".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(result.get_directory_list(), vec!["data", "data/textures", "data/textures/ca", "uniform"]);
+
+        let flat = ExtractResult {
+            stdout: "config.bin\nlogo.paa".to_string(),
+            ..Default::default()
+        };
+        assert!(flat.get_directory_list().is_empty());
+    }
+
+    #[test]
+    fn test_native_separators() {
+        let result = ExtractResult {
+            stdout: "config.bin\nuniform\\mirror.p3d\nExtracting data\\logo.paa...".to_string(),
+            config: Arc::new(PboConfig::builder().normalize_separators(false).build()),
+            ..Default::default()
+        };
+        assert_eq!(result.get_file_list(), vec!["config.bin", "data\\logo.paa", "uniform\\mirror.p3d"]);
+        assert_eq!(result.get_directory_list(), vec!["data", "uniform"]);
+        assert_eq!(result.compare_to_requested(&["data/logo.paa"]).0, vec!["data/logo.paa"]);
+    }
+
+    #[test]
+    fn test_to_summary() {
+        let result = ExtractResult {
+            return_code: 0,
+            stdout: "prefix=tc\\mirrorform;\nExtracting config.bin...\nExtracting logo.paa...".to_string(),
+            stderr: "Warning: arma pbo is missing a prefix".to_string(),
+            ..Default::default()
+        };
+
+        let summary = result.to_summary();
+        assert_eq!(summary, ExtractSummary {
+            success: true,
+            files: vec!["config.bin".to_string(), "logo.paa".to_string()],
+            prefix: Some("tc\\mirrorform".to_string()),
+            warnings: vec!["Warning: arma pbo is missing a prefix".to_string()],
+            error: None,
+            return_code: 0,
+        });
+        assert_eq!(summary.to_string(), "OK: 2 files, prefix tc\\mirrorform, 1 warnings");
+
+        let failed = ExtractResult {
+            return_code: 1,
+            stderr: "Bad Sha detected".to_string(),
+            ..Default::default()
+        }.to_summary();
+        assert!(!failed.success);
+        assert!(failed.to_string().starts_with("Failed (1): Bad Sha detected"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_summary_json() {
+        let result = ExtractResult {
+            return_code: 0,
+            stdout: "Extracting config.bin...".to_string(),
+            ..Default::default()
+        };
+
+        let value = serde_json::to_value(result.to_summary()).unwrap();
+        assert_eq!(value["success"], true);
+        assert_eq!(value["files"][0], "config.bin");
+        assert!(value["prefix"].is_null());
+        assert_eq!(value["return_code"], 0);
+    }
+
+    #[test]
+    fn test_parse_prefix_with_equals() {
+        assert_eq!(parse_prefix("prefix=weird=value;\nconfig.bin").as_deref(), Some("weird=value"));
+        assert_eq!(parse_prefix("prefix=tc\\mirrorform;").as_deref(), Some("tc\\mirrorform"));
+        assert_eq!(parse_prefix("config.bin"), None);
+    }
+
+    #[test]
+    fn test_normalize_prefix() {
+        assert_eq!(normalize_prefix("tc\\mirrorform"), "tc/mirrorform");
+        assert_eq!(normalize_prefix(" \\tc\\\\mirrorform\\; "), "tc/mirrorform");
+        assert_eq!(normalize_prefix("tc/mirrorform/"), "tc/mirrorform");
+        assert_eq!(normalize_prefix(""), "");
+
+        let result = ExtractResult {
+            stdout: "prefix=tc\\mirrorform;\nconfig.bin".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(result.get_prefix().as_deref(), Some("tc\\mirrorform"));
+        assert_eq!(result.get_prefix_normalized().as_deref(), Some("tc/mirrorform"));
+        assert_eq!(result.get_prefix_components(), vec!["tc", "mirrorform"]);
+
+        let empty = ExtractResult {
+            stdout: "prefix=\\;".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(empty.get_prefix_normalized(), None);
+        assert!(empty.get_prefix_components().is_empty());
+
+        let trailing = ExtractResult {
+            stdout: "prefix=\\x\\cba\\addons\\;".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(trailing.get_prefix_components(), vec!["x", "cba", "addons"]);
+    }
+
+    #[test]
+    fn test_compare_to_requested() {
+        let result = ExtractResult {
+            return_code: 0,
+            stdout: "prefix=tc\\mirrorform;\nExtracting config.bin...\nExtracting uniform\\black.paa...".to_string(),
+            stderr: String::new(),
+            ..Default::default()
+        };
+
+        let (matched, unmatched) = result.compare_to_requested(&["config.bin,missing.sqf", "uniform/*.paa", "*.rvmat"]);
+        assert_eq!(matched, vec!["config.bin", "uniform/*.paa"]);
+        assert_eq!(unmatched, vec!["missing.sqf", "*.rvmat"]);
+
+        // Only extraction lines count, not listing or metadata lines
+        let listing = ExtractResult {
+            stdout: "config.bin:1736976950: 1071 bytes".to_string(),
+            ..Default::default()
+        };
+        let (matched, unmatched) = listing.compare_to_requested(&["config.bin"]);
+        assert!(matched.is_empty());
+        assert_eq!(unmatched, vec!["config.bin"]);
+    }
+
+    #[test]
+    fn test_entries_parsing() {
+        let result = ExtractResult {
+            return_code: 0,
+            stdout: "prefix=tc\\mirrorform;\nconfig.bin:1736976950: 1071 bytes\nuniform\\black.paa:1707293588: 826 bytes\nlogo.paa".to_string(),
+            stderr: String::new(),
+            ..Default::default()
+        };
+
+        let entries = result.get_entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0], PboEntry {
+            path: "config.bin".to_string(),
+            size: Some(1071),
+            timestamp: Some(1736976950),
+            packing_method: None,
+        });
+        assert_eq!(entries[1].path, "uniform/black.paa");
+        assert_eq!(entries[1].size, Some(826));
+        assert_eq!(entries[2], PboEntry {
+            path: "logo.paa".to_string(),
+            size: None,
+            timestamp: None,
+            packing_method: None,
+        });
+    }
 }
@@ -1,10 +1,22 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use log::{debug, info};
 use crate::error::types::{PboError, FileSystemError, Result};
 use std::fs;
+use std::io::Read;
 use crate::core::config::PboConfig;
+use crate::core::constants::{RAPIFIED_MAGIC, SNIFFED_BIN_EXTENSION};
 
 pub fn convert_binary_file(input: &Path, output: &Path) -> Result<()> {
+    convert(input, output, false)
+}
+
+/// Like `convert_binary_file`, but copies instead of renaming so the original
+/// binary file stays next to the converted one.
+pub fn convert_binary_file_keep(input: &Path, output: &Path) -> Result<()> {
+    convert(input, output, true)
+}
+
+fn convert(input: &Path, output: &Path, keep_original: bool) -> Result<()> {
     debug!("Converting binary file from {:?} to {:?}", input, output);
     
     // Ensure parent directory exists
@@ -28,13 +40,19 @@ pub fn convert_binary_file(input: &Path, output: &Path) -> Result<()> {
         return Err(err);
     }
 
-    debug!("Renaming file");
-    fs::rename(input, output).map_err(|e| {
+    let converted = if keep_original {
+        debug!("Copying file");
+        fs::copy(input, output).map(|_| ())
+    } else {
+        debug!("Renaming file");
+        fs::rename(input, output)
+    };
+    converted.map_err(|e| {
         let err = PboError::FileSystem(FileSystemError::WriteFile {
             path: output.to_path_buf(),
             reason: e.to_string(),
         });
-        debug!("Error during rename: {}", err);
+        debug!("Error during conversion: {}", err);
         err
     })?;
 
@@ -42,13 +60,65 @@ pub fn convert_binary_file(input: &Path, output: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn process_binary_files(source_dir: &Path, config: &PboConfig) -> Result<()> {
+pub fn process_binary_files(source_dir: &Path, config: &PboConfig) -> Result<Vec<(PathBuf, PathBuf)>> {
+    process_binary_files_with_progress(source_dir, config, &mut |_, _, _| {})
+}
+
+/// Convert mapped binary files like `process_binary_files`, invoking `progress`
+/// before each conversion with the file path, its 1-based index and the total count.
+pub fn process_binary_files_with_progress(
+    source_dir: &Path,
+    config: &PboConfig,
+    progress: &mut dyn FnMut(&Path, usize, usize),
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    process_binary_files_with_options(source_dir, config, false, progress)
+}
+
+/// Rename every mapped binary file under `dir`, returning the (old, new) path
+/// pairs that were renamed. A missing directory yields an empty list.
+pub fn rename_bins_in_dir(dir: &Path, config: &PboConfig) -> Result<Vec<(PathBuf, PathBuf)>> {
+    process_binary_files(dir, config)
+}
+
+/// Convert mapped binary files, reporting progress like
+/// `process_binary_files_with_progress`.
+///
+/// With `keep_original` the binary files are copied rather than renamed, and
+/// targets that already exist (e.g. a `config.cpp` written by extractpbo) are
+/// left untouched.
+///
+/// Returns the (source, target) pairs that were converted.
+pub fn process_binary_files_with_options(
+    source_dir: &Path,
+    config: &PboConfig,
+    keep_original: bool,
+    progress: &mut dyn FnMut(&Path, usize, usize),
+) -> Result<Vec<(PathBuf, PathBuf)>> {
     if !source_dir.is_dir() {
         debug!("Source directory {:?} is not a directory", source_dir);
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     debug!("Processing binary files in {:?}", source_dir);
+    let mut conversions = Vec::new();
+    collect_binary_files(source_dir, config, &mut conversions)?;
+    if keep_original {
+        conversions.retain(|(_, new_path)| !new_path.exists());
+    }
+
+    let total = conversions.len();
+    debug!("Found {} binary files to convert", total);
+    for (index, (path, new_path)) in conversions.iter().enumerate() {
+        progress(path, index + 1, total);
+        convert(path, new_path, keep_original)?;
+    }
+
+    info!("Completed processing binary files in {:?}", source_dir);
+    Ok(conversions)
+}
+
+/// Recursively gather (source, target) pairs for every file with a bin mapping
+fn collect_binary_files(source_dir: &Path, config: &PboConfig, conversions: &mut Vec<(PathBuf, PathBuf)>) -> Result<()> {
     for entry in fs::read_dir(source_dir).map_err(|e| {
         PboError::FileSystem(FileSystemError::ReadFile {
             path: source_dir.to_path_buf(),
@@ -65,30 +135,37 @@ pub fn process_binary_files(source_dir: &Path, config: &PboConfig) -> Result<()>
         let path = entry.path();
         if path.is_dir() {
             debug!("Found directory: {:?}, recursing", path);
-            process_binary_files(&path, config)?;
+            collect_binary_files(&path, config, conversions)?;
         } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
             debug!("Processing file: {}", name);
             
-            // Try exact filename match first
-            let target_ext = config.get_bin_extension(name);
-            
-            if let Some(ext) = target_ext {
-                debug!("Found mapping for {}: new extension will be {}", name, ext);
-                // Get the filename without extension
-                let stem = path.file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("unnamed");
-                
-                let new_path = path.with_file_name(format!("{}.{}", stem, ext));
-                convert_binary_file(&path, &new_path)?;
+            if let Some(target) = config.bin_target_name(name) {
+                debug!("Found mapping for {}: new name will be {}", name, target);
+                let new_path = path.with_file_name(target);
+                conversions.push((path, new_path));
+            } else if config.sniff_bin_content() && !name.contains('.') && is_rapified(&path)? {
+                debug!("{} is a rapified config, converting to .{}", name, SNIFFED_BIN_EXTENSION);
+                let new_path = path.with_extension(SNIFFED_BIN_EXTENSION);
+                conversions.push((path, new_path));
             } else {
                 debug!("No mapping found for {}, skipping", name);
             }
         }
     }
 
-    info!("Completed processing binary files in {:?}", source_dir);
     Ok(())
 }
 
+/// Whether the file at `path` starts with the rapified config magic
+fn is_rapified(path: &Path) -> Result<bool> {
+    let mut magic = [0u8; RAPIFIED_MAGIC.len()];
+    let mut file = fs::File::open(path).map_err(|e| {
+        PboError::FileSystem(FileSystemError::ReadFile {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+    })?;
+    Ok(file.read_exact(&mut magic).is_ok() && magic == RAPIFIED_MAGIC)
+}
+
 // Remove duplicated tests since they are covered in binary_handling.rs
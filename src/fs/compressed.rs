@@ -0,0 +1,170 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use log::debug;
+use crate::core::constants::{COMPRESSED_PBO_EXTENSIONS, GZIP_MAGIC, ZSTD_MAGIC};
+use crate::core::extensions::is_pbo_path;
+use crate::error::types::{Result, PboError, FileSystemError};
+use crate::fs_err;
+
+/// Compression a PBO was wrapped in for distribution, e.g. `mod.pbo.gz`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Wrapper {
+    Gzip,
+    Zstd,
+}
+
+impl Wrapper {
+    fn from_magic(magic: &[u8]) -> Option<Self> {
+        if magic.starts_with(GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else if magic.starts_with(ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// Compression wrapping the data of `source`, recognized by its magic bytes.
+/// `source` is left at its start.
+pub(crate) fn sniff_wrapper<R: Read + Seek>(source: &mut R) -> io::Result<Option<Wrapper>> {
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+    source.by_ref().take(ZSTD_MAGIC.len() as u64).read_to_end(&mut magic)?;
+    source.seek(SeekFrom::Start(0))?;
+    Ok(Wrapper::from_magic(&magic))
+}
+
+/// Compression wrapping the file at `path`. Anything but a regular file is
+/// reported as not compressed.
+pub(crate) fn detect_wrapper(path: &Path) -> Result<Option<Wrapper>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let mut file = fs_err!(File::open(path) => ReadFile, path)?;
+    fs_err!(sniff_wrapper(&mut file) => ReadFile, path)
+}
+
+/// Decompress `file`, read from `path`, into an anonymous temp file that is
+/// deleted once closed. The returned file is positioned at its start. Fails
+/// when the PBO decompresses to more than `limit` bytes.
+pub(crate) fn unwrap_to_tempfile(file: File, wrapper: Wrapper, path: &Path, limit: u64) -> Result<File> {
+    debug!("Decompressing {} PBO {:?} to a temp file", wrapper.name(), path);
+    let mut temp = tempfile::tempfile().map_err(|e| PboError::FileSystem(FileSystemError::WriteFile {
+        path: std::env::temp_dir(),
+        reason: e.to_string(),
+    }))?;
+    copy_limited(decoder(file, wrapper, path)?, &mut temp, path, limit)?;
+    fs_err!(temp.seek(SeekFrom::Start(0)) => ReadFile, path)?;
+    Ok(temp)
+}
+
+/// Decompress the PBO at `path` into `dir`, returning the decompressed file.
+/// It is named after `path` without the compression extension, so it keeps a
+/// PBO extension extractpbo accepts. Fails, leaving no file behind, when the PBO
+/// decompresses to more than `limit` bytes.
+pub(crate) fn unwrap_to(path: &Path, wrapper: Wrapper, dir: &Path, limit: u64) -> Result<PathBuf> {
+    let target = dir.join(unwrapped_name(path));
+    debug!("Decompressing {} PBO {:?} to {:?}", wrapper.name(), path, target);
+    let file = fs_err!(File::open(path) => ReadFile, path)?;
+    let mut out = fs_err!(File::create(&target) => WriteFile, &target)?;
+    let copied = decoder(file, wrapper, path).and_then(|decoder| copy_limited(decoder, &mut out, path, limit));
+    if let Err(e) = copied {
+        drop(out);
+        let _ = std::fs::remove_file(&target);
+        return Err(e);
+    }
+    Ok(target)
+}
+
+/// Copy the decompressed data to `out`, stopping once it exceeds `limit` bytes
+fn copy_limited(decoder: impl Read, out: &mut impl Write, path: &Path, limit: u64) -> Result<u64> {
+    let copied = fs_err!(io::copy(&mut decoder.take(limit.saturating_add(1)), out) => ReadFile, path)?;
+    if copied > limit {
+        return Err(PboError::ValidationFailed(format!(
+            "{} decompresses to more than {} bytes", path.display(), limit
+        )));
+    }
+    Ok(copied)
+}
+
+/// File name of the decompressed PBO: `mod.pbo.gz` becomes `mod.pbo`, and a
+/// `.pbo` extension is added when none is left
+fn unwrapped_name(path: &Path) -> PathBuf {
+    let name = PathBuf::from(path.file_name().unwrap_or_default());
+    let compressed = name.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| COMPRESSED_PBO_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+    let name = if compressed { PathBuf::from(name.file_stem().unwrap_or_default()) } else { name };
+    if is_pbo_path(&name) {
+        return name;
+    }
+    let mut name = name.into_os_string();
+    name.push(".pbo");
+    PathBuf::from(name)
+}
+
+#[cfg(feature = "compressed")]
+fn decoder(file: File, wrapper: Wrapper, path: &Path) -> Result<Box<dyn Read>> {
+    Ok(match wrapper {
+        Wrapper::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        Wrapper::Zstd => Box::new(fs_err!(zstd::stream::read::Decoder::new(file) => ReadFile, path)?),
+    })
+}
+
+#[cfg(not(feature = "compressed"))]
+fn decoder(_file: File, wrapper: Wrapper, path: &Path) -> Result<Box<dyn Read>> {
+    Err(PboError::ValidationFailed(format!(
+        "{} is {} compressed: reading it requires the compressed feature",
+        path.display(), wrapper.name()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_sniff_wrapper() {
+        let mut gzip = Cursor::new(vec![0x1F, 0x8B, 0x08, 0x00, 0x00]);
+        assert_eq!(sniff_wrapper(&mut gzip).unwrap(), Some(Wrapper::Gzip));
+        assert_eq!(gzip.position(), 0);
+
+        let mut zstd = Cursor::new(vec![0x28, 0xB5, 0x2F, 0xFD, 0x00]);
+        assert_eq!(sniff_wrapper(&mut zstd).unwrap(), Some(Wrapper::Zstd));
+        assert_eq!(sniff_wrapper(&mut Cursor::new(b"\0sreV".to_vec())).unwrap(), None);
+        assert_eq!(sniff_wrapper(&mut Cursor::new(vec![0x1F])).unwrap(), None);
+    }
+
+    #[test]
+    fn test_copy_limited() {
+        let path = Path::new("bomb.pbo.gz");
+        let mut out = Vec::new();
+        assert_eq!(copy_limited(Cursor::new(vec![0; 64]), &mut out, path, 64).unwrap(), 64);
+        assert_eq!(out.len(), 64);
+
+        let mut out = Vec::new();
+        assert!(matches!(
+            copy_limited(Cursor::new(vec![0; 1024]), &mut out, path, 64),
+            Err(PboError::ValidationFailed(_))
+        ));
+        assert_eq!(out.len(), 65);
+    }
+
+    #[test]
+    fn test_unwrapped_name() {
+        assert_eq!(unwrapped_name(Path::new("dl/mirrorform.pbo.gz")), PathBuf::from("mirrorform.pbo"));
+        assert_eq!(unwrapped_name(Path::new("mirrorform.xbo.ZST")), PathBuf::from("mirrorform.xbo"));
+        assert_eq!(unwrapped_name(Path::new("mirrorform.pbo")), PathBuf::from("mirrorform.pbo"));
+        assert_eq!(unwrapped_name(Path::new("mirrorform.gz")), PathBuf::from("mirrorform.pbo"));
+        assert_eq!(unwrapped_name(Path::new("download")), PathBuf::from("download.pbo"));
+    }
+}
@@ -1,7 +1,16 @@
 mod binary;
+pub(crate) mod compressed;
+mod sanitizer;
 mod temp;
 mod traits;
+mod transfer;
 
-pub use binary::{convert_binary_file, process_binary_files};
+pub use binary::{
+    convert_binary_file, convert_binary_file_keep, process_binary_files, process_binary_files_with_options,
+    process_binary_files_with_progress, rename_bins_in_dir,
+};
+pub use sanitizer::{PathSanitizer, TraversalPolicy};
 pub use temp::TempFileManager;
-pub use traits::FileOperation;
\ No newline at end of file
+pub use traits::FileOperation;
+pub use transfer::{move_dir_contents, reprefix_directory};
+pub(crate) use transfer::{move_file, prefix_path};
\ No newline at end of file
@@ -0,0 +1,252 @@
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use log::debug;
+use crate::core::config::PboConfig;
+use crate::core::constants::{INVALID_PATH_CHARS, WINDOWS_RESERVED_NAMES};
+use crate::error::types::{Result, PboError, FileSystemError};
+
+/// How [`PathSanitizer`] treats `..` components and rooted paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraversalPolicy {
+    /// Fail with a path validation error
+    #[default]
+    Reject,
+    /// Drop the `..` components and any root or drive prefix, so both
+    /// `../../config.cpp` and `/config.cpp` become `config.cpp`
+    Strip,
+}
+
+/// The path safety rules behind `FileOperation::validate_path_safety`, as a
+/// value that can be built from a [`PboConfig`] and adjusted per operation.
+///
+/// Both `/` and `\` separate components, since PBO entry paths use `\` on
+/// every platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSanitizer {
+    max_length: Option<usize>,
+    allowed_chars: HashSet<char>,
+    traversal: TraversalPolicy,
+    check_reserved_names: bool,
+    allow_absolute: bool,
+}
+
+impl PathSanitizer {
+    /// Sanitizer with the limits, allowed characters and policies of `config`
+    pub fn from_config(config: &PboConfig) -> Self {
+        Self {
+            max_length: config.max_path_length(),
+            allowed_chars: config.allowed_special_chars().clone(),
+            traversal: config.traversal_policy(),
+            check_reserved_names: config.check_reserved_names(),
+            allow_absolute: false,
+        }
+    }
+
+    /// Longest accepted path, or `None` for no limit
+    pub fn with_max_length(mut self, max_length: Option<usize>) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Accept `chars` although they are on `INVALID_PATH_CHARS`
+    pub fn allow_chars(mut self, chars: impl IntoIterator<Item = char>) -> Self {
+        self.allowed_chars.extend(chars);
+        self
+    }
+
+    pub fn with_traversal_policy(mut self, policy: TraversalPolicy) -> Self {
+        self.traversal = policy;
+        self
+    }
+
+    /// Reject components named after Windows devices (`CON`, `nul.txt`, ...)
+    pub fn with_reserved_name_check(mut self, check: bool) -> Self {
+        self.check_reserved_names = check;
+        self
+    }
+
+    /// Keep the root and drive prefix of absolute paths instead of applying
+    /// the traversal policy to them, for paths on the local file system
+    /// rather than PBO entries
+    pub fn allow_absolute(mut self, allow: bool) -> Self {
+        self.allow_absolute = allow;
+        self
+    }
+
+    pub fn traversal_policy(&self) -> TraversalPolicy {
+        self.traversal
+    }
+
+    /// Whether `name` is a Windows device name this sanitizer rejects. The
+    /// extension does not matter, `aux.paa` is as reserved as `AUX`.
+    pub fn is_reserved_name(&self, name: &str) -> bool {
+        let stem = name.split('.').next().unwrap_or(name).trim_end();
+        self.check_reserved_names && WINDOWS_RESERVED_NAMES.iter().any(|r| stem.eq_ignore_ascii_case(r))
+    }
+
+    /// Check `path` and return it cleaned up: `.` components and repeated
+    /// separators are removed, and `..`, a root or a drive prefix are handled
+    /// by the traversal policy unless `allow_absolute` is set. A path starting
+    /// with `\` counts as rooted on every platform. The length limit applies
+    /// to `path` as given, not to the cleaned result.
+    /// Fails with `FileSystemError::PathValidation` for invalid or control
+    /// characters, traversal, rooted and overlong paths, and with
+    /// `FileSystemError::InvalidFileName` for reserved names.
+    pub fn sanitize(&self, path: &Path) -> Result<PathBuf> {
+        let path_str = path.to_string_lossy();
+
+        if let Some(max_length) = self.max_length {
+            if path.as_os_str().len() > max_length {
+                return Err(validation_error(format!("Path exceeds maximum length ({}): {}", max_length, path_str)));
+            }
+        }
+
+        if path_str.chars().any(|c| c.is_control()) {
+            return Err(validation_error(format!("Path contains control characters: {}", path_str)));
+        }
+        if path_str.chars().any(|c| INVALID_PATH_CHARS.contains(&c) && !self.allowed_chars.contains(&c)) {
+            return Err(validation_error(format!("Path contains invalid characters: {}", path_str)));
+        }
+
+        let mut cleaned = PathBuf::new();
+        for (index, component) in path.components().enumerate() {
+            let Component::Normal(name) = component else {
+                match component {
+                    Component::CurDir => {}
+                    Component::ParentDir => self.parent_dir(&path_str)?,
+                    root if self.allow_absolute => cleaned.push(root),
+                    _ => self.root(&path_str)?,
+                }
+                continue;
+            };
+
+            // On Unix a `\` separated entry path is a single component
+            let Some(name) = name.to_str() else {
+                cleaned.push(name);
+                continue;
+            };
+            if index == 0 && name.starts_with('\\') && !self.allow_absolute {
+                self.root(&path_str)?;
+            }
+            for part in name.split('\\').filter(|part| !part.is_empty() && *part != ".") {
+                if part == ".." {
+                    self.parent_dir(&path_str)?;
+                } else if self.is_reserved_name(part) {
+                    return Err(PboError::FileSystem(FileSystemError::InvalidFileName(path_str.into_owned())));
+                } else {
+                    cleaned.push(part);
+                }
+            }
+        }
+
+        Ok(cleaned)
+    }
+
+    fn root(&self, path_str: &str) -> Result<()> {
+        match self.traversal {
+            TraversalPolicy::Reject => Err(validation_error(format!("Path is absolute: {}", path_str))),
+            TraversalPolicy::Strip => {
+                debug!("Stripping root from {}", path_str);
+                Ok(())
+            }
+        }
+    }
+
+    fn parent_dir(&self, path_str: &str) -> Result<()> {
+        match self.traversal {
+            TraversalPolicy::Reject => {
+                Err(validation_error(format!("Path contains parent directory traversal: {}", path_str)))
+            }
+            TraversalPolicy::Strip => {
+                debug!("Stripping parent directory traversal from {}", path_str);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Default for PathSanitizer {
+    fn default() -> Self {
+        Self::from_config(&PboConfig::default())
+    }
+}
+
+fn validation_error(message: String) -> PboError {
+    PboError::FileSystem(FileSystemError::PathValidation(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        let sanitizer = PathSanitizer::default().with_reserved_name_check(false);
+        assert_eq!(
+            sanitizer.sanitize(Path::new("addons/./mirrorform//config.cpp")).unwrap(),
+            Path::new("addons/mirrorform/config.cpp")
+        );
+        assert_eq!(
+            sanitizer.sanitize(Path::new("tc\\mirrorform\\config.cpp")).unwrap(),
+            Path::new("tc/mirrorform/config.cpp")
+        );
+        assert!(sanitizer.sanitize(Path::new("addons/*.paa")).is_err());
+        assert!(sanitizer.sanitize(Path::new("addons/con\0fig.cpp")).is_err());
+        assert!(sanitizer.clone().allow_chars(['*']).sanitize(Path::new("addons/*.paa")).is_ok());
+    }
+
+    #[test]
+    fn test_traversal_policy() {
+        let sanitizer = PathSanitizer::default();
+        assert_eq!(sanitizer.traversal_policy(), TraversalPolicy::Reject);
+        assert!(sanitizer.sanitize(Path::new("../config.cpp")).is_err());
+        assert!(sanitizer.sanitize(Path::new("data\\..\\..\\config.cpp")).is_err());
+        assert!(sanitizer.sanitize(Path::new("data/config..cpp")).is_ok());
+
+        let config = PboConfig::builder().traversal_policy(TraversalPolicy::Strip).build();
+        let sanitizer = PathSanitizer::from_config(&config);
+        assert_eq!(
+            sanitizer.sanitize(Path::new("../..\\data/config.cpp")).unwrap(),
+            Path::new("data/config.cpp")
+        );
+    }
+
+    #[test]
+    fn test_reserved_names() {
+        let sanitizer = PathSanitizer::default().with_reserved_name_check(true);
+        assert!(sanitizer.is_reserved_name("CON"));
+        assert!(sanitizer.is_reserved_name("aux.paa"));
+        assert!(!sanitizer.is_reserved_name("console.sqf"));
+        assert!(matches!(
+            sanitizer.sanitize(Path::new("data/nul.txt")),
+            Err(PboError::FileSystem(FileSystemError::InvalidFileName(_)))
+        ));
+        assert!(!sanitizer.with_reserved_name_check(false).is_reserved_name("CON"));
+    }
+
+    #[test]
+    fn test_max_length() {
+        let path = Path::new("addons").join("a".repeat(300));
+        let sanitizer = PathSanitizer::default();
+        assert!(sanitizer.clone().with_max_length(Some(260)).sanitize(&path).is_err());
+        assert!(sanitizer.clone().with_max_length(None).sanitize(&path).is_ok());
+
+        // The limit applies to the path as given
+        let padded = format!("addons{}config.cpp", "/.".repeat(200));
+        assert!(sanitizer.with_max_length(Some(260)).sanitize(Path::new(&padded)).is_err());
+    }
+
+    #[test]
+    fn test_rooted_paths() {
+        let sanitizer = PathSanitizer::default();
+        assert!(sanitizer.sanitize(Path::new("/etc/passwd")).is_err());
+        assert!(sanitizer.sanitize(Path::new("\\data\\config.cpp")).is_err());
+
+        let strip = sanitizer.clone().with_traversal_policy(TraversalPolicy::Strip);
+        assert_eq!(strip.sanitize(Path::new("/etc/passwd")).unwrap(), Path::new("etc/passwd"));
+        assert_eq!(strip.sanitize(Path::new("\\data\\config.cpp")).unwrap(), Path::new("data/config.cpp"));
+
+        let absolute = std::env::temp_dir().join("out");
+        assert_eq!(sanitizer.allow_absolute(true).sanitize(&absolute).unwrap(), absolute);
+    }
+}
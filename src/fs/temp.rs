@@ -1,32 +1,148 @@
+use std::fs::File;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+use fs2::FileExt;
+use log::{debug, warn};
 use tempfile::{Builder, TempDir};
 use uuid::Uuid;
 use crate::error::types::{Result, FileSystemError, PboError};
+use crate::core::constants::TEMP_DIR_MAX_AGE_SECS;
 
+/// Prefix of the per-process root directories created under the system temp dir
+const ROOT_DIR_PREFIX: &str = "pbo_tools_";
+
+/// File in each root directory holding the owner's PID, locked for as long as
+/// the owning manager lives
+const ROOT_LOCK_FILE: &str = ".lock";
+
+/// A per-process root directory together with its held lock file
+#[derive(Debug)]
+struct LockedRoot {
+    // Declared first so the lock is released before the directory is removed
+    lock: File,
+    dir: TempDir,
+}
+
+/// Tracks temporary directories and removes them when done.
+///
+/// The per-process root directory is only created when the first temp
+/// directory is requested, so a manager that is never used writes nothing.
 #[derive(Debug, Clone)]
 pub struct TempFileManager {
     temp_dirs: Arc<Mutex<HashSet<PathBuf>>>,
-    root_dir: Arc<TempDir>,
+    root_dir: Arc<OnceLock<LockedRoot>>,
+    base: Option<PathBuf>,
+    max_age: Duration,
 }
 
 impl TempFileManager {
     pub fn new() -> Self {
-        let root_dir = Builder::new()
-            .prefix("pbo_tools_")
-            .tempdir()
-            .expect("Failed to create root temp directory");
-            
         Self {
             temp_dirs: Arc::new(Mutex::new(HashSet::new())),
-            root_dir: Arc::new(root_dir),
+            root_dir: Arc::new(OnceLock::new()),
+            base: None,
+            max_age: Duration::from_secs(TEMP_DIR_MAX_AGE_SECS),
         }
     }
 
+    /// Manager whose root directory lives in `base` instead of the system temp
+    /// dir, e.g. a volume with more room than a tmpfs `/tmp`. Orphaned directories
+    /// in `base` are purged once older than `max_age`.
+    pub fn with_base(base: PathBuf, max_age: Duration) -> Self {
+        Self::new_in(&base).with_max_age(max_age)
+    }
+
+    fn new_in(base: &Path) -> Self {
+        let mut manager = Self::new();
+        manager.base = Some(base.to_path_buf());
+        manager
+    }
+
+    /// Directory the per-process root is created in
+    fn base(&self) -> PathBuf {
+        self.base.clone().unwrap_or_else(std::env::temp_dir)
+    }
+
+    fn root_dir(&self) -> Result<&Path> {
+        if let Some(root) = self.root_dir.get() {
+            return Ok(root.dir.path());
+        }
+
+        let base = self.base();
+        let root = Builder::new()
+            .prefix(ROOT_DIR_PREFIX)
+            .tempdir_in(&base)
+            .map_err(|e| PboError::FileSystem(FileSystemError::CreateDir {
+                path: base.clone(),
+                reason: e.to_string(),
+            }))?;
+        debug!("Created temp root directory {:?}", root.path());
+        let lock = lock_root(root.path())?;
+
+        // A clone may have won the race, in which case ours is dropped and removed
+        Ok(self.root_dir.get_or_init(|| LockedRoot { lock, dir: root }).dir.path())
+    }
+
+    /// Only purge orphaned directories older than `max_age`
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Remove temp directories leaked by processes that exited without cleaning up
+    /// (e.g. after a crash). Every `pbo_tools_*` directory in this manager's base
+    /// directory that is older than `max_age` is deleted, unless its lock file is
+    /// still held by a live manager. Returns the number of directories removed.
+    pub fn purge_orphaned(&self) -> Result<usize> {
+        self.purge_orphaned_in(&self.base())
+    }
+
+    fn purge_orphaned_in(&self, base: &Path) -> Result<usize> {
+        let entries = std::fs::read_dir(base).map_err(|e| {
+            PboError::FileSystem(FileSystemError::Read {
+                path: base.to_path_buf(),
+                reason: e.to_string(),
+            })
+        })?;
+
+        let now = SystemTime::now();
+        let mut removed = 0;
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let is_ours = entry.file_name().to_string_lossy().starts_with(ROOT_DIR_PREFIX);
+            let is_own_root = self.root_dir.get().is_some_and(|root| root.dir.path() == path);
+            if !is_ours || is_own_root || !path.is_dir() {
+                continue;
+            }
+
+            let age = entry.metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok());
+            let expired = age.is_some_and(|age| age > self.max_age);
+            if !expired || is_root_in_use(&path) {
+                continue;
+            }
+
+            match std::fs::remove_dir_all(&path) {
+                Ok(()) => {
+                    debug!("Purged orphaned temp directory {:?}", path);
+                    removed += 1;
+                }
+                Err(e) => warn!("Failed to purge orphaned temp directory {:?}: {}", path, e),
+            }
+        }
+
+        Ok(removed)
+    }
+
     pub fn create_temp_dir(&self) -> Result<PathBuf> {
         let unique_name = format!("temp_{}", Uuid::new_v4());
-        let path = self.root_dir.path().join(unique_name);
+        let path = self.root_dir()?.join(unique_name);
         
         std::fs::create_dir_all(&path).map_err(|e| {
             PboError::FileSystem(FileSystemError::CreateDir {
@@ -65,6 +181,38 @@ impl TempFileManager {
     }
 }
 
+/// Create the lock file of the root directory `root`, record our PID in it and
+/// lock it until the returned file is closed
+fn lock_root(root: &Path) -> Result<File> {
+    let path = root.join(ROOT_LOCK_FILE);
+    let write_error = |e: std::io::Error| PboError::FileSystem(FileSystemError::WriteFile {
+        path: path.clone(),
+        reason: e.to_string(),
+    });
+    let mut lock = File::create(&path).map_err(write_error)?;
+    lock.try_lock_exclusive().map_err(write_error)?;
+    write!(lock, "{}", std::process::id()).map_err(write_error)?;
+    Ok(lock)
+}
+
+/// Whether another manager, in this or another process, holds the lock file of
+/// `root`. Roots without a lock file are not in use.
+fn is_root_in_use(root: &Path) -> bool {
+    let Ok(lock) = File::open(root.join(ROOT_LOCK_FILE)) else {
+        return false;
+    };
+    match lock.try_lock_exclusive() {
+        Ok(()) => {
+            let _ = FileExt::unlock(&lock);
+            false
+        }
+        Err(e) => {
+            debug!("Skipping temp directory {:?} in use: {}", root, e);
+            true
+        }
+    }
+}
+
 impl Default for TempFileManager {
     fn default() -> Self {
         Self::new()
@@ -102,6 +250,93 @@ mod tests {
         assert!(!temp_path.exists());
     }
 
+    #[test]
+    fn test_purge_orphaned() {
+        let base = tempfile::tempdir().unwrap();
+        let old = base.path().join("pbo_tools_crashed");
+        let recent = base.path().join("pbo_tools_running");
+        let unrelated = base.path().join("other_tool_dir");
+        for dir in [&old, &recent, &unrelated] {
+            std::fs::create_dir_all(dir.join("temp_1")).unwrap();
+        }
+
+        let two_days_ago = filetime::FileTime::from_system_time(
+            SystemTime::now() - Duration::from_secs(2 * TEMP_DIR_MAX_AGE_SECS)
+        );
+        filetime::set_file_mtime(&old, two_days_ago).unwrap();
+        filetime::set_file_mtime(&unrelated, two_days_ago).unwrap();
+
+        let manager = TempFileManager::new_in(base.path());
+        assert_eq!(manager.purge_orphaned().unwrap(), 1);
+        assert!(!old.exists());
+        assert!(recent.exists());
+        assert!(unrelated.exists());
+
+        let manager = manager.with_max_age(Duration::ZERO);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(manager.purge_orphaned().unwrap(), 1);
+        assert!(!recent.exists());
+    }
+
+    #[test]
+    fn test_purge_skips_own_root() {
+        let base = tempfile::tempdir().unwrap();
+        let manager = TempFileManager::with_base(base.path().to_path_buf(), Duration::ZERO);
+        let temp_dir = manager.create_temp_dir().unwrap();
+        assert!(temp_dir.starts_with(base.path()));
+        thread::sleep(Duration::from_millis(20));
+
+        manager.purge_orphaned().unwrap();
+        assert!(temp_dir.exists());
+        manager.cleanup_temp_dir(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_purge_skips_locked_roots() {
+        let base = tempfile::tempdir().unwrap();
+        let running = TempFileManager::with_base(base.path().to_path_buf(), Duration::ZERO);
+        let temp_dir = running.create_temp_dir().unwrap();
+        let root = temp_dir.parent().unwrap().to_path_buf();
+        let pid = std::fs::read_to_string(root.join(ROOT_LOCK_FILE)).unwrap();
+        assert_eq!(pid, std::process::id().to_string());
+        thread::sleep(Duration::from_millis(20));
+
+        // Another manager, as in a second process, leaves the live root alone
+        let other = TempFileManager::with_base(base.path().to_path_buf(), Duration::ZERO);
+        assert_eq!(other.purge_orphaned().unwrap(), 0);
+        assert!(temp_dir.exists());
+
+        // A root whose lock is no longer held is orphaned
+        let crashed = base.path().join("pbo_tools_crashed");
+        std::fs::create_dir_all(&crashed).unwrap();
+        std::fs::write(crashed.join(ROOT_LOCK_FILE), "1").unwrap();
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(other.purge_orphaned().unwrap(), 1);
+        assert!(!crashed.exists());
+        assert!(temp_dir.exists());
+
+        drop(running);
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn test_root_created_on_first_use() {
+        let base = tempfile::tempdir().unwrap();
+        let missing = base.path().join("missing");
+
+        let manager = TempFileManager::with_base(missing.clone(), Duration::ZERO);
+        assert!(!missing.exists());
+        assert!(matches!(
+            manager.create_temp_dir(),
+            Err(PboError::FileSystem(FileSystemError::CreateDir { .. }))
+        ));
+
+        let manager = TempFileManager::new_in(base.path());
+        assert_eq!(std::fs::read_dir(base.path()).unwrap().count(), 0);
+        let temp_dir = manager.create_temp_dir().unwrap();
+        assert_eq!(manager.create_temp_dir().unwrap().parent(), temp_dir.parent());
+    }
+
     #[test]
     fn test_temp_dir_expiration() {
         let manager = TempFileManager::new();
@@ -1,16 +1,30 @@
-use std::path::Path;
+use std::path::{Component, Path, Prefix};
 use std::fs::{create_dir_all, remove_dir_all, remove_file};
 use log::debug;
+use crate::core::config::PboConfig;
+use crate::core::constants::SUSPICIOUS_PATH_CHARS;
 use crate::error::types::{Result, PboError, FileSystemError};
+use super::sanitizer::{PathSanitizer, TraversalPolicy};
 
 pub trait FileOperation {
     fn validate_path(&self) -> Result<()>;
     fn ensure_parent_exists(&self) -> Result<()>;
     fn remove_if_exists(&self) -> Result<()>;
     fn is_safe_path(&self) -> bool;
+    /// Like `is_safe_path`, accepting the special characters `config` allows.
+    /// The default ignores `config` and runs `is_safe_path`.
+    fn is_safe_path_with(&self, _config: &PboConfig) -> bool {
+        self.is_safe_path()
+    }
     fn ensure_directory(&self) -> Result<()>;
     fn validate_filename(&self) -> Result<()>;
     fn validate_path_safety(&self) -> Result<()>;
+    /// Like `validate_path_safety`, with the rules `PathSanitizer::from_config`
+    /// builds from `config`. The default ignores `config` and runs
+    /// `validate_path_safety`, so existing implementations keep compiling.
+    fn validate_path_safety_with(&self, _config: &PboConfig) -> Result<()> {
+        self.validate_path_safety()
+    }
 }
 
 impl FileOperation for Path {
@@ -55,6 +69,10 @@ impl FileOperation for Path {
     }
 
     fn is_safe_path(&self) -> bool {
+        self.is_safe_path_with(&PboConfig::default())
+    }
+
+    fn is_safe_path_with(&self, config: &PboConfig) -> bool {
         let path_str = self.to_str().unwrap_or("");
         debug!("Checking path safety for: {}", path_str);
 
@@ -65,42 +83,29 @@ impl FileOperation for Path {
         }
 
         // Check for suspicious characters in path
-        let suspicious_chars = ['<', '>', '|', '*', '?', '"', '`', '$', '&', '{', '}', ';', '#', '='];
-        if path_str.chars().any(|c| suspicious_chars.contains(&c)) {
+        if path_str.chars().any(|c| config.rejects_char(c, SUSPICIOUS_PATH_CHARS)) {
             debug!("Rejecting path with suspicious characters");
             return false;
         }
 
-        // Handle Windows paths
-        #[cfg(windows)]
-        {
-            // Allow only drive letter colons (C:\)
-            let colon_positions: Vec<_> = path_str.match_indices(':').map(|(i, _)| i).collect();
-            match colon_positions.len() {
-                0 => (),  // No colons is fine
-                1 if colon_positions[0] == 1 => (), // Single colon at position 1 is fine (drive letter)
-                _ => {
+        // Colons are only allowed in a drive prefix (C:\), which Windows alone
+        // parses as such, and UNC paths are rejected outright
+        for component in self.components() {
+            match component {
+                Component::Prefix(prefix) if !matches!(prefix.kind(), Prefix::Disk(_)) => {
+                    debug!("Rejecting UNC or verbatim path");
+                    return false;
+                }
+                Component::Normal(name) if name.to_string_lossy().contains(':') => {
                     debug!("Rejecting path with invalid colon placement");
                     return false;
                 }
-            }
-
-            // Reject UNC paths and other potentially unsafe Windows paths
-            if path_str.starts_with("\\\\") || path_str.contains("://") {
-                debug!("Rejecting UNC or URL-like path");
-                return false;
+                _ => {}
             }
         }
 
-        #[cfg(not(windows))]
-        if path_str.contains(':') {
-            debug!("Rejecting path with colon on non-Windows system");
-            return false;
-        }
-
-        // Check for null bytes and control characters
-        if path_str.chars().any(|c| c.is_control()) {
-            debug!("Rejecting path with control characters");
+        if let Err(e) = self.validate_path_safety_with(config) {
+            debug!("Rejecting path: {}", e);
             return false;
         }
 
@@ -127,60 +132,36 @@ impl FileOperation for Path {
     fn validate_filename(&self) -> Result<()> {
         if let Some(filename) = self.file_name() {
             let filename_str = filename.to_string_lossy();
+            let invalid = || PboError::FileSystem(FileSystemError::InvalidFileName(
+                self.to_string_lossy().into_owned()
+            ));
+
             // Check for reserved filenames on Windows
-            #[cfg(windows)]
-            {
-                let reserved = [
-                    "CON", "PRN", "AUX", "NUL",
-                    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
-                    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9"
-                ];
-                
-                if reserved.iter().any(|&r| filename_str.eq_ignore_ascii_case(r)) {
-                    return Err(PboError::FileSystem(FileSystemError::InvalidFileName(
-                        self.to_string_lossy().into_owned()
-                    )));
-                }
+            if PathSanitizer::default().is_reserved_name(&filename_str) {
+                return Err(invalid());
             }
 
             // Check for dots and spaces
             if filename_str.starts_with('.') || filename_str.ends_with('.') || 
                filename_str.starts_with(' ') || filename_str.ends_with(' ') {
-                return Err(PboError::FileSystem(FileSystemError::InvalidFileName(
-                    self.to_string_lossy().into_owned()
-                )));
+                return Err(invalid());
             }
         }
         Ok(())
     }
 
     fn validate_path_safety(&self) -> Result<()> {
-        // Convert path to string for validation
-        let path_str = self.to_string_lossy();
-
-        // Check for invalid characters
-        let invalid_chars = ['<', '>', '|', '"', '*', '?'];
-        if path_str.contains(&invalid_chars[..]) {
-            return Err(PboError::FileSystem(FileSystemError::PathValidation(
-                format!("Path contains invalid characters: {}", path_str)
-            )));
-        }
-
-        // Check for reasonable path length
-        if path_str.len() > 260 {
-            return Err(PboError::FileSystem(FileSystemError::PathValidation(
-                format!("Path exceeds maximum length (260): {}", path_str)
-            )));
-        }
-
-        // Check for parent directory traversal
-        if path_str.contains("..") {
-            return Err(PboError::FileSystem(FileSystemError::PathValidation(
-                format!("Path contains parent directory traversal: {}", path_str)
-            )));
-        }
+        self.validate_path_safety_with(&PboConfig::default())
+    }
 
-        Ok(())
+    fn validate_path_safety_with(&self, config: &PboConfig) -> Result<()> {
+        // The caller keeps using the path as is, so traversal is never stripped,
+        // and it may be an absolute path on the local file system
+        PathSanitizer::from_config(config)
+            .with_traversal_policy(TraversalPolicy::Reject)
+            .allow_absolute(true)
+            .sanitize(self)
+            .map(|_| ())
     }
 }
 
@@ -200,6 +181,36 @@ mod tests {
         assert!(!Path::new("//test.txt").is_safe_path());
     }
 
+    #[test]
+    fn test_allowed_special_chars() {
+        let prefix_file = Path::new("addons/mirrorform/$PBOPREFIX$.txt");
+        assert!(!prefix_file.is_safe_path());
+
+        let config = PboConfig::builder().allow_special_chars(['$']).build();
+        assert!(prefix_file.is_safe_path_with(&config));
+        assert!(!Path::new("addons/$x$/../config.cpp").is_safe_path_with(&config));
+        assert!(!Path::new("addons/a&b.txt").is_safe_path_with(&config));
+
+        let wildcard = Path::new("addons/*.paa");
+        assert!(wildcard.validate_path_safety().is_err());
+        let config = PboConfig::builder().allow_special_chars(['*']).build();
+        assert!(wildcard.validate_path_safety_with(&config).is_ok());
+    }
+
+    #[test]
+    fn test_path_length_limit() {
+        let long_path = Path::new("addons").join("a".repeat(300)).join("config.cpp");
+
+        let limited = PboConfig::builder().max_path_length(260).build();
+        assert!(long_path.validate_path_safety_with(&limited).is_err());
+
+        let relaxed = PboConfig::builder().max_path_length(1024).build();
+        assert!(long_path.validate_path_safety_with(&relaxed).is_ok());
+
+        #[cfg(not(windows))]
+        assert!(long_path.validate_path_safety().is_ok());
+    }
+
     #[test]
     fn test_directory_operations() {
         let temp = tempdir().unwrap();
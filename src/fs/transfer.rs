@@ -0,0 +1,156 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use log::{debug, trace};
+use walkdir::WalkDir;
+use crate::error::types::{Result, PboError};
+use crate::extract::normalize_prefix;
+use crate::fs_err;
+use super::traits::FileOperation;
+
+/// Move every file below `source` to the same relative path below `destination`,
+/// replacing existing files. Renames fall back to copy and remove, e.g. when the
+/// two directories are on different devices.
+pub fn move_dir_contents(source: &Path, destination: &Path) -> Result<()> {
+    debug!("Moving contents of {:?} to {:?}", source, destination);
+
+    for entry in WalkDir::new(source).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let from = entry.path();
+        let relative = from.strip_prefix(source)
+            .map_err(|_| PboError::InvalidPath(from.to_path_buf()))?;
+        move_file(from, &destination.join(relative))?;
+    }
+
+    Ok(())
+}
+
+/// Move the files below the `old` prefix folders in `root` to the same relative
+/// paths below `new`, e.g. from `root/tc/mirrorform` to `root/my_mod/addons`,
+/// and remove the `old` folders left empty. Prefixes may use either separator
+/// and an empty `new` moves the files into `root` itself. Returns the number
+/// of files moved; nothing is moved when `root/old` does not exist.
+pub fn reprefix_directory(root: &Path, old: &str, new: &str) -> Result<usize> {
+    let old = prefix_path(old)?;
+    if old.as_os_str().is_empty() {
+        return Err(PboError::ValidationFailed("Prefix to move from cannot be empty".to_string()));
+    }
+    let source_root = root.join(old);
+    let target_root = root.join(prefix_path(new)?);
+    if source_root == target_root || !source_root.is_dir() {
+        return Ok(0);
+    }
+    debug!("Moving prefix contents from {:?} to {:?}", source_root, target_root);
+
+    // Collect first, since the target may lie inside the source tree
+    let files: Vec<PathBuf> = WalkDir::new(&source_root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+
+    for source in &files {
+        let relative = source.strip_prefix(&source_root)
+            .map_err(|_| PboError::InvalidPath(source.clone()))?;
+        move_file(source, &target_root.join(relative))?;
+    }
+
+    remove_empty_dirs(&source_root, root)?;
+    Ok(files.len())
+}
+
+/// Move a single file to `to`, creating its parent folders and replacing an
+/// existing file
+pub(crate) fn move_file(from: &Path, to: &Path) -> Result<()> {
+    trace!("Moving {:?} -> {:?}", from, to);
+
+    to.ensure_parent_exists()?;
+    if let Err(e) = fs::rename(from, to) {
+        trace!("Rename failed ({}), copying instead", e);
+        fs_err!(fs::copy(from, to) => WriteFile, to)?;
+        fs_err!(fs::remove_file(from) => Delete, from)?;
+    }
+    Ok(())
+}
+
+/// Relative path of a PBO prefix, rejecting prefixes that would leave the root:
+/// only plain folder names are allowed, no `..`, root or drive components
+pub(crate) fn prefix_path(prefix: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(normalize_prefix(prefix));
+    if path.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(PboError::ValidationFailed(format!("Invalid prefix: {}", prefix)));
+    }
+    Ok(path)
+}
+
+/// Remove empty directories below and including `dir`, then its ancestors up
+/// to (but excluding) `stop_at` while they are empty
+fn remove_empty_dirs(dir: &Path, stop_at: &Path) -> Result<()> {
+    for entry in WalkDir::new(dir).contents_first(true).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_type().is_dir() && is_empty_dir(entry.path()) {
+            fs_err!(fs::remove_dir(entry.path()) => RemoveDir, entry.path())?;
+        }
+    }
+
+    let mut parent = dir.parent();
+    while let Some(current) = parent {
+        if current == stop_at || !current.starts_with(stop_at) || !is_empty_dir(current) {
+            break;
+        }
+        fs_err!(fs::remove_dir(current) => RemoveDir, current)?;
+        parent = current.parent();
+    }
+    Ok(())
+}
+
+fn is_empty_dir(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_move_dir_contents() {
+        let source = tempdir().unwrap();
+        let destination = tempdir().unwrap();
+        fs::create_dir_all(source.path().join("tc/mirrorform")).unwrap();
+        fs::write(source.path().join("tc/mirrorform/config.cpp"), "new").unwrap();
+        fs::write(source.path().join("$PBOPREFIX$.txt"), "tc\\mirrorform").unwrap();
+        fs::create_dir_all(destination.path().join("tc/mirrorform")).unwrap();
+        fs::write(destination.path().join("tc/mirrorform/config.cpp"), "old").unwrap();
+        fs::write(destination.path().join("other.txt"), "kept").unwrap();
+
+        move_dir_contents(source.path(), destination.path()).unwrap();
+
+        assert_eq!(fs::read_to_string(destination.path().join("tc/mirrorform/config.cpp")).unwrap(), "new");
+        assert!(destination.path().join("$PBOPREFIX$.txt").exists());
+        assert!(destination.path().join("other.txt").exists());
+        assert!(!source.path().join("tc/mirrorform/config.cpp").exists());
+    }
+
+    #[test]
+    fn test_reprefix_directory() {
+        let root = tempdir().unwrap();
+        fs::create_dir_all(root.path().join("tc/mirrorform/uniform")).unwrap();
+        fs::write(root.path().join("tc/mirrorform/config.cpp"), "class CfgPatches {};").unwrap();
+        fs::write(root.path().join("tc/mirrorform/uniform/mirror.p3d"), "p3d").unwrap();
+        fs::write(root.path().join("$PBOPREFIX$.txt"), "tc\\mirrorform").unwrap();
+
+        let moved = reprefix_directory(root.path(), "tc\\mirrorform", "my_mod/addons").unwrap();
+        assert_eq!(moved, 2);
+        assert!(root.path().join("my_mod/addons/config.cpp").exists());
+        assert!(root.path().join("my_mod/addons/uniform/mirror.p3d").exists());
+        assert!(root.path().join("$PBOPREFIX$.txt").exists());
+        assert!(!root.path().join("tc").exists());
+
+        assert_eq!(reprefix_directory(root.path(), "tc\\mirrorform", "other").unwrap(), 0);
+        assert!(reprefix_directory(root.path(), "my_mod", "..\\outside").is_err());
+        assert!(reprefix_directory(root.path(), "", "other").is_err());
+    }
+}
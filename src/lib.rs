@@ -3,17 +3,23 @@ pub mod core;
 pub mod error;
 pub mod extract;
 pub mod fs;
+pub mod pack;
 #[cfg(test)]
 pub mod test_utils;
 
 // Re-export commonly used types for easier access
 pub use core::{
-    api::{PboApi, PboApiOps},
+    api::{PboApi, PboApiGeneric, PboApiOps},
     config::PboConfig,
+    diff::PboDiff,
+    tools::ToolAvailability,
     constants::{DEFAULT_TIMEOUT, DEFAULT_MAX_RETRIES},
+    reader::{NativePboReader, PboEntry, HeaderEntry, EntryContents},
+    signature::BisignVersion,
 };
 pub use error::types::{PboError, ExtractError, FileSystemError, Result};
-pub use extract::{ExtractOptions, ExtractResult};
+pub use extract::{normalize_prefix, ExtractManifest, ExtractOptions, ExtractOutcome, ExtractResult, ExtractSummary, FilterSyntax, HybridExtractor, NativeExtractor, OperationMetrics, OutputLayout, OverwritePolicy};
+pub use pack::{PackOptions, PboPackOps};
 
 /// Version of the library
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
\ No newline at end of file
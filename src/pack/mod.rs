@@ -0,0 +1,3 @@
+mod packer;
+
+pub use packer::{DefaultPacker, PackOptions, PboPackOps};
@@ -0,0 +1,275 @@
+use std::path::{Path, PathBuf};
+use std::fmt::Debug;
+use std::io;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use log::{debug, trace};
+use crate::error::types::{Result, PboError, ExtractError, FileSystemError};
+use crate::core::config::PboConfig;
+use crate::core::extensions::is_pbo_path;
+use crate::core::tools::find_program;
+use crate::extract::ExtractResult;
+use crate::extract::process::{self, ProcessOutcome};
+
+/// MakePbo Command Line Interface
+///
+/// Syntax: makepbo [-options...] SourceFolder [destination[.pbo]]
+///
+/// As with extractpbo, options must come before the source folder and the
+/// source folder before the destination.
+///
+/// Options used by this crate:
+/// - `-P`: Don't pause execution
+/// - `-N`: Noisy (verbose) output
+/// - `-U`: Don't binarize configs and rvmats
+/// - `-X=pattern[,...]`: Exclude files matching the patterns
+/// - `-@=prefix`: Set the `$PBOPREFIX$` of the packed PBO
+#[derive(Debug, Clone, Default)]
+pub struct PackOptions {
+    /// Prefix written as the PBO's `$PBOPREFIX$` (-@=prefix)
+    pub prefix: Option<String>,
+    /// Patterns for files to leave out of the PBO (-X=pattern[,...])
+    pub exclude: Vec<String>,
+    /// Binarize configs and rvmats while packing (omits -U)
+    pub binarize: bool,
+    /// Noisy (verbose) output (-N)
+    pub verbose: bool,
+}
+
+impl PackOptions {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(prefix) = &self.prefix {
+            if prefix.trim().is_empty() || prefix.contains(['<', '>', '|', '"', '\'']) {
+                return Err(PboError::ValidationFailed(format!("Invalid PBO prefix: {}", prefix)));
+            }
+        }
+
+        for pattern in &self.exclude {
+            if pattern.trim().is_empty() || pattern.contains([',', '<', '>', '|', '"', '\'']) {
+                return Err(PboError::ValidationFailed(format!("Invalid exclude pattern: {}", pattern)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Operations that create PBOs
+pub trait PboPackOps {
+    /// Pack `source_dir` into `output_pbo` using makepbo
+    fn pack(&self, source_dir: &Path, output_pbo: &Path, options: PackOptions) -> Result<ExtractResult>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DefaultPacker {
+    config: Arc<PboConfig>,
+    makepbo_path: Option<PathBuf>,
+    timeout: Option<Duration>,
+}
+
+impl DefaultPacker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: Arc<PboConfig>) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Run the makepbo binary at `path` instead of looking it up on PATH
+    pub fn with_makepbo_path(mut self, path: PathBuf) -> Self {
+        self.makepbo_path = Some(path);
+        self
+    }
+
+    /// Kill makepbo if it runs longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Whether the makepbo binary exists, at the overridden path or on PATH
+    pub fn is_available(&self) -> bool {
+        find_program(&self.program_name()).is_some()
+    }
+
+    fn program_name(&self) -> String {
+        self.makepbo_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "makepbo".to_string())
+    }
+
+    /// Build the makepbo argument list: options, source folder, destination
+    pub(crate) fn build_args(&self, source_dir: &Path, output_pbo: &Path, options: &PackOptions) -> Result<Vec<String>> {
+        if !source_dir.is_dir() {
+            return Err(PboError::InvalidPath(source_dir.to_path_buf()));
+        }
+
+        if !is_pbo_path(output_pbo) {
+            return Err(PboError::InvalidFormat(format!(
+                "File {} does not have a valid PBO extension",
+                output_pbo.display()
+            )));
+        }
+
+        options.validate()?;
+
+        // 1. Options
+        let mut opts = String::from("P");
+        if options.verbose { opts.push('N'); }
+        if !options.binarize { opts.push('U'); }
+        let mut argv = vec![format!("-{}", opts)];
+
+        if !options.exclude.is_empty() {
+            argv.push(format!("-X={}", options.exclude.join(",")));
+        }
+        if let Some(prefix) = &options.prefix {
+            argv.push(format!("-@={}", prefix.trim()));
+        }
+
+        // 2. Source folder, 3. destination
+        for path in [source_dir, output_pbo] {
+            match path.to_str() {
+                Some(s) => argv.push(s.replace("\\\\?\\", "")),
+                None => return Err(PboError::InvalidPath(path.to_path_buf())),
+            }
+        }
+
+        Ok(argv)
+    }
+
+    fn spawn_error(&self, e: io::Error) -> PboError {
+        match e.kind() {
+            io::ErrorKind::NotFound => PboError::CommandNotFound(self.program_name()),
+            io::ErrorKind::PermissionDenied =>
+                PboError::FileSystem(FileSystemError::PathValidation(
+                    "Permission denied".to_string()
+                )),
+            _ => PboError::Extraction(ExtractError::CommandFailed {
+                cmd: self.program_name(),
+                reason: e.to_string(),
+            })
+        }
+    }
+}
+
+impl PboPackOps for DefaultPacker {
+    fn pack(&self, source_dir: &Path, output_pbo: &Path, options: PackOptions) -> Result<ExtractResult> {
+        debug!("DefaultPacker::pack called");
+        debug!("Source dir: {:?}", source_dir);
+        debug!("Output PBO: {:?}", output_pbo);
+        debug!("Options: {:?}", options);
+
+        let args = self.build_args(source_dir, output_pbo, &options)?;
+
+        if let Some(path) = &self.makepbo_path {
+            if !path.exists() {
+                return Err(PboError::CommandNotFound(path.display().to_string()));
+            }
+        }
+
+        if let Some(parent) = output_pbo.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| FileSystemError::CreateDir {
+                path: parent.to_path_buf(),
+                reason: e.to_string(),
+            })?;
+        }
+
+        let mut command = Command::new(self.program_name());
+        command.args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        trace!("Full command: {:?}", command);
+
+        let child = command.spawn().map_err(|e| self.spawn_error(e))?;
+        let output = match process::wait_with_timeout(child, self.timeout, &mut |_| {}) {
+            Ok(ProcessOutcome::Exited(output)) => output,
+            Ok(ProcessOutcome::TimedOut) => return Err(PboError::Timeout(self.timeout.unwrap_or_default())),
+            Err(e) => return Err(self.spawn_error(e)),
+        };
+        trace!("Command completed with status: {:?}", output.status);
+
+        Ok(ExtractResult {
+            return_code: output.status.code().unwrap_or(-1),
+            stdout: output.stdout,
+            stderr: output.stderr,
+            config: self.config.clone(),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pack_args_order() {
+        let source = tempdir().unwrap();
+        let output = source.path().join("out/packed.pbo");
+        let options = PackOptions {
+            prefix: Some("tc\\mirrorform".to_string()),
+            exclude: vec!["*.txt".to_string(), "thumbs.db".to_string()],
+            ..Default::default()
+        };
+
+        let args = DefaultPacker::new().build_args(source.path(), &output, &options).unwrap();
+        assert_eq!(args[0], "-PU");
+        assert_eq!(args[1], "-X=*.txt,thumbs.db");
+        assert_eq!(args[2], "-@=tc\\mirrorform");
+        assert_eq!(args[3], source.path().to_str().unwrap());
+        assert_eq!(args[4], output.to_str().unwrap());
+    }
+
+    #[test]
+    fn test_pack_binarize_flag() {
+        let source = tempdir().unwrap();
+        let output = source.path().join("packed.ifa");
+        let options = PackOptions {
+            binarize: true,
+            verbose: true,
+            ..Default::default()
+        };
+
+        let args = DefaultPacker::new().build_args(source.path(), &output, &options).unwrap();
+        assert_eq!(args[0], "-PN");
+        assert_eq!(args.len(), 3);
+    }
+
+    #[test]
+    fn test_missing_makepbo() {
+        let error = DefaultPacker::new().spawn_error(io::Error::new(io::ErrorKind::NotFound, "not found"));
+        assert!(matches!(error, PboError::CommandNotFound(cmd) if cmd == "makepbo"));
+    }
+
+    #[test]
+    fn test_pack_validation() {
+        let source = tempdir().unwrap();
+        let packer = DefaultPacker::new();
+
+        let missing = source.path().join("missing");
+        assert!(matches!(
+            packer.build_args(&missing, &source.path().join("a.pbo"), &PackOptions::default()),
+            Err(PboError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            packer.build_args(source.path(), &source.path().join("a.zip"), &PackOptions::default()),
+            Err(PboError::InvalidFormat(_))
+        ));
+
+        let options = PackOptions {
+            exclude: vec!["a,b".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            packer.build_args(source.path(), &source.path().join("a.pbo"), &options),
+            Err(PboError::ValidationFailed(_))
+        ));
+    }
+}
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 use std::sync::Once;
 use env_logger;
 use log::LevelFilter;
+use crate::core::constants::PACKING_METHOD_VERSION;
 
 static INIT: Once = Once::new();
 
@@ -23,6 +24,35 @@ pub fn get_test_data_dir() -> PathBuf {
     PathBuf::from("tests/data")
 }
 
+/// Build an uncompressed PBO (without checksum) from properties and entries
+pub fn build_pbo(properties: &[(&str, &str)], files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut data = vec![0u8];
+    data.extend(PACKING_METHOD_VERSION.to_le_bytes());
+    data.extend([0u8; 16]);
+    for (key, value) in properties {
+        data.extend(key.as_bytes());
+        data.push(0);
+        data.extend(value.as_bytes());
+        data.push(0);
+    }
+    data.push(0);
+    for (name, content) in files {
+        data.extend(name.as_bytes());
+        data.push(0);
+        let size = (content.len() as u32).to_le_bytes();
+        data.extend(0u32.to_le_bytes());
+        data.extend(size);
+        data.extend(0u32.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(size);
+    }
+    data.extend([0u8; 21]);
+    for (_, content) in files {
+        data.extend(*content);
+    }
+    data
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,66 @@
+#![cfg(feature = "async")]
+
+use pbo_tools::core::PboApi;
+use pbo_tools::error::types::PboError;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_list_contents_async() {
+    let api = PboApi::new(30);
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+
+    let result = api.list_contents_async(test_pbo).await.unwrap();
+    assert!(result.is_success());
+    assert!(!result.get_file_list().is_empty());
+}
+
+#[tokio::test]
+async fn test_async_invalid_pbo_path() {
+    let api = PboApi::new(30);
+    let nonexistent = PathBuf::from("nonexistent.pbo");
+
+    match api.list_contents_async(&nonexistent).await {
+        Err(PboError::InvalidPath(path)) => assert_eq!(path, nonexistent),
+        other => panic!("Expected InvalidPath error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_async_missing_extractpbo() {
+    let api = PboApi::builder()
+        .with_extractpbo_path(PathBuf::from("/nonexistent/mikero/extractpbo"))
+        .build();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let temp_dir = TempDir::new().unwrap();
+
+    let result = api.extract_with_options_async(test_pbo, temp_dir.path(), Default::default()).await;
+    assert!(matches!(result, Err(PboError::CommandNotFound(_))));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_list_with_options_async_timeout() {
+    use pbo_tools::extract::ExtractOptions;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    let temp_dir = TempDir::new().unwrap();
+    let script = temp_dir.path().join("extractpbo");
+    std::fs::write(&script, "#!/bin/sh\nexec sleep 10\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_timeout(30)
+        .build();
+
+    let options = ExtractOptions {
+        timeout: Some(Duration::from_millis(500)),
+        ..ExtractOptions::for_listing()
+    };
+    let result = api.list_with_options_async(Path::new("tests/data/mirrorform.pbo"), options).await;
+    assert!(
+        matches!(result, Err(PboError::Timeout(timeout)) if timeout == Duration::from_millis(500)),
+        "Expected timeout, got {:?}", result
+    );
+}
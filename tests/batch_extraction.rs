@@ -0,0 +1,69 @@
+#![cfg(unix)]
+
+use pbo_tools::core::PboApi;
+use pbo_tools::error::types::PboError;
+use pbo_tools::extract::ExtractOptions;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Write an executable script standing in for extractpbo
+fn fake_extractpbo(dir: &Path, body: &str) -> PathBuf {
+    let script = dir.join("extractpbo");
+    fs::write(&script, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+#[test]
+fn test_extract_many_collects_each_result() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(temp_dir.path(), "echo 'Extracting config.bin...'");
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_concurrency(2)
+        .build();
+
+    let jobs = vec![
+        (PathBuf::from("tests/data/mirrorform.pbo"), temp_dir.path().join("a")),
+        (PathBuf::from("tests/data/missing.pbo"), temp_dir.path().join("b")),
+        (PathBuf::from("tests/data/headgear_pumpkin.pbo"), temp_dir.path().join("c")),
+    ];
+    let results = api.extract_many(&jobs, ExtractOptions::for_extraction());
+
+    assert_eq!(results.len(), 3);
+    for ((pbo_path, _), (result_path, _)) in jobs.iter().zip(&results) {
+        assert_eq!(pbo_path, result_path);
+    }
+    assert!(results[0].1.as_ref().unwrap().is_success());
+    assert!(matches!(results[1].1, Err(PboError::InvalidPath(_))));
+    assert!(results[2].1.as_ref().unwrap().is_success());
+}
+
+#[test]
+fn test_extract_many_respects_concurrency() {
+    let temp_dir = TempDir::new().unwrap();
+    let running = temp_dir.path().join("running");
+    fs::create_dir(&running).unwrap();
+    let script = fake_extractpbo(temp_dir.path(), &format!(
+        "touch '{dir}/'$$\nls '{dir}' | wc -l >> '{log}'\nsleep 0.2\nrm '{dir}/'$$",
+        dir = running.display(),
+        log = temp_dir.path().join("concurrency.log").display(),
+    ));
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_concurrency(2)
+        .build();
+
+    let jobs: Vec<(PathBuf, PathBuf)> = (0..6)
+        .map(|i| (PathBuf::from("tests/data/mirrorform.pbo"), temp_dir.path().join(format!("out{}", i))))
+        .collect();
+    let results = api.extract_many(&jobs, ExtractOptions::for_extraction());
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+    let log = fs::read_to_string(temp_dir.path().join("concurrency.log")).unwrap();
+    let counts: Vec<usize> = log.lines().map(|line| line.trim().parse().unwrap()).collect();
+    assert_eq!(counts.len(), 6);
+    assert!(counts.iter().all(|&count| count <= 2), "More than 2 extractions ran at once: {:?}", counts);
+}
@@ -1,5 +1,8 @@
 use pbo_tools::core::PboConfig;
-use pbo_tools::fs::{convert_binary_file, process_binary_files};
+use pbo_tools::fs::{
+    convert_binary_file, convert_binary_file_keep, process_binary_files, process_binary_files_with_options,
+    process_binary_files_with_progress, rename_bins_in_dir,
+};
 use std::path::Path;
 use tempfile::TempDir;
 use std::fs;
@@ -108,4 +111,110 @@ fn test_binary_conversion_with_custom_mappings() {
 
     assert!(!input_path.exists(), "Binary file should be removed: {:?}", input_path);
     assert!(output_path.exists(), "Target file should exist: {:?}", output_path);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_binary_processing_with_progress() {
+    init();
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path();
+    fs::create_dir(source_dir.join("nested")).unwrap();
+    fs::write(source_dir.join("config.bin"), "content").unwrap();
+    fs::write(source_dir.join("nested/script.bin"), "content").unwrap();
+    fs::write(source_dir.join("readme.txt"), "not mapped").unwrap();
+
+    let config = PboConfig::builder()
+        .add_bin_mapping("config.bin", "cpp")
+        .add_bin_mapping("script.bin", "cpp")
+        .build();
+
+    let mut calls = Vec::new();
+    process_binary_files_with_progress(source_dir, &config, &mut |path, index, total| {
+        debug!("Converting {}/{}: {:?}", index, total, path);
+        calls.push((path.file_name().unwrap().to_string_lossy().into_owned(), index, total));
+    }).unwrap();
+
+    assert_eq!(calls.len(), 2);
+    assert!(calls.iter().all(|(_, _, total)| *total == 2));
+    let mut indices: Vec<_> = calls.iter().map(|(_, index, _)| *index).collect();
+    indices.sort();
+    assert_eq!(indices, vec![1, 2]);
+    assert!(calls.iter().any(|(name, _, _)| name == "config.bin"));
+    assert!(calls.iter().any(|(name, _, _)| name == "script.bin"));
+
+    assert!(source_dir.join("config.cpp").exists());
+    assert!(source_dir.join("nested/script.cpp").exists());
+    assert!(source_dir.join("readme.txt").exists());
+}
+
+#[test]
+fn test_binary_file_conversion_keep_original() {
+    init();
+    let temp_dir = TempDir::new().unwrap();
+    let input_path = temp_dir.path().join("config.bin");
+    let output_path = temp_dir.path().join("config.cpp");
+    fs::write(&input_path, "\0raP content").unwrap();
+
+    convert_binary_file_keep(&input_path, &output_path).unwrap();
+
+    assert!(input_path.exists(), "Original file should be kept: {:?}", input_path);
+    assert_eq!(fs::read(&output_path).unwrap(), fs::read(&input_path).unwrap());
+}
+
+#[test]
+fn test_binary_processing_keep_original() {
+    init();
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path();
+    fs::create_dir(source_dir.join("nested")).unwrap();
+    fs::write(source_dir.join("config.bin"), "binarized").unwrap();
+    fs::write(source_dir.join("nested/model.bin"), "binarized").unwrap();
+    // Already debinarized by extractpbo; must not be overwritten
+    fs::write(source_dir.join("config.cpp"), "class CfgPatches {};").unwrap();
+
+    let mut converted = Vec::new();
+    process_binary_files_with_options(source_dir, &PboConfig::default(), true, &mut |path, _, _| {
+        converted.push(path.file_name().unwrap().to_string_lossy().into_owned());
+    }).unwrap();
+
+    assert_eq!(converted, vec!["model.bin"]);
+    assert!(source_dir.join("config.bin").exists());
+    assert_eq!(fs::read_to_string(source_dir.join("config.cpp")).unwrap(), "class CfgPatches {};");
+    assert!(source_dir.join("nested/model.bin").exists());
+    assert!(source_dir.join("nested/model.cfg").exists());
+}
+
+#[test]
+fn test_rename_bins_in_dir_reports_renames() {
+    init();
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path();
+    fs::write(source_dir.join("config.bin"), "binarized").unwrap();
+    fs::write(source_dir.join("readme.txt"), "text").unwrap();
+
+    let renamed = rename_bins_in_dir(source_dir, &PboConfig::default()).unwrap();
+
+    assert_eq!(renamed, vec![(source_dir.join("config.bin"), source_dir.join("config.cpp"))]);
+    assert!(rename_bins_in_dir(&source_dir.join("missing"), &PboConfig::default()).unwrap().is_empty());
+}
+
+#[test]
+fn test_sniff_bin_content() {
+    init();
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path();
+    fs::write(source_dir.join("config"), b"\0raP\0\0\0\0").unwrap();
+    fs::write(source_dir.join("readme"), "text").unwrap();
+    fs::write(source_dir.join("empty"), "").unwrap();
+    fs::write(source_dir.join("data.rvmat"), b"\0raP\0\0\0\0").unwrap();
+
+    // Off by default
+    assert!(process_binary_files(source_dir, &PboConfig::default()).unwrap().is_empty());
+
+    let config = PboConfig::builder().sniff_bin_content(true).build();
+    let converted = process_binary_files(source_dir, &config).unwrap();
+    assert_eq!(converted, vec![(source_dir.join("config"), source_dir.join("config.cpp"))]);
+    assert!(source_dir.join("config.cpp").exists());
+    assert!(source_dir.join("readme").exists());
+    assert!(source_dir.join("data.rvmat").exists());
+}
@@ -1,6 +1,7 @@
 use pbo_tools::core::{PboApi, PboApiOps};
-use pbo_tools::extract::ExtractOptions;
-use pbo_tools::error::types::PboError;
+use pbo_tools::extract::{ExtractOptions, FilterSyntax, OverwritePolicy};
+use pbo_tools::error::types::{PboError, ExtractError, FileSystemError};
+use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
@@ -69,4 +70,78 @@ fn test_validation_failures() {
         }
         other => panic!("Expected ValidationFailed error, got {:?}", other),
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_missing_extractpbo_path_override() {
+    let missing_tool = PathBuf::from("/nonexistent/mikero/extractpbo");
+    let api = PboApi::builder()
+        .with_extractpbo_path(missing_tool.clone())
+        .build();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+
+    match api.list_contents(test_pbo) {
+        Err(PboError::CommandNotFound(msg)) => {
+            assert_eq!(msg, missing_tool.display().to_string());
+        }
+        other => panic!("Expected CommandNotFound error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_invalid_regex_file_filter() {
+    let api = PboApi::new(30);
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let temp_dir = TempDir::new().unwrap();
+
+    let options = ExtractOptions {
+        file_filter: Some("*.paa(".to_string()),
+        filter_syntax: FilterSyntax::Regex,
+        ..Default::default()
+    };
+
+    match api.extract_with_options(test_pbo, temp_dir.path(), options) {
+        Err(PboError::ValidationFailed(msg)) => {
+            assert!(msg.contains("Invalid file filter pattern"));
+        }
+        other => panic!("Expected ValidationFailed error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_file_list_without_matches() {
+    let api = PboApi::new(30);
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let temp_dir = TempDir::new().unwrap();
+
+    match api.extract_file_list(test_pbo, temp_dir.path(), &["missing.sqf"]) {
+        Err(PboError::Extraction(ExtractError::NoFiles)) => {}
+        other => panic!("Expected NoFiles error, got {:?}", other),
+    }
+
+    match api.extract_file_list(test_pbo, temp_dir.path(), &["a.paa,b.paa"]) {
+        Err(PboError::ValidationFailed(_)) => {}
+        other => panic!("Expected ValidationFailed error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_fail_if_exists_reports_conflicts() {
+    let api = PboApi::new(30);
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let temp_dir = TempDir::new().unwrap();
+    let content_dir = temp_dir.path().join("tc/mirrorform");
+    fs::create_dir_all(&content_dir).unwrap();
+    fs::write(content_dir.join("logo.paa"), "locally modified").unwrap();
+
+    let options = ExtractOptions {
+        overwrite: OverwritePolicy::FailIfExists,
+        ..ExtractOptions::for_extraction()
+    };
+    match api.extract_with_options(test_pbo, temp_dir.path(), options) {
+        Err(PboError::FileSystem(FileSystemError::AlreadyExists { conflicts, .. })) => {
+            assert_eq!(conflicts, vec!["logo.paa"]);
+        }
+        other => panic!("Expected AlreadyExists error, got {:?}", other),
+    }
+    assert_eq!(fs::read_to_string(content_dir.join("logo.paa")).unwrap(), "locally modified");
+}
@@ -0,0 +1,701 @@
+#![cfg(unix)]
+
+use pbo_tools::core::{NativePboReader, PboApi, PboApiOps, PboConfig};
+use pbo_tools::error::types::PboError;
+use pbo_tools::extract::{DefaultExtractor, ExtractOptions, ExtractorClone};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, UNIX_EPOCH};
+use tempfile::TempDir;
+
+/// Write an executable script standing in for extractpbo
+fn fake_extractpbo(dir: &Path, body: &str) -> PathBuf {
+    fake_tool(dir, "extractpbo", body)
+}
+
+/// Write an executable script named `name`
+fn fake_tool(dir: &Path, name: &str, body: &str) -> PathBuf {
+    fs::create_dir_all(dir).unwrap();
+    let script = dir.join(name);
+    fs::write(&script, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+#[test]
+fn test_extract_with_callback_streams_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "echo 'prefix=tc\\mirrorform;'\necho 'Extracting config.bin...'\necho 'Extracting logo.paa...'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+
+    let mut lines = Vec::new();
+    let result = api.extract_with_callback(
+        Path::new("tests/data/mirrorform.pbo"),
+        &temp_dir.path().join("out"),
+        ExtractOptions::for_extraction(),
+        |line| lines.push(line.to_string()),
+    ).unwrap();
+
+    assert_eq!(lines, vec![
+        "prefix=tc\\mirrorform;",
+        "Extracting config.bin...",
+        "Extracting logo.paa...",
+    ]);
+    assert!(result.is_success());
+    assert_eq!(result.get_file_list(), vec!["config.bin", "logo.paa"]);
+}
+
+#[test]
+fn test_extract_with_callback_timeout() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(temp_dir.path(), "echo 'Extracting config.bin...'\nexec sleep 10");
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_timeout(1)
+        .build();
+
+    let mut lines = 0;
+    let result = api.extract_with_callback(
+        Path::new("tests/data/mirrorform.pbo"),
+        &temp_dir.path().join("out"),
+        ExtractOptions::for_extraction(),
+        |_| lines += 1,
+    );
+
+    assert!(matches!(result, Err(PboError::Timeout(timeout)) if timeout == Duration::from_secs(1)), "Expected timeout, got {:?}", result);
+    assert_eq!(lines, 1);
+}
+
+#[test]
+fn test_extract_timeout_kills_extractpbo() {
+    let temp_dir = TempDir::new().unwrap();
+    let pid_file = temp_dir.path().join("extractpbo.pid");
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        &format!("echo $$ > '{}'\nexec sleep 10", pid_file.display()),
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_timeout(1)
+        .build();
+
+    let result = api.extract_files(
+        Path::new("tests/data/mirrorform.pbo"),
+        &temp_dir.path().join("out"),
+        None,
+    );
+    assert!(matches!(result, Err(PboError::Timeout(timeout)) if timeout == Duration::from_secs(1)), "Expected timeout, got {:?}", result);
+
+    // The process must already be gone when the timeout error is returned
+    let pid = fs::read_to_string(&pid_file).unwrap();
+    let alive = Command::new("kill")
+        .args(["-0", pid.trim()])
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!alive.success(), "extractpbo (pid {}) is still running", pid.trim());
+}
+
+#[test]
+fn test_build_default_uses_extractor_directly() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(temp_dir.path(), "echo 'Extracting config.bin...'");
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build_default();
+
+    let result = api.extract_files(
+        Path::new("tests/data/mirrorform.pbo"),
+        &temp_dir.path().join("out"),
+        None,
+    ).unwrap();
+    assert_eq!(result.get_file_list(), vec!["config.bin"]);
+}
+
+#[test]
+fn test_capture_log_records_raw_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "echo 'Extracting config.bin...'\necho 'Warning: arma pbo is missing a prefix' >&2",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+
+    let log_path = temp_dir.path().join("logs").join("mirrorform.log");
+    let options = ExtractOptions {
+        capture_log: Some(log_path.clone()),
+        ..ExtractOptions::for_extraction()
+    };
+    api.extract_with_options(Path::new("tests/data/mirrorform.pbo"), &temp_dir.path().join("out"), options).unwrap();
+
+    let log = fs::read_to_string(&log_path).unwrap();
+    assert!(log.starts_with("PBO: tests/data/mirrorform.pbo\n"), "Unexpected log:\n{}", log);
+    assert!(log.contains("-PW"));
+    assert!(log.contains("--- stdout ---\nExtracting config.bin...\n"));
+    assert!(log.contains("--- stderr ---\nWarning: arma pbo is missing a prefix\n"));
+}
+
+#[test]
+fn test_rewrite_prefix_after_extraction() {
+    let temp_dir = TempDir::new().unwrap();
+    // The output directory is the last argument
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "for out; do :; done\nmkdir -p \"$out/tc/mirrorform\"\ntouch \"$out/tc/mirrorform/config.cpp\"\necho 'prefix=tc\\mirrorform;'\necho 'Extracting config.cpp...'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+
+    let output_dir = temp_dir.path().join("out");
+    let options = ExtractOptions {
+        rewrite_prefix: Some("my_mod\\addons".to_string()),
+        ..ExtractOptions::for_extraction()
+    };
+    api.extract_with_options(Path::new("tests/data/mirrorform.pbo"), &output_dir, options).unwrap();
+
+    assert!(output_dir.join("my_mod/addons/config.cpp").exists());
+    assert!(!output_dir.join("tc").exists());
+}
+
+#[test]
+fn test_atomic_extraction() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("out");
+    fs::create_dir(&output_dir).unwrap();
+    let options = ExtractOptions {
+        atomic: true,
+        ..ExtractOptions::for_extraction()
+    };
+
+    // A failure midway must not leave partial files in the output directory
+    let failing = fake_extractpbo(
+        temp_dir.path(),
+        "for out; do :; done\ntouch \"$out/config.cpp\"\necho 'Bad Sha detected' >&2\nexit 1",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(failing)
+        .build();
+    assert!(api.extract_with_options(Path::new("tests/data/mirrorform.pbo"), &output_dir, options.clone()).is_err());
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+
+    let succeeding = fake_extractpbo(
+        temp_dir.path(),
+        "for out; do :; done\nmkdir -p \"$out/tc/mirrorform\"\ntouch \"$out/tc/mirrorform/config.cpp\"\necho 'Extracting config.cpp...'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(succeeding)
+        .build();
+    api.extract_with_options(Path::new("tests/data/mirrorform.pbo"), &output_dir, options).unwrap();
+    assert!(output_dir.join("tc/mirrorform/config.cpp").exists());
+}
+
+#[test]
+fn test_extract_with_path_mapper() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "for out; do :; done\nmkdir -p \"$out/tc/mirrorform/Uniform\"\ntouch \"$out/tc/mirrorform/config.cpp\" \"$out/tc/mirrorform/Uniform/Black.paa\" \"$out/tc/mirrorform/Uniform/Mirror.p3d\"\necho 'prefix=tc\\mirrorform;'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+
+    let mut seen = std::cell::RefCell::new(Vec::new());
+    let output_dir = temp_dir.path().join("out");
+    api.extract_with_path_mapper(Path::new("tests/data/mirrorform.pbo"), &output_dir, |path| {
+        seen.borrow_mut().push(path.to_string());
+        path.ends_with(".paa").then(|| PathBuf::from("textures").join(path.to_lowercase().replace('\\', "/")))
+    }).unwrap();
+
+    assert_eq!(seen.get_mut(), &["Uniform\\Black.paa", "Uniform\\Mirror.p3d", "config.cpp"]);
+    assert!(output_dir.join("textures/uniform/black.paa").exists());
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 1);
+
+    // Mapped paths may not leave the output directory
+    let escaping = api.extract_with_path_mapper(Path::new("tests/data/mirrorform.pbo"), &output_dir, |_| {
+        Some(PathBuf::from("../escaped.paa"))
+    });
+    assert!(matches!(escaping, Err(PboError::ValidationFailed(_))));
+    assert!(!temp_dir.path().join("escaped.paa").exists());
+
+    // One bad mapping fails the call before any file is moved
+    let partial_dir = temp_dir.path().join("partial");
+    let partial = api.extract_with_path_mapper(Path::new("tests/data/mirrorform.pbo"), &partial_dir, |path| {
+        Some(PathBuf::from(if path == "config.cpp" { "../config.cpp" } else { "textures/file.paa" }))
+    });
+    assert!(matches!(partial, Err(PboError::ValidationFailed(_))));
+    assert!(!partial_dir.join("textures").exists());
+}
+
+#[test]
+fn test_extraction_metrics() {
+    let temp_dir = TempDir::new().unwrap();
+    // Writes only one of the selected textures, with a size unlike its header entry
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "sleep 0.1\nfor out; do :; done\nmkdir -p \"$out/tc/mirrorform\"\nprintf 12345 > \"$out/tc/mirrorform/logo.paa\"\necho 'prefix=tc\\mirrorform;'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+    let pbo_path = Path::new("tests/data/mirrorform.pbo");
+
+    let options = ExtractOptions {
+        file_filter: Some("*.paa".to_string()),
+        ..ExtractOptions::for_extraction()
+    };
+    let result = api.extract_with_options(pbo_path, &temp_dir.path().join("out"), options.clone()).unwrap();
+    let metrics = result.metrics().unwrap();
+    assert!(metrics.command_duration >= Duration::from_millis(100));
+    assert_eq!(metrics.file_count, 1);
+    assert_eq!(metrics.bytes_extracted, 5);
+
+    // Files are found where the prefix options moved them
+    let options = ExtractOptions { strip_prefix: true, ..options };
+    let result = api.extract_with_options(pbo_path, &temp_dir.path().join("stripped"), options).unwrap();
+    assert!(temp_dir.path().join("stripped/logo.paa").is_file());
+    assert_eq!(result.metrics().unwrap().file_count, 1);
+    assert_eq!(result.metrics().unwrap().bytes_extracted, 5);
+
+    let listing = api.list_contents(pbo_path).unwrap();
+    assert_eq!(listing.metrics().unwrap().file_count, 0);
+}
+
+#[test]
+fn test_preserve_timestamps() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "for out; do :; done\nmkdir -p \"$out/tc/mirrorform\"\ntouch \"$out/tc/mirrorform/config.cpp\" \"$out/tc/mirrorform/logo.paa\"\nchmod 444 \"$out/tc/mirrorform/logo.paa\"\necho 'Extracting config.bin...'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+
+    let output_dir = temp_dir.path().join("out");
+    let options = ExtractOptions {
+        preserve_timestamps: true,
+        ..ExtractOptions::for_extraction()
+    };
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    api.extract_with_options(test_pbo, &output_dir, options).unwrap();
+
+    let reader = NativePboReader::open(test_pbo).unwrap();
+    for (entry, file) in [("config.bin", "config.cpp"), ("logo.paa", "logo.paa")] {
+        let header = reader.header_entries().iter().find(|e| e.path == entry).unwrap();
+        let modified = fs::metadata(output_dir.join("tc/mirrorform").join(file)).unwrap().modified().unwrap();
+        assert_eq!(modified, UNIX_EPOCH + Duration::from_secs(u64::from(header.timestamp)));
+    }
+}
+
+#[test]
+fn test_extractpbo_env() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(temp_dir.path(), "echo \"$PBO_TOOLS_TEST_ENV\"");
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_env("PBO_TOOLS_TEST_ENV", "wine_prefix")
+        .build();
+
+    let result = api.list_contents_brief(Path::new("tests/data/mirrorform.pbo")).unwrap();
+    assert_eq!(result.stdout.trim(), "wine_prefix");
+    assert!(std::env::var_os("PBO_TOOLS_TEST_ENV").is_none());
+}
+
+#[test]
+fn test_extract_under_wine() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(temp_dir.path(), r#"printf '%s\n' "$@" > "$(dirname "$0")/args.txt""#);
+    let wine_dir = temp_dir.path().join("wine");
+    fs::create_dir(&wine_dir).unwrap();
+    let wine = fake_tool(&wine_dir, "wine", r#"program=$1; shift; exec "$program" "$@""#);
+    fake_tool(&wine_dir, "winepath", r#"printf 'Z:%s\n' "$2" | tr / '\\'"#);
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_wine(Some(wine.clone()))
+        .build();
+
+    let pbo_path = Path::new("tests/data/mirrorform.pbo");
+    let output_dir = temp_dir.path().join("out");
+    api.extract_files(pbo_path, &output_dir, None).unwrap();
+
+    let windows_path = |path: &Path| format!("Z:{}", std::path::absolute(path).unwrap().display()).replace('/', "\\");
+    let args = fs::read_to_string(temp_dir.path().join("args.txt")).unwrap();
+    let args: Vec<&str> = args.lines().collect();
+    assert_eq!(
+        args[args.len() - 2..],
+        [windows_path(pbo_path), windows_path(&output_dir.canonicalize().unwrap())],
+    );
+
+    // Failures reported by Wine itself are not mistaken for extractpbo errors
+    fake_tool(&wine_dir, "wine", r#"echo 'wine: cannot find L"C:\\windows\\system32\\extractpbo.exe"' >&2; exit 1"#);
+    assert!(matches!(api.extract_files(pbo_path, &output_dir, None), Err(PboError::Wine(_))));
+
+    let missing = PboApi::builder()
+        .with_wine(Some(wine_dir.join("missing")))
+        .build();
+    assert!(matches!(missing.list_contents(pbo_path), Err(PboError::Wine(_))));
+}
+
+#[test]
+fn test_winepath_timeout() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(temp_dir.path(), "exit 0");
+    let wine_dir = temp_dir.path().join("wine");
+    let wine = fake_tool(&wine_dir, "wine", r#"program=$1; shift; exec "$program" "$@""#);
+    let ran = temp_dir.path().join("winepath.ran");
+    fake_tool(&wine_dir, "winepath", &format!("touch '{}'\nexec sleep 10", ran.display()));
+    let extractor = DefaultExtractor::new()
+        .with_extractpbo_path(script)
+        .with_wine(Some(wine))
+        .with_timeout(Duration::from_secs(1));
+    let pbo_path = Path::new("tests/data/mirrorform.pbo");
+    let output_dir = temp_dir.path().join("out");
+
+    // Previewing the command line does not run winepath
+    let argv = extractor.build_command_line(pbo_path, &output_dir, ExtractOptions::for_extraction()).unwrap();
+    assert_eq!(argv[argv.len() - 2], pbo_path.to_str().unwrap());
+    assert!(!ran.exists());
+
+    let started = std::time::Instant::now();
+    let result = extractor.extract_with_options(pbo_path, &output_dir, ExtractOptions::for_extraction());
+    assert!(matches!(result, Err(PboError::Timeout(_))), "Expected timeout, got {:?}", result);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(ran.exists());
+}
+
+#[test]
+fn test_lenient_api() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "for arg; do case \"$arg\" in -*W*) echo 'this warning is set as an error' >&2; exit 1;; esac; done\necho 'Warning: arma pbo is missing a prefix' >&2\necho 'config.bin'",
+    );
+    let pbo_path = Path::new("tests/data/mirrorform.pbo");
+    let output_dir = temp_dir.path().join("out");
+
+    let strict = PboApi::builder()
+        .with_extractpbo_path(script.clone())
+        .build();
+    assert!(strict.list_contents(pbo_path).is_err());
+
+    let lenient = PboApi::builder()
+        .with_extractpbo_path(script)
+        .lenient(true)
+        .build();
+    let listing = lenient.list_contents(pbo_path).unwrap();
+    assert_eq!(listing.get_warnings(), vec!["Warning: arma pbo is missing a prefix"]);
+    lenient.extract_files(pbo_path, &output_dir, None).unwrap();
+    lenient.extract_with_options(pbo_path, &output_dir, ExtractOptions::for_extraction()).unwrap();
+}
+
+#[test]
+fn test_timeout_per_call() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(temp_dir.path(), "sleep 0.5\necho 'config.bin'");
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_timeout_duration(Duration::from_millis(100))
+        .build();
+    let pbo_path = Path::new("tests/data/mirrorform.pbo");
+    let output_dir = temp_dir.path().join("out");
+
+    assert!(matches!(api.list_contents(pbo_path), Err(PboError::Timeout(_))));
+    api.list_with_options_timeout(pbo_path, ExtractOptions::for_listing(), Duration::from_secs(10)).unwrap();
+    api.extract_with_options_timeout(pbo_path, &output_dir, ExtractOptions::for_extraction(), Duration::from_secs(10)).unwrap();
+
+    // The override only applies to the call it was passed to
+    assert!(matches!(api.extract_files(pbo_path, &output_dir, None), Err(PboError::Timeout(_))));
+}
+
+#[test]
+fn test_default_indicators_fail_validation() {
+    let temp_dir = TempDir::new().unwrap();
+    // Any of BAD_PBO_INDICATORS fails the run, even with a zero exit code
+    let script = fake_extractpbo(temp_dir.path(), "echo 'config.bin'\necho 'Error writing config.cpp'");
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+    assert!(matches!(
+        api.list_contents(Path::new("tests/data/mirrorform.pbo")),
+        Err(PboError::ValidationFailed(_))
+    ));
+}
+
+#[test]
+fn test_retry_locked_pbo() {
+    let temp_dir = TempDir::new().unwrap();
+    // Fails like a PBO held open by another process on the first two runs
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "n=$(($(cat \"$COUNTER\" 2>/dev/null || echo 0) + 1))\necho $n > \"$COUNTER\"\n\
+         if [ $n -le 2 ]; then echo 'Cannot open mirrorform.pbo'; exit 1; fi\necho 'Extracting config.cpp...'",
+    );
+    let counter = temp_dir.path().join("runs");
+    let pbo_path = Path::new("tests/data/mirrorform.pbo");
+    let output_dir = temp_dir.path().join("out");
+
+    let api = PboApi::builder()
+        .with_extractpbo_path(script.clone())
+        .with_env("COUNTER", counter.display().to_string())
+        .build();
+    let result = api.extract_files(pbo_path, &output_dir, None).unwrap();
+    assert_eq!(result.get_file_list(), vec!["config.cpp"]);
+    assert_eq!(fs::read_to_string(&counter).unwrap().trim(), "3");
+
+    fs::remove_file(&counter).unwrap();
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_env("COUNTER", counter.display().to_string())
+        .with_config(PboConfig::builder().max_retries(1).build())
+        .build();
+    assert!(api.extract_files(pbo_path, &output_dir, None).is_err());
+}
+
+#[test]
+fn test_skip_checksum() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "case \"$1\" in *W*) echo 'Bad Sha detected' >&2; exit 1;; esac\necho 'Bad Sha detected'\necho 'Extracting config.bin...'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+
+    assert!(api.extract_with_options(test_pbo, &temp_dir.path().join("strict"), ExtractOptions::for_extraction()).is_err());
+
+    let options = ExtractOptions {
+        skip_checksum: true,
+        ..ExtractOptions::for_extraction()
+    };
+    let result = api.extract_with_options(test_pbo, &temp_dir.path().join("out"), options.clone()).unwrap();
+    assert!(result.is_success());
+    assert!(result.checksum_skipped());
+
+    // Other warnings still fail the extraction without -W
+    let script = fake_extractpbo(
+        &temp_dir.path().join("warn"),
+        "echo 'Bad Sha detected'\necho 'Warning: arma pbo is missing a prefix'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+    assert!(api.extract_with_options(test_pbo, &temp_dir.path().join("warned"), options.clone()).is_err());
+
+    let options = ExtractOptions {
+        warnings_as_errors: false,
+        ..options
+    };
+    assert!(api.extract_with_options(test_pbo, &temp_dir.path().join("lenient"), options).is_ok());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_write_manifest() {
+    let temp_dir = TempDir::new().unwrap();
+    // logo_small.paa is selected but not written, config.bin is written debinarized
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "for out; do :; done\nmkdir -p \"$out/tc/mirrorform\"\nprintf 1234 > \"$out/tc/mirrorform/logo.paa\"\nprintf 'class CfgPatches {};' > \"$out/tc/mirrorform/config.cpp\"\necho 'prefix=tc\\mirrorform;'\necho 'Extracting logo.paa...'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+
+    let manifest_path = temp_dir.path().join("manifests/mirrorform.json");
+    let options = ExtractOptions {
+        file_filter: Some("logo*.paa,config.bin".to_string()),
+        write_manifest: Some(manifest_path.clone()),
+        ..ExtractOptions::for_extraction()
+    };
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    api.extract_with_options(test_pbo, &temp_dir.path().join("out"), options).unwrap();
+
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+    assert_eq!(manifest["pbo"], test_pbo.display().to_string());
+    assert_eq!(manifest["prefix"], "tc\\mirrorform");
+    let mut files: Vec<(String, u64)> = manifest["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| (file["path"].as_str().unwrap().to_string(), file["size"].as_u64().unwrap()))
+        .collect();
+    files.sort();
+    assert_eq!(files, vec![
+        ("tc/mirrorform/config.cpp".to_string(), 20),
+        ("tc/mirrorform/logo.paa".to_string(), 4),
+    ]);
+}
+
+#[test]
+fn test_encrypted_pbo_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let extractpbo = fake_extractpbo(&temp_dir.path().join("bin"), "touch \"$0.ran\"\nexit 1");
+    let api = PboApi::builder().with_extractpbo_path(extractpbo.clone()).build();
+    let expected = "encrypted PBO (.ebo) cannot be extracted";
+
+    let ebo = temp_dir.path().join("mirrorform.ebo");
+    fs::copy("tests/data/mirrorform.pbo", &ebo).unwrap();
+    let result = api.extract_with_options(&ebo, &temp_dir.path().join("out"), ExtractOptions::for_extraction());
+    assert!(matches!(result, Err(PboError::InvalidPbo(ref reason)) if reason == expected), "Got {:?}", result);
+
+    assert!(!extractpbo.with_extension("ran").exists(), "extractpbo should not have run");
+}
+
+#[test]
+fn test_incompatible_extractpbo() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let old = fake_extractpbo(
+        &temp_dir.path().join("old"),
+        "echo 'ExtractPbo Version 1.86, Dll 5.12'\n[ $# -le 1 ] && echo >> \"$0.probes\" && exit 0\necho 'unknown option -LB' >&2\nexit 1",
+    );
+    let probe_log = old.with_extension("probes");
+    let probes = || fs::read_to_string(&probe_log).map(|s| s.lines().count()).unwrap_or(0);
+    let extractor = DefaultExtractor::new().with_extractpbo_path(old.clone());
+    assert_eq!(extractor.tool_version().unwrap(), "1.86");
+    // Without a minimum any version passes
+    assert_eq!(extractor.check_tool_version().unwrap(), "1.86");
+    let extractor = extractor.with_min_tool_version("2.00");
+    assert!(matches!(
+        extractor.check_tool_version(),
+        Err(PboError::IncompatibleTool { ref required, ref found }) if required == "2.00" && found == "1.86"
+    ));
+    assert_eq!(probes(), 1, "The version should be probed once");
+
+    let api = PboApi::builder().with_extractpbo_path(old.clone()).build();
+    let result = api.extract_with_options(test_pbo, &temp_dir.path().join("out"), ExtractOptions::for_extraction());
+    assert!(!matches!(result, Err(PboError::IncompatibleTool { .. })), "Version should not be checked, got {:?}", result);
+    assert_eq!(probes(), 1);
+
+    let api = PboApi::builder().with_extractpbo_path(old).with_min_extractpbo_version("2.00").build();
+    let result = api.extract_with_options(test_pbo, &temp_dir.path().join("out"), ExtractOptions::for_extraction());
+    assert!(matches!(result, Err(PboError::IncompatibleTool { .. })), "Expected incompatible tool, got {:?}", result);
+    let result = api.extract_with_options(test_pbo, &temp_dir.path().join("out"), ExtractOptions::for_extraction());
+    assert!(matches!(result, Err(PboError::IncompatibleTool { .. })));
+    assert_eq!(probes(), 2, "Failures of one API should share the probed version");
+
+    // A failure of a recent enough extractpbo is reported as is
+    let recent = fake_extractpbo(
+        &temp_dir.path().join("recent"),
+        "echo 'ExtractPbo Version 3.31, Dll 8.80'\n[ $# -le 1 ] && exit 0\nexit 1",
+    );
+    let extractor = DefaultExtractor::new().with_extractpbo_path(recent.clone()).with_min_tool_version("2.00");
+    assert_eq!(extractor.check_tool_version().unwrap(), "3.31");
+    let api = PboApi::builder().with_extractpbo_path(recent).with_min_extractpbo_version("2.00").build();
+    let result = api.extract_with_options(test_pbo, &temp_dir.path().join("out"), ExtractOptions::for_extraction());
+    assert!(!matches!(result, Err(PboError::IncompatibleTool { .. })));
+}
+
+#[test]
+fn test_extract_and_hash() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = fake_extractpbo(
+        temp_dir.path(),
+        "for out; do :; done\nmkdir -p \"$out/tc/mirrorform\"\nprintf 'class CfgPatches {};' > \"$out/tc/mirrorform/config.cpp\"\nprintf 'paa' > \"$out/tc/mirrorform/logo.paa\"\necho 'Extracting config.cpp...'\necho 'Extracting logo.paa...'",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .build();
+
+    let output_dir = temp_dir.path().join("out");
+    fs::create_dir(&output_dir).unwrap();
+    fs::write(output_dir.join("existing.txt"), "kept").unwrap();
+    let hashes = api.extract_and_hash(Path::new("tests/data/mirrorform.pbo"), &output_dir).unwrap();
+
+    assert_eq!(hashes.len(), 2);
+    assert_eq!(
+        hex(&hashes["tc\\mirrorform\\config.cpp"]),
+        "f4efde67065d910a5da85a1f822a0f5b35c15b2a0425690d636fae7c6d7240bf"
+    );
+    assert_eq!(
+        hex(&hashes["tc\\mirrorform\\logo.paa"]),
+        "204e1db22bfdc48a931e39b64031a5a700062fde3d9e9144e8fcd321f6bc2d97"
+    );
+    assert_eq!(fs::read_to_string(output_dir.join("tc/mirrorform/config.cpp")).unwrap(), "class CfgPatches {};");
+    assert!(output_dir.join("existing.txt").exists());
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// mirrorform.pbo gzipped into `dir`, and an API whose extractpbo only
+/// extracts config.cpp when it receives the decompressed PBO under a .pbo name
+#[cfg(feature = "compressed")]
+fn compressed_pbo_api(dir: &Path) -> (PboApi, PathBuf) {
+    use std::io::Write;
+
+    let mirrorform = fs::canonicalize("tests/data/mirrorform.pbo").unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&fs::read(&mirrorform).unwrap()).unwrap();
+    let gzip = dir.join("mirrorform.pbo.gz");
+    fs::write(&gzip, encoder.finish().unwrap()).unwrap();
+
+    let script = fake_extractpbo(
+        dir,
+        "for out; do :; done\nfor arg; do case \"$arg\" in *mirrorform.pbo) cmp -s \"$arg\" \"$ORIGINAL\" || exit 0\n\
+         mkdir -p \"$out/tc/mirrorform\" && echo 'class CfgPatches {};' > \"$out/tc/mirrorform/config.cpp\"\n\
+         echo 'Extracting config.cpp...';; esac; done",
+    );
+    let api = PboApi::builder()
+        .with_extractpbo_path(script)
+        .with_env("ORIGINAL", mirrorform.display().to_string())
+        .build();
+    (api, gzip)
+}
+
+#[cfg(feature = "compressed")]
+#[test]
+fn test_extract_compressed_pbo() {
+    let temp_dir = TempDir::new().unwrap();
+    let (api, gzip) = compressed_pbo_api(temp_dir.path());
+
+    let result = api.extract_files(&gzip, &temp_dir.path().join("out"), None).unwrap();
+    assert_eq!(result.get_file_list(), vec!["config.cpp"]);
+
+    // The manifest names the compressed file, not the removed temp copy
+    #[cfg(feature = "serde")]
+    {
+        let manifest = temp_dir.path().join("manifest.json");
+        let options = ExtractOptions {
+            write_manifest: Some(manifest.clone()),
+            ..ExtractOptions::for_extraction()
+        };
+        api.extract_with_options(&gzip, &temp_dir.path().join("out"), options).unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(manifest).unwrap()).unwrap();
+        assert_eq!(manifest["pbo"], serde_json::json!(gzip));
+    }
+}
+
+#[cfg(feature = "compressed")]
+#[test]
+fn test_extract_file_list_compressed_pbo() {
+    let temp_dir = TempDir::new().unwrap();
+    let (api, gzip) = compressed_pbo_api(temp_dir.path());
+
+    let result = api.extract_file_list(&gzip, &temp_dir.path().join("out"), &["config.bin"]).unwrap();
+    assert_eq!(result.get_file_list(), vec!["config.cpp"]);
+    assert_eq!(result.found_files(), ["config.bin"]);
+}
+
+#[cfg(feature = "compressed")]
+#[test]
+fn test_search_compressed_pbo() {
+    let temp_dir = TempDir::new().unwrap();
+    let (api, gzip) = compressed_pbo_api(temp_dir.path());
+
+    assert_eq!(api.search_contents(&gzip, "CfgPatches").unwrap(), vec!["config.bin"]);
+}
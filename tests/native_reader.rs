@@ -0,0 +1,303 @@
+use pbo_tools::core::{PboApi, PboApiOps, PboConfig, NativePboReader};
+use pbo_tools::error::types::{ExtractError, PboError};
+use std::path::Path;
+use tempfile::TempDir;
+use std::fs;
+
+#[test]
+fn test_native_list_contents() {
+    let api = PboApi::builder()
+        .with_native_reader()
+        .build();
+    let test_pbo = Path::new("tests/data/headgear_pumpkin.pbo");
+
+    let result = api.list_contents(test_pbo).unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.get_prefix(), Some("tc\\headgear_pumpkin".to_string()));
+
+    let files = result.get_file_list();
+    assert!(files.contains(&"config.bin".to_string()));
+    assert!(files.iter().any(|f| f.ends_with(".p3d")));
+}
+
+#[test]
+fn test_native_reader_matches_listing() {
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let reader = NativePboReader::open(test_pbo).unwrap();
+
+    let api = PboApi::builder()
+        .with_native_reader()
+        .build();
+    let files = api.list_contents_brief(test_pbo).unwrap().get_file_list();
+
+    assert_eq!(files.len(), reader.entries().len());
+}
+
+#[test]
+fn test_native_list_invalid_pbo() {
+    let temp_dir = TempDir::new().unwrap();
+    let bad_pbo = temp_dir.path().join("bad.pbo");
+    fs::write(&bad_pbo, b"invalid data").unwrap();
+
+    let api = PboApi::builder()
+        .with_native_reader()
+        .build();
+
+    match api.list_contents(&bad_pbo) {
+        Err(PboError::InvalidPbo(msg)) => assert!(msg.contains("bad magic"), "Unexpected message: {}", msg),
+        other => panic!("Expected InvalidPbo error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_native_list_entries_metadata() {
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let api = PboApi::builder()
+        .with_native_reader()
+        .build();
+
+    let entries = api.list_contents(test_pbo).unwrap().get_entries();
+    let expected = NativePboReader::open(test_pbo).unwrap().entries();
+    assert_eq!(entries.len(), expected.len());
+
+    for (entry, header) in entries.iter().zip(expected.iter()) {
+        assert_eq!(entry.path, header.path.replace('\\', "/"));
+        assert_eq!(entry.size, header.size);
+        assert_eq!(entry.timestamp, header.timestamp);
+    }
+}
+
+#[test]
+fn test_native_list_without_writable_temp() {
+    let temp_dir = TempDir::new().unwrap();
+    let missing_base = temp_dir.path().join("read_only");
+
+    let api = PboApi::builder()
+        .with_native_reader()
+        .with_temp_base(missing_base.clone())
+        .build();
+
+    let result = api.list_contents(Path::new("tests/data/mirrorform.pbo")).unwrap();
+    assert!(!result.get_file_list().is_empty());
+    assert!(!missing_base.exists());
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_verify_checksum() {
+    let api = PboApi::new(30);
+    assert!(api.verify_checksum(Path::new("tests/data/mirrorform.pbo")).unwrap());
+    assert!(api.verify_checksum(Path::new("tests/data/headgear_pumpkin.pbo")).unwrap());
+}
+
+#[test]
+fn test_public_hash() {
+    let temp_dir = TempDir::new().unwrap();
+    let api = PboApi::new(30);
+    let mirrorform = Path::new("tests/data/mirrorform.pbo");
+
+    let stored = NativePboReader::open(mirrorform).unwrap().stored_checksum().unwrap();
+    assert_eq!(Some(api.public_hash(mirrorform).unwrap()), stored);
+
+    let data = fs::read(mirrorform).unwrap();
+    let unsigned = temp_dir.path().join("no_sha.pbo");
+    fs::write(&unsigned, &data[..data.len() - 21]).unwrap();
+    assert!(matches!(api.public_hash(&unsigned), Err(PboError::InvalidPbo(_))));
+}
+
+#[test]
+fn test_extract_entry_by_index() {
+    let temp_dir = TempDir::new().unwrap();
+    let api = PboApi::new(30);
+    let mirrorform = Path::new("tests/data/mirrorform.pbo");
+    let mut reader = NativePboReader::open(mirrorform).unwrap();
+    let index = reader.header_entries().iter().position(|e| e.path == "uniform\\mirror.p3d").unwrap();
+
+    let output_dir = temp_dir.path().join("out");
+    let path = api.extract_entry_by_index(mirrorform, index, &output_dir).unwrap();
+    assert_eq!(path, output_dir.join("uniform").join("mirror.p3d"));
+    assert_eq!(fs::read(&path).unwrap(), reader.read_entry("uniform\\mirror.p3d").unwrap());
+
+    let count = reader.header_entries().len();
+    assert!(matches!(
+        api.extract_entry_by_index(mirrorform, count, &output_dir),
+        Err(PboError::Extraction(ExtractError::NoFiles))
+    ));
+}
+
+#[cfg(feature = "compressed")]
+#[test]
+fn test_native_list_compressed() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let mirrorform = Path::new("tests/data/mirrorform.pbo");
+    let data = fs::read(mirrorform).unwrap();
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&data).unwrap();
+    let gzip = temp_dir.path().join("mirrorform.pbo.gz");
+    fs::write(&gzip, encoder.finish().unwrap()).unwrap();
+    // Recognized by content, whatever the extension
+    let zstd = temp_dir.path().join("mirrorform.download");
+    fs::write(&zstd, zstd::encode_all(&data[..], 0).unwrap()).unwrap();
+
+    let api = PboApi::builder()
+        .with_native_reader()
+        .build();
+    let expected = api.list_contents(mirrorform).unwrap().get_file_list();
+    for compressed in [&gzip, &zstd] {
+        let reader = NativePboReader::open(compressed).unwrap();
+        assert_eq!(reader.prefix(), Some("tc\\mirrorform".to_string()));
+        assert_eq!(api.list_contents(compressed).unwrap().get_file_list(), expected);
+    }
+}
+
+#[cfg(feature = "compressed")]
+#[test]
+fn test_compressed_size_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let data = fs::read("tests/data/mirrorform.pbo").unwrap();
+    let zstd = temp_dir.path().join("mirrorform.pbo.zst");
+    fs::write(&zstd, zstd::encode_all(&data[..], 0).unwrap()).unwrap();
+
+    let limit = data.len() as u64 - 1;
+    assert!(matches!(NativePboReader::open_with_limit(&zstd, limit), Err(PboError::ValidationFailed(_))));
+    assert!(NativePboReader::open_with_limit(&zstd, data.len() as u64).is_ok());
+
+    let api = PboApi::builder()
+        .with_native_reader()
+        .with_config(PboConfig::builder().max_decompressed_size(limit).build())
+        .build();
+    assert!(matches!(api.list_contents(&zstd), Err(PboError::ValidationFailed(_))));
+    assert!(matches!(
+        api.extract_metadata_files(&zstd, &temp_dir.path().join("out")),
+        Err(PboError::ValidationFailed(_))
+    ));
+}
+
+#[cfg(not(feature = "compressed"))]
+#[test]
+fn test_compressed_requires_feature() {
+    let temp_dir = TempDir::new().unwrap();
+    let gzip = temp_dir.path().join("mirrorform.pbo.gz");
+    fs::write(&gzip, [0x1F, 0x8B, 0x08, 0x00]).unwrap();
+    assert!(matches!(NativePboReader::open(&gzip), Err(PboError::ValidationFailed(_))));
+}
+
+#[test]
+fn test_native_list_contents_ifa() {
+    let api = PboApi::builder()
+        .with_native_reader()
+        .build();
+    let temp_dir = TempDir::new().unwrap();
+    let test_ifa = temp_dir.path().join("mirrorform.ifa");
+    fs::copy("tests/data/mirrorform.pbo", &test_ifa).unwrap();
+
+    let result = api.list_contents(&test_ifa).unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.get_prefix(), Some("tc\\mirrorform".to_string()));
+    assert!(result.get_file_list().contains(&"logo.paa".to_string()));
+}
+
+#[test]
+fn test_is_binarized() {
+    let api = PboApi::new(30);
+    assert!(api.is_binarized(Path::new("tests/data/mirrorform.pbo")).unwrap());
+    assert!(api.is_binarized(Path::new("tests/data/headgear_pumpkin.pbo")).unwrap());
+    assert!(matches!(
+        api.is_binarized(Path::new("tests/data/missing.pbo")),
+        Err(PboError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn test_entries_iter() {
+    let api = PboApi::new(30);
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+
+    let mut reader = NativePboReader::open(test_pbo).unwrap();
+    let headers = reader.header_entries().to_vec();
+    let entries: Vec<(String, Vec<u8>)> = api.entries_iter(test_pbo).unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(entries.len(), headers.len());
+    for ((path, data), header) in entries.iter().zip(&headers) {
+        assert_eq!(path, &header.path);
+        assert_eq!(data.len() as u64, header.uncompressed_size());
+        assert_eq!(data, &reader.read_entry(path).unwrap());
+    }
+}
+
+#[test]
+fn test_total_size() {
+    let api = PboApi::new(30);
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+
+    let reader = NativePboReader::open(test_pbo).unwrap();
+    let expected: u64 = reader.header_entries().iter().map(|e| e.uncompressed_size()).sum();
+    assert_eq!(api.total_size(test_pbo).unwrap(), expected);
+
+    let temp_dir = TempDir::new().unwrap();
+    let bad_pbo = temp_dir.path().join("bad.pbo");
+    fs::write(&bad_pbo, b"invalid data").unwrap();
+    assert!(matches!(api.total_size(&bad_pbo), Err(PboError::InvalidPbo(_))));
+}
+
+#[test]
+fn test_diff_contents() {
+    let api = PboApi::builder()
+        .with_native_reader()
+        .build();
+    let mirrorform = Path::new("tests/data/mirrorform.pbo");
+    let pumpkin = Path::new("tests/data/headgear_pumpkin.pbo");
+
+    assert!(api.diff_contents(mirrorform, mirrorform).unwrap().is_empty());
+
+    let diff = api.diff_contents(mirrorform, pumpkin).unwrap();
+    let old_files = api.list_contents(mirrorform).unwrap().get_file_list();
+    assert_eq!(diff.removed.len() + diff.common.len(), old_files.len());
+    assert!(diff.common.contains(&"config.bin".to_string()));
+    assert!(diff.removed.contains(&"uniform/mirror.p3d".to_string()));
+}
+
+#[test]
+fn test_contains_file() {
+    let api = PboApi::builder()
+        .with_native_reader()
+        .build();
+    let mirrorform = Path::new("tests/data/mirrorform.pbo");
+
+    assert!(api.contains_file(mirrorform, "config.bin").unwrap());
+    assert!(api.contains_file(mirrorform, "uniform\\mirror.p3d").unwrap());
+    assert!(api.contains_file(mirrorform, "Uniform/Mirror.p3d").unwrap());
+    assert!(!api.contains_file(mirrorform, "uniform/missing.p3d").unwrap());
+
+    let case_sensitive = PboApi::builder()
+        .with_native_reader()
+        .with_config(PboConfig::builder().case_sensitive(true).build())
+        .build();
+    assert!(!case_sensitive.contains_file(mirrorform, "Uniform/Mirror.p3d").unwrap());
+}
+
+#[test]
+fn test_scan_prefixes() {
+    let temp_dir = TempDir::new().unwrap();
+    let mods = temp_dir.path();
+    fs::create_dir_all(mods.join("@mod/addons")).unwrap();
+    fs::copy("tests/data/mirrorform.pbo", mods.join("@mod/addons/mirrorform.pbo")).unwrap();
+    fs::write(mods.join("@mod/addons/broken.pbo"), b"not a pbo").unwrap();
+    fs::write(mods.join("@mod/readme.txt"), b"text").unwrap();
+
+    let api = PboApi::builder()
+        .with_native_reader()
+        .with_concurrency(2)
+        .build();
+    let results = api.scan_prefixes(mods);
+
+    let paths: Vec<&Path> = results.iter().map(|(path, _)| path.as_path()).collect();
+    assert_eq!(paths, vec![mods.join("@mod/addons/broken.pbo"), mods.join("@mod/addons/mirrorform.pbo")]);
+    assert!(results[0].1.is_err());
+    assert_eq!(results[1].1.as_ref().unwrap().as_deref(), Some("tc\\mirrorform"));
+}
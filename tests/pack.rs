@@ -0,0 +1,56 @@
+use pbo_tools::core::PboApi;
+use pbo_tools::error::types::PboError;
+use pbo_tools::pack::{PackOptions, PboPackOps};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+#[test]
+fn test_pack_with_missing_makepbo() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("addon");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("config.cpp"), "class CfgPatches {};").unwrap();
+
+    let api = PboApi::builder()
+        .with_makepbo_path(PathBuf::from("/nonexistent/tools/makepbo"))
+        .build();
+    let options = PackOptions {
+        prefix: Some("tc\\addon".to_string()),
+        ..Default::default()
+    };
+
+    match api.pack(&source, &temp_dir.path().join("addon.pbo"), options) {
+        Err(PboError::CommandNotFound(cmd)) => assert!(cmd.contains("makepbo")),
+        other => panic!("Expected CommandNotFound error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_pack_invalid_source() {
+    let temp_dir = TempDir::new().unwrap();
+    let api = PboApi::new(30);
+
+    let missing = temp_dir.path().join("missing");
+    match api.pack(&missing, &temp_dir.path().join("addon.pbo"), PackOptions::default()) {
+        Err(PboError::InvalidPath(path)) => assert_eq!(path, missing),
+        other => panic!("Expected InvalidPath error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_check_tools_uses_overridden_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let extractpbo = temp_dir.path().join("extractpbo");
+    fs::write(&extractpbo, "").unwrap();
+
+    let api = PboApi::builder()
+        .with_extractpbo_path(extractpbo)
+        .with_makepbo_path(temp_dir.path().join("makepbo"))
+        .build();
+    let tools = api.check_tools();
+
+    assert!(tools.extractpbo);
+    assert!(!tools.makepbo);
+    assert!(!tools.all_available());
+}
@@ -1,5 +1,6 @@
 use pbo_tools::core::{PboApi, PboApiOps};
-use pbo_tools::extract::ExtractOptions;
+use pbo_tools::extract::{ExtractOptions, OutputLayout};
+use pbo_tools::error::types::PboError;
 use std::path::Path;
 use tempfile::TempDir;
 use std::fs;
@@ -244,4 +245,92 @@ fn test_headgear_pumpkin_extract_cpp() {
         !name.contains("config.cpp") && name != "$PBOPREFIX$.txt"
     });
     assert!(!has_other, "No other files should have been extracted");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_extract_file_to_memory() {
+    let (api, _temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+
+    let bytes = api.extract_file_to_memory(test_pbo, "logo.paa").unwrap();
+    assert!(!bytes.is_empty());
+
+    let missing = api.extract_file_to_memory(test_pbo, "missing.sqf");
+    assert!(missing.is_err());
+}
+
+#[test]
+fn test_extract_text_file() {
+    let (api, _temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+
+    let config = api.extract_text_file(test_pbo, "config.cpp").unwrap();
+    assert!(config.contains("CfgPatches"));
+
+    assert!(matches!(
+        api.extract_text_file(test_pbo, "logo.paa"),
+        Err(PboError::Encoding { .. })
+    ));
+}
+
+#[test]
+fn test_extract_with_flattened_prefix() {
+    let (api, temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let output_dir = temp_dir.path().join("flattened");
+
+    let options = ExtractOptions {
+        layout: OutputLayout::FlattenPrefix,
+        ..ExtractOptions::for_extraction()
+    };
+
+    let result = api.extract_with_options(test_pbo, &output_dir, options).unwrap();
+    assert!(result.is_success());
+    assert!(output_dir.join("logo.paa").exists(), "Files should land directly in the output dir");
+    assert!(!output_dir.join("tc").exists(), "Prefix folders should be stripped");
+}
+
+#[test]
+fn test_extract_file_list() {
+    let (api, temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let output_dir = temp_dir.path().join("file_list");
+
+    let result = api.extract_file_list(test_pbo, &output_dir, &["logo.paa", "uniform\\black.paa", "missing.sqf"]).unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.found_files(), ["logo.paa", "uniform\\black.paa"]);
+    assert_eq!(result.missing_files(), ["missing.sqf"]);
+}
+
+#[test]
+fn test_list_contents_ifa() {
+    let (api, temp_dir) = setup();
+    let test_ifa = temp_dir.path().join("mirrorform.ifa");
+    fs::copy("tests/data/mirrorform.pbo", &test_ifa).unwrap();
+
+    let result = api.list_contents(&test_ifa).unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.get_prefix(), Some("tc\\mirrorform".to_string()));
+}
+
+#[test]
+fn test_search_contents() {
+    let (api, _temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+
+    let matches = api.search_contents(test_pbo, "CfgPatches").unwrap();
+    assert_eq!(matches, vec!["config.bin"]);
+    assert!(api.search_contents(test_pbo, "").is_err());
+}
+
+#[test]
+fn test_extract_by_extensions() {
+    let (api, temp_dir) = setup();
+    let test_pbo = Path::new("tests/data/mirrorform.pbo");
+    let output_dir = temp_dir.path().join("by_extension");
+
+    let result = api.extract_by_extensions(test_pbo, &output_dir, &["paa"]).unwrap();
+    assert!(result.is_success());
+    assert!(result.get_file_list().iter().all(|f| f.ends_with(".paa")));
+    assert!(api.extract_by_extensions(test_pbo, &output_dir, &["*.paa"]).is_err());
+}